@@ -20,27 +20,29 @@ fn patch_parameter_provider_dto_properties(schemas: &mut Value) {
 
     let field_schema = schemas
         .get_mut(schema_name)
-        .expect(&format!("FATAL: schema '{}' not to be found", schema_name))
+        .unwrap_or_else(|| panic!("FATAL: schema '{}' not to be found", schema_name))
         .get_mut("properties")
-        .expect(&format!(
-            "FATAL: DTO '{}'.properties not to be found",
-            schema_name
-        ))
+        .unwrap_or_else(|| panic!("FATAL: DTO '{}'.properties not to be found", schema_name))
         .get_mut(field_name)
-        .expect(&format!(
-            "FATAL: El DTO '{}' has no properties called '{}'",
-            schema_name, field_name
-        ));
+        .unwrap_or_else(|| {
+            panic!(
+                "FATAL: El DTO '{}' has no properties called '{}'",
+                schema_name, field_name
+            )
+        });
 
-    let field_obj = field_schema.as_object_mut().expect(&format!(
-        "FATAL: Field '{}' is not a JSON object",
-        field_name
-    ));
+    let field_obj = field_schema
+        .as_object_mut()
+        .unwrap_or_else(|| panic!("FATAL: Field '{}' is not a JSON object", field_name));
     field_obj.insert("nullable".to_string(), json!(true));
-    let add_props = field_obj.get_mut("additionalProperties").expect(&format!(
-        "FATAL: Field '{}' has not 'additionalProperties'. Is it a Map?",
-        field_name
-    ));
+    let add_props = field_obj
+        .get_mut("additionalProperties")
+        .unwrap_or_else(|| {
+            panic!(
+                "FATAL: Field '{}' has not 'additionalProperties'. Is it a Map?",
+                field_name
+            )
+        });
     let add_props_obj = add_props
         .as_object_mut()
         .expect("FATAL: 'additionalProperties' is not a JSON object");
@@ -63,23 +65,24 @@ fn patch_recursive_maps(value: &mut Value) {
     match value {
         Value::Object(map) => {
             let is_string_map = {
-                let has_type_object = map.get("type").map_or(false, |t| t == "object");
+                let has_type_object = map.get("type").is_some_and(|t| t == "object");
 
                 let has_string_values = map
                     .get("additionalProperties")
                     .and_then(|ap| ap.as_object())
                     .and_then(|ap_obj| ap_obj.get("type"))
-                    .map_or(false, |t| t == "string");
+                    .is_some_and(|t| t == "string");
 
                 has_type_object && has_string_values
             };
 
             if is_string_map {
                 map.insert("nullable".to_string(), json!(true));
-                if let Some(add_props) = map.get_mut("additionalProperties") {
-                    if let Some(add_props_obj) = add_props.as_object_mut() {
-                        add_props_obj.insert("type".to_string(), json!(["string", "null"]));
-                    }
+                if let Some(add_props_obj) = map
+                    .get_mut("additionalProperties")
+                    .and_then(|add_props| add_props.as_object_mut())
+                {
+                    add_props_obj.insert("type".to_string(), json!(["string", "null"]));
                 }
             }
 
@@ -113,10 +116,11 @@ fn main() {
     // cast components.schemas to BTreeMap<String, Schema>
     let definitions: BTreeMap<String, Schema> = serde_json::from_value(schemas_value.clone())
         .expect("Not a valid schema or cast impossible");
-    // root schema
-    let mut root_schema = RootSchema::default();
-    // put definitions in root schema
-    root_schema.definitions = definitions;
+    // root schema, with definitions in it
+    let root_schema = RootSchema {
+        definitions,
+        ..Default::default()
+    };
     // using typify, create root
     let mut type_space = TypeSpace::new(TypeSpaceSettings::default().with_struct_builder(true));
     // add root schema to typify (typify creates ast)
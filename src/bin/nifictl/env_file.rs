@@ -0,0 +1,60 @@
+//! Parsing of `.env` style files (`KEY=VALUE` per line) for `params sync`.
+
+use anyhow::bail;
+use std::collections::BTreeMap;
+
+/// Parses the content of an env file.
+///
+/// Blank lines and lines starting with `#` are ignored, an optional `export `
+/// prefix is accepted, and values may be wrapped in single or double quotes.
+///
+/// # Errors
+/// Returns an error naming the first line without a `=` or with an empty key.
+pub fn parse(content: &str) -> anyhow::Result<BTreeMap<String, String>> {
+    let mut values = BTreeMap::new();
+    for (number, line) in content.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let line = line.strip_prefix("export ").unwrap_or(line);
+        let Some((key, value)) = line.split_once('=') else {
+            bail!("line {}: expected KEY=VALUE", number + 1);
+        };
+        let key = key.trim();
+        if key.is_empty() {
+            bail!("line {}: empty key", number + 1);
+        }
+        values.insert(key.to_string(), unquote(value.trim()).to_string());
+    }
+    Ok(values)
+}
+
+fn unquote(value: &str) -> &str {
+    for quote in ['"', '\''] {
+        if let Some(inner) = value
+            .strip_prefix(quote)
+            .and_then(|value| value.strip_suffix(quote))
+        {
+            return inner;
+        }
+    }
+    value
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let values = parse(
+            "# database\nexport DB_URL=\"jdbc:postgresql://db/app\"\n\nDB_USER = app\nEMPTY=\n",
+        )
+        .unwrap();
+        assert_eq!(values["DB_URL"], "jdbc:postgresql://db/app");
+        assert_eq!(values["DB_USER"], "app");
+        assert_eq!(values["EMPTY"], "");
+        assert!(parse("NOT A PAIR").is_err());
+    }
+}
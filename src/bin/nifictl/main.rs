@@ -0,0 +1,828 @@
+//! # nifictl
+//!
+//! Command-line client for NiFi built on the `nifi-rs` services.
+//!
+//! Connection settings come from `Config::load`: defaults, then the config
+//! file (`--config`, `$NIFI_CONFIG` or `~/.config/nifi-rs/config.toml`), then
+//! the `NIFI_*` environment variables, then the command-line flags. `login`
+//! stores the token next to the default config file so later invocations reuse
+//! it.
+//!
+//! Every command prints a table by default, or JSON with `--output json`.
+//! The exit code tells CI what happened (see `ExitCode`).
+//!
+//! With `--state <file>`, `pg import` and `apply` keep the logical id of each
+//! deployed group (and its components) mapped to the UUIDs NiFi assigned, per
+//! `--env`. Importing the same flow again then updates the group instead of
+//! creating a copy, and `plan`/`diff`/`apply` find the group without `--group`.
+
+mod env_file;
+mod output;
+
+use anyhow::{Context, bail};
+use clap::{Args, Parser, Subcommand};
+use nifi_rs::common::client::{HttpClient, HttpClientError};
+use nifi_rs::common::config::{Config, default_config_path};
+use nifi_rs::common::polling::PollOptions;
+use nifi_rs::deploy::diff::{ComponentDiff, DiffOptions, diff_groups};
+use nifi_rs::deploy::hooks::{ChangeAction, DeployEvent, Plan, PlannedChange, Step};
+use nifi_rs::deploy::rollback::{DeployError, Deployment};
+use nifi_rs::deploy::state::{
+    self, ComponentKind, JsonFileStateStore, MemoryStateStore, StateStore,
+};
+use nifi_rs::proxy::v260::access::Access;
+use nifi_rs::proxy::v260::api::{
+    ParameterContextDto, ParameterContextEntity, ParameterDto, ParameterEntity,
+    RegisteredFlowSnapshot,
+};
+use nifi_rs::proxy::v260::flow::Flow;
+use nifi_rs::proxy::v260::parameter_context::ParameterContext;
+use nifi_rs::proxy::v260::process_group::ProcessGroup;
+use nifi_rs::proxy::v260::versions::Versions;
+use output::{OutputFormat, Table, cell, emit};
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Process exit codes, stable so CI pipelines can branch on them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ExitCode {
+    /// The command succeeded (and, with `--detailed-exitcode`, found no changes).
+    Success = 0,
+    /// Any other error.
+    Failure = 1,
+    /// Invalid command line (reported by clap).
+    Usage = 2,
+    /// NiFi rejected the credentials or the token (401/403).
+    Unauthorized = 3,
+    /// `plan`/`diff` with `--detailed-exitcode` found changes, or
+    /// `pg modifications` found a dirty group.
+    ChangesPending = 4,
+    /// `apply` failed and the process group was rolled back.
+    RolledBack = 5,
+}
+
+#[derive(Debug, Parser)]
+#[command(
+    name = "nifictl",
+    version,
+    about = "Manage Apache NiFi flows from the command line"
+)]
+struct Cli {
+    /// TOML config file (defaults to $NIFI_CONFIG or ~/.config/nifi-rs/config.toml).
+    #[arg(long, global = true)]
+    config: Option<PathBuf>,
+
+    /// NiFi API base URL, e.g. https://localhost:8443/nifi-api.
+    #[arg(long, global = true)]
+    url: Option<String>,
+
+    /// Output format.
+    #[arg(long, short, global = true, value_enum, default_value = "table")]
+    output: OutputFormat,
+
+    /// Seconds to wait for asynchronous NiFi requests.
+    #[arg(long, global = true, default_value_t = 120)]
+    timeout: u64,
+
+    /// JSON file mapping logical ids to NiFi UUIDs, read and updated by
+    /// `pg import` and `apply`. Without it, nothing is remembered.
+    #[arg(long, global = true)]
+    state: Option<PathBuf>,
+
+    /// Environment of the state file.
+    #[arg(long, global = true, default_value = "default")]
+    env: String,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// Authenticate and store the access token.
+    Login {
+        #[arg(long)]
+        username: Option<String>,
+        /// Prefer $NIFI_PASSWORD or the config file over this flag.
+        #[arg(long)]
+        password: Option<String>,
+    },
+    /// Invalidate and forget the stored access token.
+    Logout,
+    /// Show the authenticated identity.
+    Whoami,
+    /// Export and import process groups.
+    #[command(subcommand)]
+    Pg(PgCommand),
+    /// Manage parameter contexts.
+    #[command(subcommand)]
+    Params(ParamsCommand),
+    /// Show the changes `apply` would make to a process group.
+    Plan(FlowArgs),
+    /// Show the changes `apply` would make, field by field.
+    Diff(FlowArgs),
+    /// Replace a process group with a flow definition, rolling back on failure.
+    Apply(FlowArgs),
+    /// Show the run status and queues of a process group and its children.
+    Status {
+        /// Process group id.
+        #[arg(long, default_value = "root")]
+        group: String,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+enum PgCommand {
+    /// Download the flow definition of a process group.
+    Export {
+        id: String,
+        /// Write to this file instead of stdout.
+        #[arg(long)]
+        file: Option<PathBuf>,
+    },
+    /// Show the local modifications of a version-controlled process group.
+    /// Exits with 4 when there are any.
+    Modifications { id: String },
+    /// Create a process group from a flow definition file.
+    Import {
+        file: PathBuf,
+        /// Parent process group id.
+        #[arg(long, default_value = "root")]
+        parent: String,
+        /// Name of the new group (defaults to the name in the file).
+        #[arg(long)]
+        name: Option<String>,
+        /// Logical id of the group in the state (defaults to the identifier
+        /// in the file).
+        #[arg(long)]
+        logical_id: Option<String>,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+enum ParamsCommand {
+    /// Set the parameters of a context from an env file.
+    Sync {
+        #[arg(long)]
+        env_file: PathBuf,
+        /// Parameter context name or id.
+        #[arg(long)]
+        context: String,
+        /// Parameters to create as sensitive (repeatable).
+        #[arg(long)]
+        sensitive: Vec<String>,
+        /// Only show what would change.
+        #[arg(long)]
+        dry_run: bool,
+    },
+}
+
+#[derive(Debug, Args)]
+struct FlowArgs {
+    /// Flow definition file, as written by `pg export`.
+    file: PathBuf,
+    /// Process group id to compare against (defaults to the group recorded
+    /// in the state for the logical id).
+    #[arg(long)]
+    group: Option<String>,
+    /// Logical id of the group in the state (defaults to the identifier in
+    /// the file).
+    #[arg(long)]
+    logical_id: Option<String>,
+    /// Exit with 4 when there are changes (plan and diff only).
+    #[arg(long)]
+    detailed_exitcode: bool,
+}
+
+/// Shared state of a command invocation.
+struct Session {
+    client: Arc<HttpClient>,
+    config: Arc<Config>,
+    output: OutputFormat,
+    poll: PollOptions,
+    state: Arc<dyn StateStore>,
+    environment: String,
+}
+
+#[tokio::main]
+async fn main() {
+    let cli = match Cli::try_parse() {
+        Ok(cli) => cli,
+        Err(err) => {
+            let _ = err.print();
+            let code = if err.use_stderr() {
+                ExitCode::Usage
+            } else {
+                ExitCode::Success
+            };
+            std::process::exit(code as i32);
+        },
+    };
+    let code = match run(cli).await {
+        Ok(code) => code,
+        Err(err) => {
+            eprintln!("error: {:#}", err);
+            exit_code(&err)
+        },
+    };
+    std::process::exit(code as i32);
+}
+
+fn exit_code(err: &anyhow::Error) -> ExitCode {
+    for cause in err.chain() {
+        if let Some(DeployError::RolledBack { .. }) = cause.downcast_ref::<DeployError>() {
+            return ExitCode::RolledBack;
+        }
+        if let Some(HttpClientError::HttpError { status, .. }) =
+            cause.downcast_ref::<HttpClientError>()
+            && (status.as_u16() == 401 || status.as_u16() == 403)
+        {
+            return ExitCode::Unauthorized;
+        }
+    }
+    ExitCode::Failure
+}
+
+async fn run(cli: Cli) -> anyhow::Result<ExitCode> {
+    let mut config = Config::load(cli.config.as_deref())?;
+    if let Some(url) = cli.url {
+        config.api_base_url = url.trim_end_matches('/').to_string();
+    }
+    if let Command::Login { username, password } = &cli.command {
+        if let Some(username) = username {
+            config.username = username.clone();
+        }
+        if let Some(password) = password {
+            config.password = password.clone();
+        }
+    } else if config.get_token().is_none()
+        && let Some(path) = token_path()
+        && let Ok(token) = std::fs::read_to_string(path)
+    {
+        config.set_token(Some(token.trim().to_string()));
+    }
+
+    let client = Arc::new(HttpClient::new());
+    if let Some(token) = config.get_token() {
+        client.set_auth_token(token).await?;
+    }
+    let session = Session {
+        client,
+        config: Arc::new(config),
+        output: cli.output,
+        poll: PollOptions {
+            timeout: Duration::from_secs(cli.timeout),
+            ..PollOptions::default()
+        },
+        state: match &cli.state {
+            Some(path) => Arc::new(JsonFileStateStore::new(path)),
+            None => Arc::new(MemoryStateStore::new()),
+        },
+        environment: cli.env.clone(),
+    };
+
+    match cli.command {
+        Command::Login { .. } => login(&session).await,
+        Command::Logout => logout(&session).await,
+        Command::Whoami => whoami(&session).await,
+        Command::Pg(PgCommand::Export { id, file }) => export(&session, &id, file.as_deref()).await,
+        Command::Pg(PgCommand::Modifications { id }) => modifications(&session, &id).await,
+        Command::Pg(PgCommand::Import {
+            file,
+            parent,
+            name,
+            logical_id,
+        }) => {
+            import(
+                &session,
+                &file,
+                &parent,
+                name.as_deref(),
+                logical_id.as_deref(),
+            )
+            .await
+        },
+        Command::Params(ParamsCommand::Sync {
+            env_file,
+            context,
+            sensitive,
+            dry_run,
+        }) => params_sync(&session, &env_file, &context, &sensitive, dry_run).await,
+        Command::Plan(args) => plan_or_diff(&session, &args, false).await,
+        Command::Diff(args) => plan_or_diff(&session, &args, true).await,
+        Command::Apply(args) => apply(&session, &args).await,
+        Command::Status { group } => status(&session, &group).await,
+    }
+}
+
+/// Where `login` stores the token: next to the default config file.
+fn token_path() -> Option<PathBuf> {
+    default_config_path().and_then(|path| path.parent().map(|dir| dir.join("token")))
+}
+
+async fn login(session: &Session) -> anyhow::Result<ExitCode> {
+    let token = Access::new(session.client.clone(), session.config.clone())
+        .get_access_token()
+        .await?;
+    let path = token_path().context("Cannot locate the config directory to store the token")?;
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    std::fs::write(&path, &token)
+        .with_context(|| format!("Cannot write token to {}", path.display()))?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o600))?;
+    }
+    println!(
+        "Logged in to {} as {}",
+        session.config.api_base_url, session.config.username
+    );
+    Ok(ExitCode::Success)
+}
+
+async fn logout(session: &Session) -> anyhow::Result<ExitCode> {
+    let result = Access::new(session.client.clone(), session.config.clone())
+        .logout()
+        .await;
+    if let Some(path) = token_path()
+        && path.exists()
+    {
+        std::fs::remove_file(path)?;
+    }
+    result?;
+    println!("Logged out");
+    Ok(ExitCode::Success)
+}
+
+async fn whoami(session: &Session) -> anyhow::Result<ExitCode> {
+    let user = Flow::new(session.client.clone(), session.config.clone())
+        .get_current_user()
+        .await?;
+    emit(session.output, &user, |user| {
+        let mut table = Table::new(["IDENTITY", "ANONYMOUS", "CAN VERSION FLOWS"]);
+        table.row([
+            cell(user.identity.as_ref()),
+            cell(user.anonymous),
+            cell(user.can_version_flows),
+        ]);
+        table
+    })?;
+    Ok(ExitCode::Success)
+}
+
+async fn export(session: &Session, id: &str, file: Option<&Path>) -> anyhow::Result<ExitCode> {
+    let snapshot = ProcessGroup::new(session.client.clone(), session.config.clone())
+        .download_process_group(id)
+        .await?;
+    let json = serde_json::to_string_pretty(&snapshot)?;
+    match file {
+        Some(file) => {
+            std::fs::write(file, json)
+                .with_context(|| format!("Cannot write {}", file.display()))?;
+            eprintln!("Exported process group {} to {}", id, file.display());
+        },
+        None => println!("{}", json),
+    }
+    Ok(ExitCode::Success)
+}
+
+async fn modifications(session: &Session, id: &str) -> anyhow::Result<ExitCode> {
+    let report = Versions::new(session.client.clone(), session.config.clone())
+        .local_modifications(id)
+        .await?;
+    match session.output {
+        OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&report)?),
+        OutputFormat::Table => print!("{}", report),
+    }
+    Ok(if report.is_clean() {
+        ExitCode::Success
+    } else {
+        ExitCode::ChangesPending
+    })
+}
+
+fn read_snapshot(file: &Path) -> anyhow::Result<RegisteredFlowSnapshot> {
+    let content =
+        std::fs::read_to_string(file).with_context(|| format!("Cannot read {}", file.display()))?;
+    serde_json::from_str(&content)
+        .with_context(|| format!("{} is not a flow definition", file.display()))
+}
+
+/// The logical id of the group a flow definition deploys to: `--logical-id`,
+/// or the identifier (then the name) of the flow in the file.
+fn logical_id(arg: Option<&str>, snapshot: &RegisteredFlowSnapshot) -> anyhow::Result<String> {
+    let flow = snapshot.flow_contents.as_ref();
+    arg.map(str::to_string)
+        .or_else(|| flow.and_then(|flow| flow.identifier.clone()))
+        .or_else(|| flow.and_then(|flow| flow.name.clone()))
+        .context("The flow definition has no identifier; pass --logical-id")
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct GroupRow {
+    id: Option<String>,
+    name: Option<String>,
+    running: Option<i32>,
+    stopped: Option<i32>,
+    invalid: Option<i32>,
+    disabled: Option<i32>,
+    queued: Option<String>,
+}
+
+fn group_table(rows: &[GroupRow]) -> Table {
+    let mut table = Table::new([
+        "NAME", "ID", "RUNNING", "STOPPED", "INVALID", "DISABLED", "QUEUED",
+    ]);
+    for row in rows {
+        table.row([
+            cell(row.name.as_ref()),
+            cell(row.id.as_ref()),
+            cell(row.running),
+            cell(row.stopped),
+            cell(row.invalid),
+            cell(row.disabled),
+            cell(row.queued.as_ref()),
+        ]);
+    }
+    table
+}
+
+fn group_row(group: &nifi_rs::proxy::v260::api::ProcessGroupEntity) -> GroupRow {
+    GroupRow {
+        id: group.id.clone(),
+        name: group.component.as_ref().and_then(|c| c.name.clone()),
+        running: group.running_count,
+        stopped: group.stopped_count,
+        invalid: group.invalid_count,
+        disabled: group.disabled_count,
+        queued: group
+            .status
+            .as_ref()
+            .and_then(|status| status.aggregate_snapshot.as_ref())
+            .and_then(|snapshot| snapshot.queued.clone()),
+    }
+}
+
+async fn import(
+    session: &Session,
+    file: &Path,
+    parent: &str,
+    name: Option<&str>,
+    logical_id_arg: Option<&str>,
+) -> anyhow::Result<ExitCode> {
+    let snapshot = read_snapshot(file)?;
+    let logical_id = logical_id(logical_id_arg, &snapshot)?;
+    let mut env_state = session.state.load(&session.environment).await?;
+    let group = state::import_process_group(
+        &ProcessGroup::new(session.client.clone(), session.config.clone()),
+        &mut env_state,
+        &logical_id,
+        parent,
+        name,
+        &snapshot,
+        session.poll,
+    )
+    .await?;
+    session.state.save(&env_state).await?;
+    emit(session.output, &vec![group_row(&group)], |rows| {
+        group_table(rows)
+    })?;
+    Ok(ExitCode::Success)
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ParameterChange {
+    name: String,
+    action: ChangeAction,
+    sensitive: bool,
+}
+
+async fn params_sync(
+    session: &Session,
+    env_file: &Path,
+    context: &str,
+    sensitive: &[String],
+    dry_run: bool,
+) -> anyhow::Result<ExitCode> {
+    let content = std::fs::read_to_string(env_file)
+        .with_context(|| format!("Cannot read {}", env_file.display()))?;
+    let values = env_file::parse(&content)
+        .with_context(|| format!("Invalid env file {}", env_file.display()))?;
+
+    let service = ParameterContext::new(session.client.clone(), session.config.clone());
+    let contexts = service
+        .get_parameter_contexts()
+        .await?
+        .parameter_contexts
+        .unwrap_or_default();
+    let Some(existing) = contexts.into_iter().find(|entity| {
+        entity.id.as_deref() == Some(context)
+            || entity.component.as_ref().and_then(|c| c.name.as_deref()) == Some(context)
+    }) else {
+        bail!("Parameter context {} not found", context);
+    };
+    let id = existing.id.clone().unwrap_or_default();
+    let current: Vec<ParameterDto> = existing
+        .component
+        .as_ref()
+        .and_then(|component| component.parameters.clone())
+        .unwrap_or_default()
+        .into_iter()
+        .filter_map(|entity| entity.parameter)
+        .collect();
+
+    let mut changes = Vec::new();
+    let mut parameters = Vec::new();
+    for (name, value) in values {
+        let existing = current.iter().find(|p| p.name.as_deref() == Some(&name));
+        let is_sensitive = existing
+            .and_then(|p| p.sensitive)
+            .unwrap_or_else(|| sensitive.contains(&name));
+        let action = match existing {
+            None => ChangeAction::Create,
+            // Sensitive values are masked by NiFi and cannot be compared.
+            Some(p) if is_sensitive || p.value.as_deref() != Some(value.as_str()) => {
+                ChangeAction::Update
+            },
+            Some(_) => continue,
+        };
+        changes.push(ParameterChange {
+            name: name.clone(),
+            action,
+            sensitive: is_sensitive,
+        });
+        parameters.push(ParameterEntity {
+            can_write: None,
+            parameter: Some(ParameterDto {
+                name: Some(name),
+                value: Some(value),
+                sensitive: Some(is_sensitive),
+                description: existing.and_then(|p| p.description.clone()),
+                ..Default::default()
+            }),
+        });
+    }
+
+    if !dry_run && !parameters.is_empty() {
+        service
+            .update_parameter_context(
+                &id,
+                &ParameterContextEntity {
+                    id: Some(id.clone()),
+                    revision: existing.revision.clone(),
+                    component: Some(ParameterContextDto {
+                        id: Some(id.clone()),
+                        parameters: Some(parameters),
+                        ..Default::default()
+                    }),
+                    ..Default::default()
+                },
+                session.poll,
+            )
+            .await?;
+    }
+    emit(session.output, &changes, |changes| {
+        let mut table = Table::new(["PARAMETER", "ACTION", "SENSITIVE"]);
+        for change in changes {
+            table.row([
+                change.name.clone(),
+                format!("{:?}", change.action),
+                change.sensitive.to_string(),
+            ]);
+        }
+        table
+    })?;
+    Ok(ExitCode::Success)
+}
+
+/// A flow definition file compared with the live process group.
+struct Comparison {
+    declared: RegisteredFlowSnapshot,
+    logical_id: String,
+    /// The id of the live process group.
+    group: String,
+    live: RegisteredFlowSnapshot,
+    diffs: Vec<ComponentDiff>,
+}
+
+/// Compares a flow definition file with the live process group, given by
+/// `--group` or resolved through the state.
+async fn compare(session: &Session, args: &FlowArgs) -> anyhow::Result<Comparison> {
+    let declared = read_snapshot(&args.file)?;
+    let logical_id = logical_id(args.logical_id.as_deref(), &declared)?;
+    let group = match &args.group {
+        Some(group) => group.clone(),
+        None => session
+            .state
+            .load(&session.environment)
+            .await?
+            .resolve(&logical_id)
+            .map(str::to_string)
+            .with_context(|| {
+                format!(
+                    "No group recorded for {} in environment {}; pass --group",
+                    logical_id, session.environment
+                )
+            })?,
+    };
+    let live = ProcessGroup::new(session.client.clone(), session.config.clone())
+        .download_process_group(&group)
+        .await?;
+    let diffs = diff_groups(
+        &live.flow_contents.clone().unwrap_or_default(),
+        &declared.flow_contents.clone().unwrap_or_default(),
+        DiffOptions {
+            ignore_position: true,
+            ..DiffOptions::default()
+        },
+    )?
+    .changes;
+    Ok(Comparison {
+        declared,
+        logical_id,
+        group,
+        live,
+        diffs,
+    })
+}
+
+async fn plan_or_diff(
+    session: &Session,
+    args: &FlowArgs,
+    fields: bool,
+) -> anyhow::Result<ExitCode> {
+    let diffs = compare(session, args).await?.diffs;
+    emit(session.output, &diffs, |diffs| {
+        if fields {
+            let mut table = Table::new(["ACTION", "KIND", "NAME", "FIELD", "LIVE", "DECLARED"]);
+            for diff in diffs {
+                let change = &diff.planned_change();
+                if diff.fields.is_empty() {
+                    table.row(change_cells(change, ["", "", ""].map(str::to_string)));
+                }
+                for field in &diff.fields {
+                    table.row(change_cells(
+                        change,
+                        [
+                            field.path.clone(),
+                            field.old.to_string(),
+                            field.new.to_string(),
+                        ],
+                    ));
+                }
+            }
+            table
+        } else {
+            let mut table = Table::new(["ACTION", "KIND", "NAME", "ID"]);
+            for diff in diffs {
+                let change = &diff.planned_change();
+                table.row([
+                    format!("{:?}", change.action),
+                    format!("{:?}", change.kind),
+                    cell(change.name.as_ref()),
+                    cell(change.logical_id.as_ref()),
+                ]);
+            }
+            table
+        }
+    })?;
+    if session.output == OutputFormat::Table {
+        eprintln!("{} change(s)", diffs.len());
+    }
+    Ok(if args.detailed_exitcode && !diffs.is_empty() {
+        ExitCode::ChangesPending
+    } else {
+        ExitCode::Success
+    })
+}
+
+fn change_cells(change: &PlannedChange, rest: [String; 3]) -> [String; 6] {
+    let [field, live, declared] = rest;
+    [
+        format!("{:?}", change.action),
+        format!("{:?}", change.kind),
+        cell(change.name.as_ref()),
+        field,
+        live,
+        declared,
+    ]
+}
+
+async fn apply(session: &Session, args: &FlowArgs) -> anyhow::Result<ExitCode> {
+    let Comparison {
+        declared,
+        logical_id,
+        group,
+        live,
+        diffs,
+    } = compare(session, args).await?;
+    if diffs.is_empty() {
+        eprintln!("No changes");
+        return Ok(ExitCode::Success);
+    }
+
+    let mut deployment = Deployment::new(session.client.clone(), session.config.clone())
+        .with_poll_options(session.poll);
+    // The printer ends once the deployment (the sending side) is dropped.
+    let mut printer = None;
+    if session.output == OutputFormat::Table {
+        let mut events = deployment.events();
+        printer = Some(tokio::spawn(async move {
+            use futures::StreamExt;
+            while let Some(event) = events.next().await {
+                match event {
+                    DeployEvent::ChangeStarted { change } => eprintln!("applying {}", change),
+                    DeployEvent::ChangeFailed { change, error } => {
+                        eprintln!("failed {}: {}", change, error)
+                    },
+                    DeployEvent::RolledBack { report } => eprintln!(
+                        "rolled back {} group(s), {} failed",
+                        report.restored.len(),
+                        report.failed.len()
+                    ),
+                    _ => {},
+                }
+            }
+        }));
+    }
+
+    let process_group = ProcessGroup::new(session.client.clone(), session.config.clone());
+    let service = ProcessGroup::new(session.client.clone(), session.config.clone());
+    let group_id = group.clone();
+    let poll = session.poll;
+    let change = PlannedChange {
+        kind: ComponentKind::ProcessGroup,
+        action: ChangeAction::Update,
+        logical_id: Some(logical_id.clone()),
+        component_id: Some(group.clone()),
+        group_id: Some(group.clone()),
+        name: declared
+            .flow_contents
+            .as_ref()
+            .and_then(|flow| flow.name.clone()),
+    };
+    let deployed = deployment
+        .deploy(&[group_id.as_str()], || async move {
+            let mut plan = Plan::new();
+            plan.push(Step::new(change, move || async move {
+                process_group
+                    .replace_process_group(&group, &declared, poll)
+                    .await?;
+                Ok(())
+            }));
+            Ok(plan)
+        })
+        .await;
+    // Let the printer drain every event before anything else is written.
+    drop(deployment);
+    if let Some(printer) = printer {
+        let _ = printer.await;
+    }
+    deployed?;
+
+    let mut env_state = session.state.load(&session.environment).await?;
+    let deployed = service.download_process_group(&group_id).await?;
+    env_state.record_deployment(
+        &logical_id,
+        live.flow_contents.as_ref(),
+        &deployed.flow_contents.unwrap_or_default(),
+    );
+    session.state.save(&env_state).await?;
+
+    emit(session.output, &diffs, |diffs| {
+        let mut table = Table::new(["ACTION", "KIND", "NAME"]);
+        for diff in diffs {
+            let change = diff.planned_change();
+            table.row([
+                format!("{:?}", change.action),
+                format!("{:?}", change.kind),
+                cell(change.name.as_ref()),
+            ]);
+        }
+        table
+    })?;
+    if session.output == OutputFormat::Table {
+        eprintln!("Applied {} change(s)", diffs.len());
+    }
+    Ok(ExitCode::Success)
+}
+
+async fn status(session: &Session, group: &str) -> anyhow::Result<ExitCode> {
+    let service = ProcessGroup::new(session.client.clone(), session.config.clone());
+    let mut rows = vec![group_row(&service.get_process_group(group).await?)];
+    let flow = service.get_process_group_flow(group).await?;
+    let children = flow
+        .process_group_flow
+        .and_then(|flow| flow.flow)
+        .and_then(|flow| flow.process_groups)
+        .unwrap_or_default();
+    rows.extend(children.iter().map(group_row));
+    emit(session.output, &rows, |rows| group_table(rows))?;
+    Ok(ExitCode::Success)
+}
@@ -4,6 +4,9 @@
 //! that internally manages authentication state.
 
 use async_trait::async_trait;
+use bytes::Bytes;
+use futures::StreamExt;
+use futures::stream::BoxStream;
 use serde::Serialize;
 use serde::de::DeserializeOwned;
 use std::sync::Arc;
@@ -11,6 +14,12 @@ use std::time::Duration;
 use thiserror::Error;
 use tokio::sync::RwLock;
 
+/// The time limit of a whole request made through `HttpClient::get_stream`.
+///
+/// Streamed bodies (FlowFile or provenance content) can take far longer to
+/// download than the 30 seconds every other request is allowed.
+pub const STREAM_TIMEOUT: Duration = Duration::from_secs(60 * 60);
+
 /// A cloneable, async, and state-aware HTTP client for making API requests.
 ///
 /// This client wraps a `reqwest::Client` and is designed to be safely shared
@@ -70,6 +79,9 @@ impl From<reqwest::Error> for HttpClientError {
     }
 }
 
+/// A response body read chunk by chunk, as returned by `HttpClient::get_stream`.
+pub type ByteStream = BoxStream<'static, Result<Bytes, HttpClientError>>;
+
 /// A newtype wrapper to indicate that a response should be deserialized as JSON.
 ///
 /// This is used in generic trait bounds to disambiguate, for example:
@@ -124,6 +136,12 @@ where
     }
 }
 
+impl Default for HttpClient {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl HttpClient {
     /// Creates a new `HttpClient` with default settings.
     ///
@@ -249,6 +267,38 @@ impl HttpClient {
         Self::deserialize_json_response(response).await
     }
 
+    /// Performs a `GET` request and returns the response body as text.
+    ///
+    /// # Errors
+    /// Returns `HttpClientError` on network, HTTP, or body read failure.
+    pub async fn get_text(&self, url: &str) -> anyhow::Result<String, HttpClientError> {
+        let builder = self.client.get(url);
+        let response = self.execute_request(builder).await?;
+        String::from_response(response).await
+    }
+
+    /// Performs a `GET` request and returns the response body as a stream of
+    /// chunks, so large bodies are never fully buffered in memory.
+    ///
+    /// The request, body included, is limited by `STREAM_TIMEOUT` instead of
+    /// the client's default timeout.
+    ///
+    /// # Errors
+    /// Returns `HttpClientError` on network or HTTP failure; errors while
+    /// reading the body (a timeout included) are yielded by the stream.
+    pub async fn get_stream(&self, url: &str) -> anyhow::Result<ByteStream, HttpClientError> {
+        let builder = self.client.get(url).timeout(STREAM_TIMEOUT);
+        let response = self.execute_request(builder).await?;
+        let stream = futures::stream::try_unfold(response, |mut response| async move {
+            let chunk = response
+                .chunk()
+                .await
+                .map_err(HttpClientError::BodyReadError)?;
+            Ok(chunk.map(|chunk| (chunk, response)))
+        });
+        Ok(stream.boxed())
+    }
+
     /// Performs a `POST` request with a JSON payload and deserializes the response as JSON.
     ///
     /// `T` is the payload type (must be `Serialize`).
@@ -270,6 +320,22 @@ impl HttpClient {
         Self::deserialize_json_response(response).await
     }
 
+    /// Performs a `POST` request without a body and deserializes the response as JSON.
+    ///
+    /// Used by endpoints that create a resource from the URL alone (e.g. queue
+    /// listing and drop requests).
+    ///
+    /// # Errors
+    /// Returns `HttpClientError` on network, HTTP, or parsing failure.
+    pub async fn post_empty<R>(&self, url: &str) -> anyhow::Result<R, HttpClientError>
+    where
+        R: DeserializeOwned,
+    {
+        let builder = self.client.post(url);
+        let response = self.execute_request(builder).await?;
+        Self::deserialize_json_response(response).await
+    }
+
     /// Performs a `PUT` request with a JSON payload and deserializes the response as JSON.
     ///
     /// (Identical to `post_json`, but uses `PUT`).
@@ -1,8 +1,19 @@
 #![allow(warnings)]
+use anyhow::Context;
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+
+/// Environment variables read by `Config::load`, on top of the config file.
+pub const ENV_API_BASE_URL: &str = "NIFI_API_BASE_URL";
+pub const ENV_USERNAME: &str = "NIFI_USERNAME";
+pub const ENV_PASSWORD: &str = "NIFI_PASSWORD";
+pub const ENV_TOKEN: &str = "NIFI_TOKEN";
+/// Overrides the location of the config file.
+pub const ENV_CONFIG_FILE: &str = "NIFI_CONFIG";
+
 /// https://nifi.apache.org/docs/nifi-docs/html/administration-guide.html
 ///
-
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Config {
     pub port_configuration: PortConfiguration,
     pub api_base_url: String,
@@ -11,7 +22,7 @@ pub struct Config {
     pub(crate) token: Option<String>,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct PortConfiguration {
     pub web_https_port: u16,                     // nifi.web.https.port
     pub remote_input_socket_port: Option<u16>,   // nifi.remote.input.socket.port
@@ -46,7 +57,89 @@ impl Default for Config {
     }
 }
 
+/// The optional settings of a TOML config file. Missing keys keep the value
+/// of the layer below.
+///
+/// ```toml
+/// api_base_url = "https://nifi.example.com:8443/nifi-api"
+/// username = "deployer"
+/// password = "..."
+/// ```
+#[derive(Debug, Default, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ConfigFile {
+    pub api_base_url: Option<String>,
+    pub username: Option<String>,
+    pub password: Option<String>,
+    pub token: Option<String>,
+}
+
+impl ConfigFile {
+    /// Reads and parses a TOML config file.
+    pub fn read(path: &Path) -> anyhow::Result<Self> {
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("Cannot read config file {}", path.display()))?;
+        toml::from_str(&content).with_context(|| format!("Invalid config file {}", path.display()))
+    }
+}
+
 impl Config {
+    /// Builds the configuration from three layers, each overriding the previous:
+    ///
+    /// 1. `Config::default()`.
+    /// 2. The config file: `path` if given, else `$NIFI_CONFIG`, else
+    ///    `default_config_path()` when it exists.
+    /// 3. The `NIFI_API_BASE_URL`, `NIFI_USERNAME`, `NIFI_PASSWORD` and
+    ///    `NIFI_TOKEN` environment variables.
+    ///
+    /// # Errors
+    /// Returns an error if an explicitly requested config file cannot be read,
+    /// or if any config file is not valid.
+    pub fn load(path: Option<&Path>) -> anyhow::Result<Self> {
+        let explicit = path
+            .map(Path::to_path_buf)
+            .or_else(|| std::env::var_os(ENV_CONFIG_FILE).map(PathBuf::from));
+        let file = match explicit {
+            Some(path) => Some(ConfigFile::read(&path)?),
+            None => match default_config_path() {
+                Some(path) if path.exists() => Some(ConfigFile::read(&path)?),
+                _ => None,
+            },
+        };
+        Ok(Self::from_layers(file, |key| std::env::var(key).ok()))
+    }
+
+    /// Merges a config file and an environment (any `key -> value` lookup)
+    /// over the defaults. See `load`.
+    pub fn from_layers(file: Option<ConfigFile>, env: impl Fn(&str) -> Option<String>) -> Self {
+        let mut config = Self::default();
+        if let Some(file) = file {
+            config.apply(file);
+        }
+        config.apply(ConfigFile {
+            api_base_url: env(ENV_API_BASE_URL),
+            username: env(ENV_USERNAME),
+            password: env(ENV_PASSWORD),
+            token: env(ENV_TOKEN),
+        });
+        config
+    }
+
+    fn apply(&mut self, layer: ConfigFile) {
+        if let Some(api_base_url) = layer.api_base_url {
+            self.api_base_url = api_base_url.trim_end_matches('/').to_string();
+        }
+        if let Some(username) = layer.username {
+            self.username = username;
+        }
+        if let Some(password) = layer.password {
+            self.password = password;
+        }
+        if layer.token.is_some() {
+            self.token = layer.token;
+        }
+    }
+
     pub fn get_token(&self) -> Option<String> {
         self.token.clone()
     }
@@ -55,3 +148,38 @@ impl Config {
         self.token.clone()
     }
 }
+
+/// `$XDG_CONFIG_HOME/nifi-rs/config.toml`, falling back to
+/// `$HOME/.config/nifi-rs/config.toml`.
+pub fn default_config_path() -> Option<PathBuf> {
+    let base = std::env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+    Some(base.join("nifi-rs").join("config.toml"))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_config_layers() {
+        let file: ConfigFile = toml::from_str(
+            r#"
+            api_base_url = "https://nifi.example.com/nifi-api/"
+            username = "deployer"
+            password = "from-file"
+            "#,
+        )
+        .unwrap();
+        let env: HashMap<&str, &str> = [(ENV_PASSWORD, "from-env"), (ENV_TOKEN, "jwt")].into();
+
+        let config = Config::from_layers(Some(file), |key| env.get(key).map(|v| v.to_string()));
+
+        assert_eq!(config.api_base_url, "https://nifi.example.com/nifi-api");
+        assert_eq!(config.username, "deployer");
+        assert_eq!(config.password, "from-env");
+        assert_eq!(config.get_token().as_deref(), Some("jwt"));
+    }
+}
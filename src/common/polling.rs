@@ -0,0 +1,240 @@
+//! # Polling Module
+//!
+//! Many NiFi operations are asynchronous: the API accepts a request and the
+//! caller has to poll until the server reports it as finished (update-requests,
+//! drop-requests, controller services going from `ENABLING` to `ENABLED`...).
+//!
+//! `poll_until` wraps that loop with a timeout and a fixed interval.
+//! `run_async_request` builds the usual cycle on top of it: submit a request,
+//! poll it until NiFi reports it finished, and delete it in every case.
+
+use anyhow::bail;
+use reqwest::Url;
+use std::future::Future;
+use std::time::Duration;
+use thiserror::Error;
+use tokio::time::Instant;
+
+/// How long and how often to poll.
+#[derive(Debug, Clone, Copy)]
+pub struct PollOptions {
+    /// Maximum time to wait before giving up.
+    pub timeout: Duration,
+    /// Pause between two consecutive checks.
+    pub interval: Duration,
+}
+
+impl Default for PollOptions {
+    fn default() -> Self {
+        Self {
+            timeout: Duration::from_secs(60),
+            interval: Duration::from_millis(500),
+        }
+    }
+}
+
+/// Represents the ways a polling loop can end without a result.
+#[derive(Debug, Error)]
+pub enum PollError {
+    /// The condition was not met before the timeout expired.
+    #[error("PollError::Timeout - condition not met after {0:?}")]
+    Timeout(Duration),
+
+    /// A check failed (e.g. the request could not be fetched).
+    #[error("PollError::Failed - {0}")]
+    Failed(#[from] anyhow::Error),
+}
+
+/// Calls `check` until it yields `Some(value)`, an error, or the timeout expires.
+///
+/// `check` is always called at least once, even with a zero timeout.
+///
+/// # Errors
+///
+/// Returns `PollError::Timeout` if the condition is not met in time, or
+/// `PollError::Failed` with the first error returned by `check`.
+pub async fn poll_until<T, F, Fut>(options: PollOptions, mut check: F) -> Result<T, PollError>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = anyhow::Result<Option<T>>>,
+{
+    let deadline = Instant::now() + options.timeout;
+    loop {
+        if let Some(value) = check().await? {
+            return Ok(value);
+        }
+        if Instant::now() >= deadline {
+            return Err(PollError::Timeout(options.timeout));
+        }
+        tokio::time::sleep(options.interval).await;
+    }
+}
+
+/// The progress of an asynchronous request, as reported by NiFi.
+pub trait RequestStatus {
+    /// `true` once NiFi is done with the request, successfully or not.
+    fn is_finished(&self) -> bool;
+
+    /// Why the request failed, if it did.
+    fn failure_reason(&self) -> Option<&str> {
+        None
+    }
+}
+
+/// Runs an asynchronous request to completion.
+///
+/// `submit` yields the id of the submitted request. `check` fetches the
+/// request by id until it is finished; `delete` is then called with the id,
+/// even when polling failed. `what` names the request in error messages.
+///
+/// # Errors
+/// Returns an error if `submit` or `check` fails, if the request has no id,
+/// if it does not finish within `poll.timeout`, or if NiFi reports a failure
+/// reason.
+pub async fn run_async_request<T, Check, CheckFut, Delete, DeleteFut, D>(
+    what: &str,
+    poll: PollOptions,
+    submit: impl Future<Output = anyhow::Result<Option<String>>>,
+    mut check: Check,
+    delete: Delete,
+) -> anyhow::Result<T>
+where
+    T: RequestStatus,
+    Check: FnMut(String) -> CheckFut,
+    CheckFut: Future<Output = anyhow::Result<T>>,
+    Delete: FnOnce(String) -> DeleteFut,
+    DeleteFut: Future<Output = anyhow::Result<D>>,
+{
+    let Some(request_id) = submit.await? else {
+        bail!("{} has no id", what);
+    };
+
+    let finished = poll_until(poll, || {
+        let request = check(request_id.clone());
+        async move {
+            let request = request.await?;
+            Ok(request.is_finished().then_some(request))
+        }
+    })
+    .await;
+    // Always clean up, even when polling failed.
+    let _ = delete(request_id).await;
+
+    let request = finished?;
+    if let Some(reason) = request.failure_reason() {
+        bail!("{} failed: {}", what, reason);
+    }
+    Ok(request)
+}
+
+/// Appends `?clusterNodeId=` to `url` when a node is given.
+pub(crate) fn with_node(url: String, cluster_node_id: Option<&str>) -> anyhow::Result<String> {
+    match cluster_node_id {
+        Some(node) => Ok(Url::parse_with_params(&url, [("clusterNodeId", node)])?.to_string()),
+        None => Ok(url),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::sync::Mutex;
+
+    struct Request {
+        finished: bool,
+        failure_reason: Option<String>,
+    }
+
+    impl RequestStatus for Request {
+        fn is_finished(&self) -> bool {
+            self.finished
+        }
+
+        fn failure_reason(&self) -> Option<&str> {
+            self.failure_reason.as_deref()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_poll_until_returns_value() {
+        let mut calls = 0;
+        let result = poll_until(
+            PollOptions {
+                timeout: Duration::from_secs(5),
+                interval: Duration::from_millis(1),
+            },
+            || {
+                calls += 1;
+                let done = calls == 3;
+                async move { Ok(done.then_some("finished")) }
+            },
+        )
+        .await;
+        assert_eq!(result.unwrap(), "finished");
+        assert_eq!(calls, 3);
+    }
+
+    #[tokio::test]
+    async fn test_poll_until_times_out() {
+        let result: Result<(), PollError> = poll_until(
+            PollOptions {
+                timeout: Duration::from_millis(10),
+                interval: Duration::from_millis(2),
+            },
+            || async { Ok(None) },
+        )
+        .await;
+        assert!(matches!(result, Err(PollError::Timeout(_))));
+    }
+
+    #[tokio::test]
+    async fn test_run_async_request_always_deletes() {
+        let poll = PollOptions {
+            timeout: Duration::from_millis(10),
+            interval: Duration::from_millis(2),
+        };
+        let deleted = Mutex::new(Vec::new());
+        let delete = |id: String| {
+            deleted.lock().unwrap().push(id);
+            async { Ok(()) }
+        };
+
+        // --- 1. A request that never finishes times out ---
+        let result = run_async_request(
+            "Pending request",
+            poll,
+            async { Ok(Some("pending".to_string())) },
+            |_| async {
+                Ok(Request {
+                    finished: false,
+                    failure_reason: None,
+                })
+            },
+            delete,
+        )
+        .await;
+        assert!(result.is_err());
+
+        // --- 2. A finished request with a failure reason is an error ---
+        let result = run_async_request(
+            "Failing request",
+            poll,
+            async { Ok(Some("failing".to_string())) },
+            |_| async {
+                Ok(Request {
+                    finished: true,
+                    failure_reason: Some("component is running".to_string()),
+                })
+            },
+            delete,
+        )
+        .await;
+        assert!(result.is_err());
+
+        // --- 3. Both were deleted ---
+        assert_eq!(
+            *deleted.lock().unwrap(),
+            vec!["pending".to_string(), "failing".to_string()]
+        );
+    }
+}
@@ -0,0 +1,352 @@
+//! # Prometheus Module
+//!
+//! A parser for the Prometheus text exposition format, as served by
+//! `/flow/metrics/prometheus`.
+//!
+//! The text is grouped into `MetricFamily`s (one per `# TYPE` line, or per
+//! sample name when untyped), each holding its samples and their labels.
+//! NiFi labels component samples with `component_id` and `component_name`,
+//! which `Metrics::for_component_id` and `Metrics::for_component_name` use.
+//!
+//! This module has no dependency on the NiFi API types.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use thiserror::Error;
+
+/// The label NiFi puts the component id in.
+pub const COMPONENT_ID_LABEL: &str = "component_id";
+/// The label NiFi puts the component name in.
+pub const COMPONENT_NAME_LABEL: &str = "component_name";
+
+/// Represents the ways exposition text can be malformed.
+#[derive(Debug, Error, PartialEq)]
+pub enum ParseError {
+    /// A sample line could not be parsed.
+    #[error("ParseError::InvalidSample - line {line}: {message}")]
+    InvalidSample { line: usize, message: String },
+}
+
+/// The type declared by a `# TYPE` line.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MetricType {
+    Counter,
+    Gauge,
+    Histogram,
+    Summary,
+    Untyped,
+}
+
+impl MetricType {
+    fn parse(value: &str) -> Self {
+        match value {
+            "counter" => MetricType::Counter,
+            "gauge" => MetricType::Gauge,
+            "histogram" => MetricType::Histogram,
+            "summary" => MetricType::Summary,
+            _ => MetricType::Untyped,
+        }
+    }
+}
+
+/// A single sample line.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Sample {
+    /// The sample name; differs from the family name for histogram and
+    /// summary samples (`_bucket`, `_sum`, `_count`).
+    pub name: String,
+    pub labels: BTreeMap<String, String>,
+    pub value: f64,
+    /// Milliseconds since the epoch, when given.
+    pub timestamp: Option<i64>,
+}
+
+impl Sample {
+    pub fn label(&self, name: &str) -> Option<&str> {
+        self.labels.get(name).map(String::as_str)
+    }
+}
+
+/// The samples sharing a name (and its `_bucket`, `_sum`... variants).
+#[derive(Debug, Clone, PartialEq)]
+pub struct MetricFamily {
+    pub name: String,
+    pub help: Option<String>,
+    pub metric_type: MetricType,
+    pub samples: Vec<Sample>,
+}
+
+/// A parsed exposition.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Metrics {
+    pub families: Vec<MetricFamily>,
+}
+
+impl Metrics {
+    /// Parses exposition text.
+    ///
+    /// # Errors
+    /// Returns `ParseError` on the first sample line that cannot be parsed.
+    /// Unknown comment lines are ignored.
+    pub fn parse(text: &str) -> Result<Self, ParseError> {
+        let mut metrics = Metrics::default();
+        for (index, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+            if let Some(comment) = line.strip_prefix('#') {
+                metrics.parse_comment(comment.trim_start());
+                continue;
+            }
+            let sample = parse_sample(line).map_err(|message| ParseError::InvalidSample {
+                line: index + 1,
+                message,
+            })?;
+            metrics.family_for(&sample.name).samples.push(sample);
+        }
+        Ok(metrics)
+    }
+
+    pub fn family(&self, name: &str) -> Option<&MetricFamily> {
+        self.families.iter().find(|family| family.name == name)
+    }
+
+    /// Every sample, across families.
+    pub fn samples(&self) -> impl Iterator<Item = &Sample> {
+        self.families
+            .iter()
+            .flat_map(|family| family.samples.iter())
+    }
+
+    /// The samples carrying a given label value.
+    pub fn with_label<'a>(
+        &'a self,
+        label: &'a str,
+        value: &'a str,
+    ) -> impl Iterator<Item = &'a Sample> {
+        self.samples()
+            .filter(move |sample| sample.label(label) == Some(value))
+    }
+
+    /// The samples of a component, by id.
+    pub fn for_component_id<'a>(&'a self, id: &'a str) -> impl Iterator<Item = &'a Sample> {
+        self.with_label(COMPONENT_ID_LABEL, id)
+    }
+
+    /// The samples of the components with a given name.
+    pub fn for_component_name<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a Sample> {
+        self.with_label(COMPONENT_NAME_LABEL, name)
+    }
+
+    /// The value of a sample of a component, by sample name and component id.
+    pub fn value(&self, sample_name: &str, component_id: &str) -> Option<f64> {
+        self.for_component_id(component_id)
+            .find(|sample| sample.name == sample_name)
+            .map(|sample| sample.value)
+    }
+
+    fn parse_comment(&mut self, comment: &str) {
+        let mut parts = comment.splitn(3, ' ');
+        let (Some(keyword), Some(name)) = (parts.next(), parts.next()) else {
+            return;
+        };
+        let rest = parts.next().unwrap_or("").trim();
+        match keyword {
+            "HELP" => self.declared(name).help = Some(unescape(rest)),
+            "TYPE" => self.declared(name).metric_type = MetricType::parse(rest),
+            _ => {},
+        }
+    }
+
+    /// The family declared by a `# HELP` or `# TYPE` line, created if needed.
+    fn declared(&mut self, name: &str) -> &mut MetricFamily {
+        match self.families.iter().position(|family| family.name == name) {
+            Some(index) => &mut self.families[index],
+            None => self.push(name),
+        }
+    }
+
+    /// The family a sample belongs to: the last family whose name the
+    /// sample name equals, or extends with a histogram/summary suffix.
+    fn family_for(&mut self, sample_name: &str) -> &mut MetricFamily {
+        let position = self.families.iter().rposition(|family| {
+            sample_name == family.name
+                || ["_bucket", "_sum", "_count", "_total", "_created"]
+                    .iter()
+                    .any(|suffix| sample_name.strip_suffix(suffix) == Some(&family.name))
+        });
+        match position {
+            Some(index) => &mut self.families[index],
+            None => self.push(sample_name),
+        }
+    }
+
+    fn push(&mut self, name: &str) -> &mut MetricFamily {
+        self.families.push(MetricFamily {
+            name: name.to_string(),
+            help: None,
+            metric_type: MetricType::Untyped,
+            samples: Vec::new(),
+        });
+        self.families.last_mut().expect("just pushed")
+    }
+}
+
+impl fmt::Display for MetricType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            MetricType::Counter => "counter",
+            MetricType::Gauge => "gauge",
+            MetricType::Histogram => "histogram",
+            MetricType::Summary => "summary",
+            MetricType::Untyped => "untyped",
+        };
+        f.write_str(name)
+    }
+}
+
+/// Parses `name{label="value",...} value [timestamp]`.
+fn parse_sample(line: &str) -> Result<Sample, String> {
+    let name_end = line
+        .find(|c: char| c == '{' || c.is_whitespace())
+        .ok_or("missing value")?;
+    let name = line[..name_end].to_string();
+    let mut rest = &line[name_end..];
+
+    let mut labels = BTreeMap::new();
+    if let Some(after_brace) = rest.strip_prefix('{') {
+        let (parsed, remaining) = parse_labels(after_brace)?;
+        labels = parsed;
+        rest = remaining;
+    }
+
+    let mut fields = rest.split_whitespace();
+    let value = fields.next().ok_or("missing value")?;
+    let value = parse_value(value).ok_or_else(|| format!("invalid value '{}'", value))?;
+    let timestamp = match fields.next() {
+        Some(timestamp) => Some(
+            timestamp
+                .parse::<i64>()
+                .map_err(|_| format!("invalid timestamp '{}'", timestamp))?,
+        ),
+        None => None,
+    };
+    Ok(Sample {
+        name,
+        labels,
+        value,
+        timestamp,
+    })
+}
+
+/// Parses labels up to the closing brace (a trailing comma is allowed, as
+/// NiFi emits one) and returns them with the text after the brace.
+fn parse_labels(text: &str) -> Result<(BTreeMap<String, String>, &str), String> {
+    let mut labels = BTreeMap::new();
+    let mut rest = text.trim_start();
+    loop {
+        if let Some(after) = rest.strip_prefix('}') {
+            return Ok((labels, after));
+        }
+        let eq = rest.find('=').ok_or("unterminated labels")?;
+        let name = rest[..eq].trim().to_string();
+        let after_eq = rest[eq + 1..].trim_start();
+        let quoted = after_eq
+            .strip_prefix('"')
+            .ok_or_else(|| format!("unquoted value for label '{}'", name))?;
+
+        let mut value = String::new();
+        let mut chars = quoted.char_indices();
+        let end = loop {
+            match chars.next() {
+                Some((index, '"')) => break index,
+                Some((_, '\\')) => match chars.next() {
+                    Some((_, 'n')) => value.push('\n'),
+                    Some((_, other)) => value.push(other),
+                    None => return Err("unterminated label value".to_string()),
+                },
+                Some((_, other)) => value.push(other),
+                None => return Err("unterminated label value".to_string()),
+            }
+        };
+        labels.insert(name, value);
+
+        rest = quoted[end + 1..].trim_start();
+        if let Some(after_comma) = rest.strip_prefix(',') {
+            rest = after_comma.trim_start();
+        }
+    }
+}
+
+fn parse_value(value: &str) -> Option<f64> {
+    match value {
+        "+Inf" | "Inf" => Some(f64::INFINITY),
+        "-Inf" => Some(f64::NEG_INFINITY),
+        "NaN" => Some(f64::NAN),
+        _ => value.parse().ok(),
+    }
+}
+
+/// Unescapes `\\` and `\n` in `# HELP` texts.
+fn unescape(text: &str) -> String {
+    text.replace("\\n", "\n").replace("\\\\", "\\")
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const FIXTURE: &str = include_str!("testdata/flow_metrics.prom");
+
+    #[test]
+    fn test_parse_fixture() {
+        let metrics = Metrics::parse(FIXTURE).unwrap();
+
+        let received = metrics.family("nifi_amount_flowfiles_received").unwrap();
+        assert_eq!(received.metric_type, MetricType::Gauge);
+        assert_eq!(
+            received.help.as_deref(),
+            Some("Total number of FlowFiles received by the component")
+        );
+        assert_eq!(received.samples.len(), 2);
+
+        let fetch = "b1a0e6c1-0190-1000-0000-000000000001";
+        assert_eq!(
+            metrics.value("nifi_amount_flowfiles_received", fetch),
+            Some(42.0)
+        );
+        assert_eq!(metrics.for_component_id(fetch).count(), 2);
+        assert_eq!(metrics.for_component_name("PutFile").count(), 2);
+
+        let duration = metrics.family("nifi_processing_duration").unwrap();
+        assert_eq!(duration.metric_type, MetricType::Histogram);
+        assert_eq!(duration.samples.len(), 4);
+        let inf = duration
+            .samples
+            .iter()
+            .find(|sample| sample.label("le") == Some("+Inf"))
+            .unwrap();
+        assert_eq!(inf.value, 7.0);
+
+        let untyped = metrics.family("jvm_uptime_seconds").unwrap();
+        assert_eq!(untyped.metric_type, MetricType::Untyped);
+        assert_eq!(untyped.samples[0].timestamp, Some(1735689600000));
+        assert_eq!(
+            untyped.samples[0].label("note"),
+            Some("quoted \"value\", with comma")
+        );
+    }
+
+    #[test]
+    fn test_parse_error_reports_line() {
+        let error = Metrics::parse("# TYPE a gauge\na{x=\"1\"} abc\n").unwrap_err();
+        assert_eq!(
+            error,
+            ParseError::InvalidSample {
+                line: 2,
+                message: "invalid value 'abc'".to_string()
+            }
+        );
+    }
+}
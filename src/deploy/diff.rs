@@ -0,0 +1,771 @@
+//! # Diff Module
+//!
+//! Offline comparison of two flow definitions, without a running NiFi.
+//!
+//! Works on `FlowSnapshot`s, `RegisteredFlowSnapshot`s, or directly on the JSON
+//! files they are stored in (both share the `flowContents`,
+//! `parameterContexts` and `parameterProviders` layout), and on bare
+//! `VersionedProcessGroup`s: a live group downloaded from NiFi is just another
+//! flow, which is how `nifictl plan` compares it with a declared one.
+//! Components are matched by `identifier` first and by name (within the same
+//! kind) second, so flows exported from different instances still line up.
+//! Connections, which are rarely named, are matched by their source,
+//! destination and selected relationships instead.
+//!
+//! Nested fields are reported with dotted paths (`properties.URL`,
+//! `bundle.version`, `parameters.db.value`...).
+
+use crate::deploy::hooks::{ChangeAction, PlannedChange};
+use crate::deploy::state::ComponentKind;
+use crate::proxy::v260::FlowSnapshot;
+use crate::proxy::v260::api::{RegisteredFlowSnapshot, VersionedProcessGroup};
+use anyhow::{Context, bail};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::BTreeSet;
+use std::path::Path;
+
+/// Fields never reported, at any depth: they differ between instances of the
+/// same flow (e.g. a connection's `source.instanceIdentifier`).
+const IGNORED_FIELDS: [&str; 4] = [
+    "identifier",
+    "instanceIdentifier",
+    "instanceGroupId",
+    "groupIdentifier",
+];
+
+/// The component arrays of a `VersionedProcessGroup`, with their kind.
+const CHILD_ARRAYS: [(&str, ComponentKind); 9] = [
+    ("processors", ComponentKind::Processor),
+    ("connections", ComponentKind::Connection),
+    ("controllerServices", ComponentKind::ControllerService),
+    ("inputPorts", ComponentKind::InputPort),
+    ("outputPorts", ComponentKind::OutputPort),
+    ("funnels", ComponentKind::Funnel),
+    ("labels", ComponentKind::Label),
+    ("remoteProcessGroups", ComponentKind::RemoteProcessGroup),
+    ("processGroups", ComponentKind::ProcessGroup),
+];
+
+/// What to leave out of the comparison.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DiffOptions {
+    /// Ignore `position`, connection `bends` and `labelIndex`, and label sizes.
+    pub ignore_position: bool,
+    /// Ignore `comments` and `description`.
+    pub ignore_comments: bool,
+}
+
+impl DiffOptions {
+    fn ignores(&self, field: &str) -> bool {
+        IGNORED_FIELDS.contains(&field)
+            || (self.ignore_position
+                && matches!(
+                    field,
+                    "position" | "bends" | "labelIndex" | "width" | "height"
+                ))
+            || (self.ignore_comments && matches!(field, "comments" | "description"))
+    }
+}
+
+/// How a component differs between the old and the new flow.
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "camelCase")]
+pub enum DiffKind {
+    Added,
+    Removed,
+    Modified,
+}
+
+/// A field whose value differs.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct FieldDiff {
+    /// Dotted path of the field, e.g. `properties.URL`.
+    pub path: String,
+    /// `null` when the field is absent from the old flow.
+    pub old: Value,
+    /// `null` when the field is absent from the new flow.
+    pub new: Value,
+}
+
+/// A component that was added, removed or modified.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ComponentDiff {
+    pub kind: ComponentKind,
+    pub diff: DiffKind,
+    /// The identifier in the new flow (the old one for removals).
+    pub identifier: Option<String>,
+    /// The `instanceIdentifier` in the old flow, i.e. the NiFi UUID of the
+    /// component when the old flow is a live one. `None` for additions.
+    pub instance_identifier: Option<String>,
+    pub name: Option<String>,
+    /// The identifier of the enclosing process group, if any.
+    pub group_identifier: Option<String>,
+    /// The differing fields; only filled for modifications.
+    pub fields: Vec<FieldDiff>,
+}
+
+impl ComponentDiff {
+    /// The change that turns the old flow into the new one for this component.
+    pub fn planned_change(&self) -> PlannedChange {
+        PlannedChange {
+            kind: self.kind,
+            action: match self.diff {
+                DiffKind::Added => ChangeAction::Create,
+                DiffKind::Removed => ChangeAction::Delete,
+                DiffKind::Modified => ChangeAction::Update,
+            },
+            logical_id: self.identifier.clone(),
+            component_id: self.instance_identifier.clone(),
+            group_id: self.group_identifier.clone(),
+            name: self.name.clone(),
+        }
+    }
+}
+
+/// The result of comparing two flows.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct FlowDiff {
+    pub changes: Vec<ComponentDiff>,
+}
+
+impl FlowDiff {
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+
+    /// The changes of one kind of component.
+    pub fn of_kind(&self, kind: ComponentKind) -> impl Iterator<Item = &ComponentDiff> {
+        self.changes
+            .iter()
+            .filter(move |change| change.kind == kind)
+    }
+
+    /// Number of added, removed and modified components.
+    pub fn counts(&self) -> (usize, usize, usize) {
+        let count = |diff| self.changes.iter().filter(|c| c.diff == diff).count();
+        (
+            count(DiffKind::Added),
+            count(DiffKind::Removed),
+            count(DiffKind::Modified),
+        )
+    }
+}
+
+/// Compares two `RegisteredFlowSnapshot`s (downloads or registry snapshots).
+///
+/// # Errors
+/// Returns an error if a snapshot cannot be converted to JSON.
+pub fn diff_snapshots(
+    old: &RegisteredFlowSnapshot,
+    new: &RegisteredFlowSnapshot,
+    options: DiffOptions,
+) -> anyhow::Result<FlowDiff> {
+    Ok(diff_values(
+        &serde_json::to_value(old)?,
+        &serde_json::to_value(new)?,
+        options,
+    ))
+}
+
+/// Compares two `FlowSnapshot`s.
+///
+/// # Errors
+/// Returns an error if a snapshot cannot be converted to JSON.
+pub fn diff_flow_snapshots(
+    old: &FlowSnapshot,
+    new: &FlowSnapshot,
+    options: DiffOptions,
+) -> anyhow::Result<FlowDiff> {
+    Ok(diff_values(
+        &serde_json::to_value(old)?,
+        &serde_json::to_value(new)?,
+        options,
+    ))
+}
+
+/// Compares two `VersionedProcessGroup`s (the `flowContents` of snapshots),
+/// including their child groups.
+pub fn diff_groups(
+    old: &VersionedProcessGroup,
+    new: &VersionedProcessGroup,
+    options: DiffOptions,
+) -> anyhow::Result<FlowDiff> {
+    let mut diff = FlowDiff::default();
+    compare_group(
+        &serde_json::to_value(old)?,
+        &serde_json::to_value(new)?,
+        None,
+        options,
+        &mut diff.changes,
+    );
+    Ok(diff)
+}
+
+/// Compares two flow definition files (`FlowSnapshot` or
+/// `RegisteredFlowSnapshot` JSON).
+///
+/// # Errors
+/// Returns an error if a file cannot be read or has no `flowContents`.
+pub fn diff_files(old: &Path, new: &Path, options: DiffOptions) -> anyhow::Result<FlowDiff> {
+    Ok(diff_values(&read_flow(old)?, &read_flow(new)?, options))
+}
+
+fn read_flow(path: &Path) -> anyhow::Result<Value> {
+    let content =
+        std::fs::read_to_string(path).with_context(|| format!("Cannot read {}", path.display()))?;
+    let value: Value = serde_json::from_str(&content)
+        .with_context(|| format!("{} is not valid JSON", path.display()))?;
+    if !value["flowContents"].is_object() {
+        bail!("{} has no flowContents", path.display());
+    }
+    Ok(value)
+}
+
+/// Compares two snapshots already in JSON form.
+pub fn diff_values(old: &Value, new: &Value, options: DiffOptions) -> FlowDiff {
+    let mut diff = FlowDiff::default();
+    compare_group(
+        &old["flowContents"],
+        &new["flowContents"],
+        None,
+        options,
+        &mut diff.changes,
+    );
+    compare_map(
+        &old["parameterContexts"],
+        &new["parameterContexts"],
+        ComponentKind::ParameterContext,
+        options,
+        &mut diff.changes,
+    );
+    compare_map(
+        &old["parameterProviders"],
+        &new["parameterProviders"],
+        ComponentKind::ParameterProvider,
+        options,
+        &mut diff.changes,
+    );
+    diff
+}
+
+fn compare_group(
+    old: &Value,
+    new: &Value,
+    parent: Option<String>,
+    options: DiffOptions,
+    changes: &mut Vec<ComponentDiff>,
+) {
+    let children: Vec<&str> = CHILD_ARRAYS.iter().map(|(key, _)| *key).collect();
+    let fields = compare_fields(&without(old, &children), &without(new, &children), options);
+    if !fields.is_empty() {
+        changes.push(component(
+            new,
+            Some(old),
+            ComponentKind::ProcessGroup,
+            DiffKind::Modified,
+            parent,
+            fields,
+        ));
+    }
+
+    let group = string(new, "identifier");
+    for (key, kind) in CHILD_ARRAYS {
+        for (old_child, new_child) in match_components(kind, &old[key], &new[key]) {
+            match (old_child, new_child) {
+                (None, Some(added)) => changes.push(component(
+                    added,
+                    None,
+                    kind,
+                    DiffKind::Added,
+                    group.clone(),
+                    Vec::new(),
+                )),
+                (Some(removed), None) => changes.push(component(
+                    removed,
+                    Some(removed),
+                    kind,
+                    DiffKind::Removed,
+                    group.clone(),
+                    Vec::new(),
+                )),
+                (Some(old_child), Some(new_child)) if kind == ComponentKind::ProcessGroup => {
+                    compare_group(old_child, new_child, group.clone(), options, changes)
+                },
+                (Some(old_child), Some(new_child)) => {
+                    let fields = compare_fields(old_child, new_child, options);
+                    if !fields.is_empty() {
+                        changes.push(component(
+                            new_child,
+                            Some(old_child),
+                            kind,
+                            DiffKind::Modified,
+                            group.clone(),
+                            fields,
+                        ));
+                    }
+                },
+                (None, None) => {},
+            }
+        }
+    }
+}
+
+/// Compares maps keyed by name or id (`parameterContexts`, `parameterProviders`).
+fn compare_map(
+    old: &Value,
+    new: &Value,
+    kind: ComponentKind,
+    options: DiffOptions,
+    changes: &mut Vec<ComponentDiff>,
+) {
+    let empty = Map::new();
+    let old = old.as_object().unwrap_or(&empty);
+    let new = new.as_object().unwrap_or(&empty);
+    let keys: BTreeSet<&String> = old.keys().chain(new.keys()).collect();
+    for key in keys {
+        let named = |value: &Value| {
+            let mut value = parameters_by_name(value);
+            if value["name"].is_null()
+                && let Some(object) = value.as_object_mut()
+            {
+                object.insert("name".to_string(), Value::String(key.clone()));
+            }
+            value
+        };
+        match (old.get(key), new.get(key)) {
+            (None, Some(added)) => changes.push(component(
+                &named(added),
+                None,
+                kind,
+                DiffKind::Added,
+                None,
+                Vec::new(),
+            )),
+            (Some(removed), None) => changes.push(component(
+                &named(removed),
+                None,
+                kind,
+                DiffKind::Removed,
+                None,
+                Vec::new(),
+            )),
+            (Some(old_value), Some(new_value)) => {
+                let new_value = named(new_value);
+                let fields = compare_fields(&named(old_value), &new_value, options);
+                if !fields.is_empty() {
+                    changes.push(component(
+                        &new_value,
+                        None,
+                        kind,
+                        DiffKind::Modified,
+                        None,
+                        fields,
+                    ));
+                }
+            },
+            (None, None) => {},
+        }
+    }
+}
+
+/// Pairs the components of two arrays: by identifier, then by `fallback_key`.
+/// Returns `(old, new)` pairs, with `None` for unmatched sides.
+fn match_components<'a>(
+    kind: ComponentKind,
+    old: &'a Value,
+    new: &'a Value,
+) -> Vec<(Option<&'a Value>, Option<&'a Value>)> {
+    let old: Vec<&Value> = old.as_array().into_iter().flatten().collect();
+    let new: Vec<&Value> = new.as_array().into_iter().flatten().collect();
+    let mut old_used = vec![false; old.len()];
+    let mut pairs: Vec<(Option<&Value>, Option<&Value>)> = Vec::new();
+    let mut unmatched = Vec::new();
+
+    for component in &new {
+        let id = string(component, "identifier");
+        let found = old.iter().enumerate().position(|(i, candidate)| {
+            !old_used[i] && id.is_some() && string(candidate, "identifier") == id
+        });
+        match found {
+            Some(i) => {
+                old_used[i] = true;
+                pairs.push((Some(old[i]), Some(component)));
+            },
+            None => unmatched.push(*component),
+        }
+    }
+    for component in unmatched {
+        let key = fallback_key(kind, component);
+        let found = old.iter().enumerate().position(|(i, candidate)| {
+            !old_used[i] && key.is_some() && fallback_key(kind, candidate) == key
+        });
+        match found {
+            Some(i) => {
+                old_used[i] = true;
+                pairs.push((Some(old[i]), Some(component)));
+            },
+            None => pairs.push((None, Some(component))),
+        }
+    }
+    for (i, component) in old.iter().enumerate() {
+        if !old_used[i] {
+            pairs.push((Some(component), None));
+        }
+    }
+    pairs
+}
+
+/// What identifies a component when its `identifier` does not match: the
+/// source, destination and selected relationships of a connection, the
+/// non-empty name of anything else.
+fn fallback_key(kind: ComponentKind, component: &Value) -> Option<Value> {
+    if kind == ComponentKind::Connection {
+        let endpoint = |key: &str| {
+            let endpoint = &component[key];
+            Some(Value::from(vec![
+                endpoint["type"].clone(),
+                Value::String(string(endpoint, "name")?),
+            ]))
+        };
+        let mut relationships: Vec<String> = component["selectedRelationships"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|relationship| relationship.as_str().map(str::to_string))
+            .collect();
+        relationships.sort();
+        return Some(Value::from(vec![
+            endpoint("source")?,
+            endpoint("destination")?,
+            Value::from(relationships),
+        ]));
+    }
+    string(component, "name")
+        .filter(|name| !name.is_empty())
+        .map(Value::String)
+}
+
+fn compare_fields(old: &Value, new: &Value, options: DiffOptions) -> Vec<FieldDiff> {
+    let mut fields = Vec::new();
+    flatten_diff("", old, new, options, &mut fields);
+    fields
+}
+
+fn flatten_diff(
+    prefix: &str,
+    old: &Value,
+    new: &Value,
+    options: DiffOptions,
+    out: &mut Vec<FieldDiff>,
+) {
+    if old == new {
+        return;
+    }
+    match (old, new) {
+        (Value::Object(old_map), Value::Object(new_map)) => {
+            let keys: BTreeSet<&String> = old_map.keys().chain(new_map.keys()).collect();
+            // Property and parameter names are user-chosen, never ignored.
+            let named = matches!(prefix.rsplit('.').next(), Some("properties" | "parameters"));
+            for key in keys {
+                if !named && options.ignores(key) {
+                    continue;
+                }
+                let path = if prefix.is_empty() {
+                    key.clone()
+                } else {
+                    format!("{}.{}", prefix, key)
+                };
+                flatten_diff(
+                    &path,
+                    old_map.get(key).unwrap_or(&Value::Null),
+                    new_map.get(key).unwrap_or(&Value::Null),
+                    options,
+                    out,
+                );
+            }
+        },
+        _ => out.push(FieldDiff {
+            path: prefix.to_string(),
+            old: old.clone(),
+            new: new.clone(),
+        }),
+    }
+}
+
+/// Turns the `parameters` array of a parameter context into an object keyed
+/// by parameter name, so parameters are compared one by one.
+fn parameters_by_name(context: &Value) -> Value {
+    let mut context = context.clone();
+    if let Some(parameters) = context["parameters"].as_array() {
+        let by_name: Map<String, Value> = parameters
+            .iter()
+            .filter_map(|parameter| Some((string(parameter, "name")?, parameter.clone())))
+            .collect();
+        context["parameters"] = Value::Object(by_name);
+    }
+    context
+}
+
+fn without(value: &Value, keys: &[&str]) -> Value {
+    let mut value = value.clone();
+    if let Some(object) = value.as_object_mut() {
+        for key in keys {
+            object.remove(*key);
+        }
+    }
+    value
+}
+
+fn string(value: &Value, key: &str) -> Option<String> {
+    value[key].as_str().map(str::to_string)
+}
+
+/// `value` is the component as reported; `old` the same component in the old
+/// flow, if it was there.
+fn component(
+    value: &Value,
+    old: Option<&Value>,
+    kind: ComponentKind,
+    diff: DiffKind,
+    group_identifier: Option<String>,
+    fields: Vec<FieldDiff>,
+) -> ComponentDiff {
+    ComponentDiff {
+        kind,
+        diff,
+        identifier: string(value, "identifier"),
+        instance_identifier: old.and_then(|old| string(old, "instanceIdentifier")),
+        name: string(value, "name"),
+        group_identifier,
+        fields,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use serde_json::json;
+
+    fn flow(processors: Value, parameters: Value) -> Value {
+        json!({
+            "flowContents": {"identifier": "root", "name": "root", "processors": processors},
+            "parameterContexts": {"ctx": {"name": "ctx", "parameters": parameters}},
+        })
+    }
+
+    #[test]
+    fn test_diff_values() {
+        let old = flow(
+            json!([
+                {"identifier": "p1", "name": "fetch", "properties": {"URL": "http://a"},
+                 "position": {"x": 0.0, "y": 0.0}, "comments": ""},
+                {"identifier": "old-id", "name": "route", "schedulingPeriod": "1 sec"},
+                {"identifier": "p2", "name": "legacy"},
+            ]),
+            json!([{"name": "db", "value": "a", "sensitive": false}]),
+        );
+        let new = flow(
+            json!([
+                {"identifier": "p1", "name": "fetch", "properties": {"URL": "http://b"},
+                 "position": {"x": 400.0, "y": 0.0}, "comments": "changed"},
+                {"identifier": "new-id", "name": "route", "schedulingPeriod": "5 sec"},
+                {"identifier": "p3", "name": "publish"},
+            ]),
+            json!([{"name": "db", "value": "b", "sensitive": false}]),
+        );
+
+        let diff = diff_values(
+            &old,
+            &new,
+            DiffOptions {
+                ignore_position: true,
+                ignore_comments: true,
+            },
+        );
+
+        let summary: Vec<_> = diff
+            .changes
+            .iter()
+            .map(|change| (change.kind, change.diff, change.name.clone().unwrap()))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                (
+                    ComponentKind::Processor,
+                    DiffKind::Modified,
+                    "fetch".to_string()
+                ),
+                (
+                    ComponentKind::Processor,
+                    DiffKind::Modified,
+                    "route".to_string()
+                ),
+                (
+                    ComponentKind::Processor,
+                    DiffKind::Added,
+                    "publish".to_string()
+                ),
+                (
+                    ComponentKind::Processor,
+                    DiffKind::Removed,
+                    "legacy".to_string()
+                ),
+                (
+                    ComponentKind::ParameterContext,
+                    DiffKind::Modified,
+                    "ctx".to_string()
+                ),
+            ]
+        );
+        assert_eq!(diff.changes[0].fields.len(), 1);
+        assert_eq!(diff.changes[0].fields[0].path, "properties.URL");
+        assert_eq!(diff.changes[4].fields[0].path, "parameters.db.value");
+        assert_eq!(diff.counts(), (1, 1, 3));
+    }
+
+    #[test]
+    fn test_diff_groups() {
+        // --- 1. Setup: a live group and the declared version of it ---
+        let group = |processors: Value| -> VersionedProcessGroup {
+            serde_json::from_value(json!({
+                "identifier": "root", "name": "root", "processors": processors,
+            }))
+            .unwrap()
+        };
+        let live = group(json!([
+            {"identifier": "p1", "instanceIdentifier": "uuid-1", "name": "fetch",
+             "properties": {"url": "http://old"}, "position": {"x": 0.0, "y": 0.0}},
+            {"identifier": "p2", "instanceIdentifier": "uuid-2", "name": "legacy"},
+        ]));
+        let declared = group(json!([
+            {"identifier": "p1", "name": "fetch", "properties": {"url": "http://new"},
+             "position": {"x": 500.0, "y": 0.0}},
+            {"identifier": "p3", "name": "publish"},
+        ]));
+
+        // --- 2. Compare ---
+        let diff = diff_groups(
+            &live,
+            &declared,
+            DiffOptions {
+                ignore_position: true,
+                ..DiffOptions::default()
+            },
+        )
+        .unwrap();
+
+        // --- 3. Assert ---
+        let changes: Vec<_> = diff
+            .changes
+            .iter()
+            .map(|change| {
+                let planned = change.planned_change();
+                (planned.action, planned.name.unwrap(), planned.component_id)
+            })
+            .collect();
+        assert_eq!(
+            changes,
+            vec![
+                (
+                    ChangeAction::Update,
+                    "fetch".to_string(),
+                    Some("uuid-1".to_string())
+                ),
+                (ChangeAction::Create, "publish".to_string(), None),
+                (
+                    ChangeAction::Delete,
+                    "legacy".to_string(),
+                    Some("uuid-2".to_string())
+                ),
+            ]
+        );
+        assert_eq!(diff.changes[0].fields.len(), 1);
+        assert_eq!(diff.changes[0].fields[0].path, "properties.url");
+    }
+
+    #[test]
+    fn test_unnamed_connections() {
+        // --- 1. Setup: two unnamed connections, re-identified on export ---
+        let connection = |id: &str, destination: &str, threshold: i64| {
+            json!({
+                "identifier": id, "name": "",
+                "source": {"type": "PROCESSOR", "name": "fetch"},
+                "destination": {"type": "PROCESSOR", "name": destination},
+                "selectedRelationships": ["success"],
+                "backPressureObjectThreshold": threshold,
+            })
+        };
+        let flow = |connections: Value| json!({"flowContents": {"connections": connections}});
+        let old = flow(json!([
+            connection("old-a", "publish", 10000),
+            connection("old-b", "archive", 10000),
+        ]));
+        let new = flow(json!([
+            connection("new-b", "archive", 20000),
+            connection("new-a", "publish", 10000),
+        ]));
+
+        // --- 2. Compare ---
+        let diff = diff_values(&old, &new, DiffOptions::default());
+
+        // --- 3. Assert: only the archive connection changed ---
+        assert_eq!(diff.counts(), (0, 0, 1));
+        assert_eq!(diff.changes[0].identifier.as_deref(), Some("new-b"));
+        assert_eq!(
+            diff.changes[0].fields[0].path,
+            "backPressureObjectThreshold"
+        );
+    }
+
+    #[test]
+    fn test_nested_instance_ids_ignored() {
+        // --- 1. Setup: the same connection, declared and downloaded live ---
+        let endpoint = |name: &str, instance: Option<&str>| {
+            let mut endpoint = json!({"id": name, "type": "PROCESSOR", "name": name});
+            if let Some(instance) = instance {
+                endpoint["instanceIdentifier"] = json!(instance);
+            }
+            endpoint
+        };
+        let flow = |source: Value, destination: Value| {
+            json!({"flowContents": {"connections": [{
+                "identifier": "fetch-publish",
+                "source": source, "destination": destination,
+                "selectedRelationships": ["success"],
+            }]}})
+        };
+        let declared = flow(endpoint("fetch", None), endpoint("publish", None));
+        let live = flow(
+            endpoint("fetch", Some("uuid-fetch")),
+            endpoint("publish", Some("uuid-publish")),
+        );
+
+        // --- 2. Compare ---
+        let diff = diff_values(&declared, &live, DiffOptions::default());
+
+        // --- 3. Assert ---
+        assert!(diff.changes.is_empty(), "{:?}", diff.changes);
+    }
+
+    #[test]
+    fn test_cosmetic_fields_reported_unless_ignored() {
+        let old = flow(
+            json!([{"identifier": "p1", "position": {"x": 0.0}}]),
+            json!([]),
+        );
+        let new = flow(
+            json!([{"identifier": "p1", "position": {"x": 1.0}}]),
+            json!([]),
+        );
+
+        let diff = diff_values(&old, &new, DiffOptions::default());
+
+        assert_eq!(diff.changes.len(), 1);
+        assert_eq!(diff.changes[0].fields[0].path, "position.x");
+    }
+}
@@ -0,0 +1,231 @@
+//! # Hooks Module
+//!
+//! Observing and extending deployments.
+//!
+//! A deployment is described as a `Plan`: an ordered list of `Step`s, each one
+//! a `PlannedChange` (what is going to happen to which component) plus the
+//! async action that applies it. `Deployment::deploy` runs a plan and, along
+//! the way:
+//!
+//! * calls every registered `DeployHook` (before-plan, before/after each
+//!   change, on-error and after-deploy). `before_apply` can veto a change,
+//!   either skipping it or aborting the whole deployment.
+//! * publishes a `DeployEvent` to every `DeployEventStream` obtained from
+//!   `Deployment::events`, so they can be forwarded to chat or an audit log.
+
+use crate::deploy::rollback::RollbackReport;
+use crate::deploy::state::ComponentKind;
+use async_trait::async_trait;
+use futures::Stream;
+use futures::future::BoxFuture;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use tokio::sync::mpsc::UnboundedReceiver;
+
+/// What a step does to its component.
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "camelCase")]
+pub enum ChangeAction {
+    Create,
+    Update,
+    Delete,
+}
+
+/// A single change a deployment is about to make.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct PlannedChange {
+    pub kind: ComponentKind,
+    pub action: ChangeAction,
+    /// The declared (logical) id of the component, if it has one.
+    pub logical_id: Option<String>,
+    /// The NiFi UUID of the component. `None` for components not created yet.
+    pub component_id: Option<String>,
+    /// The process group the component lives in.
+    pub group_id: Option<String>,
+    pub name: Option<String>,
+}
+
+impl fmt::Display for PlannedChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:?} {:?} {}",
+            self.action,
+            self.kind,
+            self.name
+                .as_deref()
+                .or(self.logical_id.as_deref())
+                .or(self.component_id.as_deref())
+                .unwrap_or("-")
+        )
+    }
+}
+
+/// A `PlannedChange` together with the action applying it.
+pub struct Step {
+    pub change: PlannedChange,
+    apply: Box<dyn FnOnce() -> BoxFuture<'static, anyhow::Result<()>> + Send>,
+}
+
+impl Step {
+    /// Creates a step that runs `apply` to make `change` happen.
+    pub fn new<F, Fut>(change: PlannedChange, apply: F) -> Self
+    where
+        F: FnOnce() -> Fut + Send + 'static,
+        Fut: Future<Output = anyhow::Result<()>> + Send + 'static,
+    {
+        Self {
+            change,
+            apply: Box::new(move || Box::pin(apply())),
+        }
+    }
+
+    pub(crate) async fn run(self) -> anyhow::Result<()> {
+        (self.apply)().await
+    }
+}
+
+impl fmt::Debug for Step {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Step")
+            .field("change", &self.change)
+            .finish()
+    }
+}
+
+/// The ordered list of steps a deployment applies.
+#[derive(Debug, Default)]
+pub struct Plan {
+    pub steps: Vec<Step>,
+}
+
+impl Plan {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Appends a step to the plan.
+    pub fn push(&mut self, step: Step) {
+        self.steps.push(step);
+    }
+
+    /// The changes of every step, in order.
+    pub fn changes(&self) -> Vec<PlannedChange> {
+        self.steps.iter().map(|step| step.change.clone()).collect()
+    }
+}
+
+/// What a `DeployHook` wants to happen with a change.
+#[derive(Debug, Clone, PartialEq)]
+pub enum HookDecision {
+    /// Apply the change.
+    Proceed,
+    /// Do not apply this change, but carry on with the rest of the plan.
+    Skip(String),
+    /// Stop the deployment. Already applied changes are rolled back.
+    Abort(String),
+}
+
+/// The outcome of `Deployment::deploy`.
+#[derive(Deserialize, Serialize, Debug, Clone, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct DeploySummary {
+    /// Changes applied successfully, in order.
+    pub applied: Vec<PlannedChange>,
+    /// Changes skipped by a hook, with the reason given.
+    pub skipped: Vec<(PlannedChange, String)>,
+    /// The error that stopped the deployment, if any.
+    pub error: Option<String>,
+    /// What was rolled back after the error, if anything.
+    pub rollback: Option<RollbackReport>,
+}
+
+impl DeploySummary {
+    pub fn is_success(&self) -> bool {
+        self.error.is_none()
+    }
+}
+
+/// Callbacks invoked by `Deployment::deploy`. Every method has a no-op
+/// default, so implementors only override what they need.
+#[async_trait]
+pub trait DeployHook: Send + Sync {
+    /// Called before the plan is computed. Returning an error aborts the
+    /// deployment before anything is touched.
+    async fn before_plan(&self) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    /// Called before each change is applied. Can veto the change.
+    async fn before_apply(&self, _change: &PlannedChange) -> HookDecision {
+        HookDecision::Proceed
+    }
+
+    /// Called after each change was applied successfully.
+    async fn after_apply(&self, _change: &PlannedChange) {}
+
+    /// Called when the deployment fails, before rolling back.
+    async fn on_error(&self, _error: &anyhow::Error) {}
+
+    /// Called once the deployment is over, successful or not.
+    async fn after_deploy(&self, _summary: &DeploySummary) {}
+}
+
+/// Everything that happens during `Deployment::deploy`, in order.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum DeployEvent {
+    PlanStarted,
+    PlanReady {
+        changes: Vec<PlannedChange>,
+    },
+    GroupSnapshotted {
+        group_id: String,
+    },
+    ChangeStarted {
+        change: PlannedChange,
+    },
+    ChangeApplied {
+        change: PlannedChange,
+    },
+    ChangeSkipped {
+        change: PlannedChange,
+        reason: String,
+    },
+    ChangeFailed {
+        change: PlannedChange,
+        error: String,
+    },
+    RolledBack {
+        report: RollbackReport,
+    },
+    Finished {
+        summary: DeploySummary,
+    },
+}
+
+/// An async `Stream` of `DeployEvent`s, obtained from `Deployment::events`.
+///
+/// The stream ends when the `Deployment` it came from is dropped.
+#[derive(Debug)]
+pub struct DeployEventStream {
+    receiver: UnboundedReceiver<DeployEvent>,
+}
+
+impl DeployEventStream {
+    pub(crate) fn new(receiver: UnboundedReceiver<DeployEvent>) -> Self {
+        Self { receiver }
+    }
+}
+
+impl Stream for DeployEventStream {
+    type Item = DeployEvent;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.receiver.poll_recv(cx)
+    }
+}
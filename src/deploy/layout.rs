@@ -0,0 +1,690 @@
+//! # Layout Module
+//!
+//! Automatic canvas layout for flows built through the API.
+//!
+//! Components created programmatically have no `position`, so NiFi stacks them
+//! all at the origin of the canvas. `LayoutEngine` assigns coordinates to every
+//! processor, port, funnel, child group and remote process group of a
+//! `VersionedProcessGroup` before it is uploaded, using a layered
+//! (Sugiyama-style) layout of the connection graph:
+//!
+//! 1. Cycles are broken by reversing the back edges found by a depth-first search.
+//! 2. Components are assigned to layers by longest path from the sources.
+//! 3. Connections spanning several layers get virtual nodes, which later become
+//!    connection bends.
+//! 4. Components inside each layer are ordered with the barycenter heuristic to
+//!    reduce crossings.
+//! 5. Layers are stacked top to bottom and centered horizontally.
+//!
+//! Labels are placed in a column to the right of the laid-out components.
+//! Versioned components use `Position` (the versioned counterpart of `PositionDto`),
+//! whose coordinates refer to the top-left corner of the component.
+
+use crate::proxy::v260::api::{ConnectableComponent, Position, VersionedProcessGroup};
+use std::collections::{HashMap, HashSet};
+
+/// Tunables for `LayoutEngine`.
+#[derive(Debug, Clone)]
+pub struct LayoutOptions {
+    /// Top-left corner of the laid-out flow.
+    pub origin_x: f64,
+    pub origin_y: f64,
+    /// Gap between components of the same layer.
+    pub horizontal_spacing: f64,
+    /// Gap between two consecutive layers.
+    pub vertical_spacing: f64,
+    /// Number of down/up barycenter sweeps used to reduce crossings.
+    pub ordering_sweeps: usize,
+    /// When `true`, components that already have a position keep it.
+    pub preserve_existing: bool,
+    /// When `true`, child process groups are laid out as well.
+    pub recursive: bool,
+}
+
+impl Default for LayoutOptions {
+    fn default() -> Self {
+        Self {
+            origin_x: 0.0,
+            origin_y: 0.0,
+            horizontal_spacing: 80.0,
+            vertical_spacing: 100.0,
+            ordering_sweeps: 4,
+            preserve_existing: false,
+            recursive: true,
+        }
+    }
+}
+
+/// Assigns canvas positions to the components of a `VersionedProcessGroup`.
+#[derive(Debug, Clone, Default)]
+pub struct LayoutEngine {
+    options: LayoutOptions,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum NodeKind {
+    Processor,
+    Port,
+    Funnel,
+    ProcessGroup,
+    RemoteProcessGroup,
+    Virtual,
+}
+
+impl NodeKind {
+    /// Approximate rendered size of each component kind in the NiFi UI.
+    fn size(self) -> (f64, f64) {
+        match self {
+            NodeKind::Processor => (352.0, 128.0),
+            NodeKind::Port => (240.0, 48.0),
+            NodeKind::Funnel => (48.0, 48.0),
+            NodeKind::ProcessGroup | NodeKind::RemoteProcessGroup => (384.0, 176.0),
+            NodeKind::Virtual => (0.0, 0.0),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Node {
+    id: Option<String>,
+    width: f64,
+    height: f64,
+    layer: usize,
+    x: f64,
+    y: f64,
+}
+
+impl Node {
+    fn center(&self) -> (f64, f64) {
+        (self.x + self.width / 2.0, self.y + self.height / 2.0)
+    }
+}
+
+/// The connection graph of a single process group.
+#[derive(Debug, Default)]
+struct Graph {
+    nodes: Vec<Node>,
+    index: HashMap<String, usize>,
+    /// Resolved `(source, destination)` of each connection, by connection index.
+    connections: Vec<Option<(usize, usize)>>,
+    /// Edges that had to be reversed to make the graph acyclic.
+    reversed: HashSet<(usize, usize)>,
+    /// Virtual nodes inserted along each acyclic edge spanning several layers.
+    chains: HashMap<(usize, usize), Vec<usize>>,
+    layers: Vec<Vec<usize>>,
+}
+
+impl LayoutEngine {
+    /// Creates a new `LayoutEngine` with the given options.
+    pub fn new(options: LayoutOptions) -> Self {
+        Self { options }
+    }
+
+    /// Lays out `group` in place: positions of components and labels, and
+    /// bends of connections.
+    pub fn apply(&self, group: &mut VersionedProcessGroup) {
+        if self.options.recursive {
+            for child in group.process_groups.iter_mut().flatten() {
+                self.apply(child);
+            }
+        }
+
+        let mut graph = Graph::from_group(group);
+        graph.break_cycles();
+        graph.assign_layers();
+        graph.insert_virtual_nodes();
+        graph.order_layers(self.options.ordering_sweeps);
+        graph.assign_coordinates(&self.options);
+        self.write_back(group, &graph);
+    }
+
+    fn write_back(&self, group: &mut VersionedProcessGroup, graph: &Graph) {
+        let preserve = self.options.preserve_existing;
+        let place = |identifier: &Option<String>, position: &mut Option<Position>| {
+            if preserve && position.is_some() {
+                return;
+            }
+            if let Some(node) = identifier
+                .as_ref()
+                .and_then(|id| graph.index.get(id))
+                .map(|&i| &graph.nodes[i])
+            {
+                *position = Some(point(node.x, node.y));
+            }
+        };
+
+        for processor in group.processors.iter_mut().flatten() {
+            place(&processor.identifier, &mut processor.position);
+        }
+        for port in group
+            .input_ports
+            .iter_mut()
+            .flatten()
+            .chain(group.output_ports.iter_mut().flatten())
+        {
+            place(&port.identifier, &mut port.position);
+        }
+        for funnel in group.funnels.iter_mut().flatten() {
+            place(&funnel.identifier, &mut funnel.position);
+        }
+        for child in group.process_groups.iter_mut().flatten() {
+            place(&child.identifier, &mut child.position);
+        }
+        for remote in group.remote_process_groups.iter_mut().flatten() {
+            place(&remote.identifier, &mut remote.position);
+        }
+
+        let mut seen_pairs: HashMap<(usize, usize), usize> = HashMap::new();
+        for (i, connection) in group.connections.iter_mut().flatten().enumerate() {
+            let Some((source, destination)) = graph.connections.get(i).copied().flatten() else {
+                continue;
+            };
+            let duplicate = seen_pairs.entry((source, destination)).or_insert(0);
+            let bends = graph.bends(source, destination, *duplicate, &self.options);
+            *duplicate += 1;
+            if preserve && !connection.bends.is_empty() {
+                continue;
+            }
+            connection.label_index = (!bends.is_empty()).then_some((bends.len() / 2) as i32);
+            connection.bends = bends;
+        }
+
+        // Labels go in a column to the right of everything else.
+        let right_edge = graph
+            .nodes
+            .iter()
+            .filter(|node| node.id.is_some())
+            .map(|node| node.x + node.width)
+            .fold(self.options.origin_x, f64::max);
+        let mut y = self.options.origin_y;
+        for label in group.labels.iter_mut().flatten() {
+            let height = label.height.unwrap_or(150.0);
+            if !(preserve && label.position.is_some()) {
+                label.position = Some(point(right_edge + self.options.horizontal_spacing, y));
+            }
+            y += height + self.options.vertical_spacing / 2.0;
+        }
+    }
+}
+
+impl Graph {
+    fn from_group(group: &VersionedProcessGroup) -> Self {
+        let mut graph = Graph::default();
+        for processor in group.processors.iter().flatten() {
+            graph.add_node(&processor.identifier, NodeKind::Processor);
+        }
+        for port in group
+            .input_ports
+            .iter()
+            .flatten()
+            .chain(group.output_ports.iter().flatten())
+        {
+            graph.add_node(&port.identifier, NodeKind::Port);
+        }
+        for funnel in group.funnels.iter().flatten() {
+            graph.add_node(&funnel.identifier, NodeKind::Funnel);
+        }
+        for child in group.process_groups.iter().flatten() {
+            graph.add_node(&child.identifier, NodeKind::ProcessGroup);
+        }
+        for remote in group.remote_process_groups.iter().flatten() {
+            graph.add_node(&remote.identifier, NodeKind::RemoteProcessGroup);
+        }
+
+        let group_id = group.identifier.as_deref();
+        graph.connections = group
+            .connections
+            .iter()
+            .flatten()
+            .map(|connection| {
+                let source = graph.resolve(connection.source.as_ref()?, group_id)?;
+                let destination = graph.resolve(connection.destination.as_ref()?, group_id)?;
+                Some((source, destination))
+            })
+            .collect();
+        graph
+    }
+
+    fn add_node(&mut self, identifier: &Option<String>, kind: NodeKind) {
+        let Some(id) = identifier.as_ref() else {
+            return;
+        };
+        let (width, height) = kind.size();
+        self.nodes.push(Node {
+            id: Some(id.clone()),
+            width,
+            height,
+            layer: 0,
+            x: 0.0,
+            y: 0.0,
+        });
+        self.index.insert(id.clone(), self.nodes.len() - 1);
+    }
+
+    /// Maps a connection endpoint to a node of this group. Endpoints living in
+    /// a child group (its ports) or a remote process group map to that group.
+    fn resolve(&self, component: &ConnectableComponent, group_id: Option<&str>) -> Option<usize> {
+        let component_group = component.group_id.as_deref();
+        if component_group.is_none() || component_group == group_id {
+            self.index.get(component.id.as_ref()?).copied()
+        } else {
+            self.index.get(component_group?).copied()
+        }
+    }
+
+    fn edges(&self) -> Vec<(usize, usize)> {
+        let mut seen = HashSet::new();
+        self.connections
+            .iter()
+            .flatten()
+            .copied()
+            .filter(|(source, destination)| source != destination)
+            .filter(|edge| seen.insert(*edge))
+            .collect()
+    }
+
+    /// Edges of the acyclic graph, with back edges reversed.
+    fn acyclic_edges(&self) -> Vec<(usize, usize)> {
+        let mut seen = HashSet::new();
+        self.edges()
+            .into_iter()
+            .map(|(source, destination)| {
+                if self.reversed.contains(&(source, destination)) {
+                    (destination, source)
+                } else {
+                    (source, destination)
+                }
+            })
+            .filter(|edge| seen.insert(*edge))
+            .collect()
+    }
+
+    fn break_cycles(&mut self) {
+        let mut successors: Vec<Vec<usize>> = vec![Vec::new(); self.nodes.len()];
+        let mut in_degree = vec![0usize; self.nodes.len()];
+        for (source, destination) in self.edges() {
+            successors[source].push(destination);
+            in_degree[destination] += 1;
+        }
+
+        // Starting from the sources keeps the declared direction of the flow,
+        // so the edges closing a loop are the ones reversed.
+        let mut starts: Vec<usize> = (0..self.nodes.len()).collect();
+        starts.sort_by_key(|&node| in_degree[node]);
+
+        // 0 = unvisited, 1 = on the DFS stack, 2 = done
+        let mut state = vec![0u8; self.nodes.len()];
+        for start in starts {
+            if state[start] != 0 {
+                continue;
+            }
+            let mut stack = vec![(start, 0usize)];
+            state[start] = 1;
+            while let Some((node, next)) = stack.last_mut() {
+                let node = *node;
+                if let Some(&successor) = successors[node].get(*next) {
+                    *next += 1;
+                    match state[successor] {
+                        0 => {
+                            state[successor] = 1;
+                            stack.push((successor, 0));
+                        },
+                        1 => {
+                            self.reversed.insert((node, successor));
+                        },
+                        _ => {},
+                    }
+                } else {
+                    state[node] = 2;
+                    stack.pop();
+                }
+            }
+        }
+    }
+
+    fn assign_layers(&mut self) {
+        let edges = self.acyclic_edges();
+        let mut in_degree = vec![0usize; self.nodes.len()];
+        let mut successors: Vec<Vec<usize>> = vec![Vec::new(); self.nodes.len()];
+        for &(source, destination) in &edges {
+            in_degree[destination] += 1;
+            successors[source].push(destination);
+        }
+
+        let mut queue: Vec<usize> = (0..self.nodes.len())
+            .filter(|&node| in_degree[node] == 0)
+            .collect();
+        let mut head = 0;
+        while head < queue.len() {
+            let node = queue[head];
+            head += 1;
+            for &successor in &successors[node] {
+                let layer = self.nodes[node].layer + 1;
+                if self.nodes[successor].layer < layer {
+                    self.nodes[successor].layer = layer;
+                }
+                in_degree[successor] -= 1;
+                if in_degree[successor] == 0 {
+                    queue.push(successor);
+                }
+            }
+        }
+
+        let depth = self
+            .nodes
+            .iter()
+            .map(|node| node.layer + 1)
+            .max()
+            .unwrap_or(0);
+        self.layers = vec![Vec::new(); depth];
+        for (i, node) in self.nodes.iter().enumerate() {
+            self.layers[node.layer].push(i);
+        }
+    }
+
+    fn insert_virtual_nodes(&mut self) {
+        for (source, destination) in self.acyclic_edges() {
+            let (from, to) = (self.nodes[source].layer, self.nodes[destination].layer);
+            if to <= from + 1 {
+                continue;
+            }
+            let chain: Vec<usize> = (from + 1..to)
+                .map(|layer| {
+                    self.nodes.push(Node {
+                        id: None,
+                        width: NodeKind::Virtual.size().0,
+                        height: NodeKind::Virtual.size().1,
+                        layer,
+                        x: 0.0,
+                        y: 0.0,
+                    });
+                    let index = self.nodes.len() - 1;
+                    self.layers[layer].push(index);
+                    index
+                })
+                .collect();
+            self.chains.insert((source, destination), chain);
+        }
+    }
+
+    /// Edges between consecutive layers, virtual nodes included.
+    fn layer_edges(&self) -> Vec<(usize, usize)> {
+        self.acyclic_edges()
+            .into_iter()
+            .flat_map(|(source, destination)| {
+                let mut path = vec![source];
+                path.extend(
+                    self.chains
+                        .get(&(source, destination))
+                        .into_iter()
+                        .flatten(),
+                );
+                path.push(destination);
+                path.windows(2)
+                    .map(|pair| (pair[0], pair[1]))
+                    .collect::<Vec<_>>()
+            })
+            .collect()
+    }
+
+    fn order_layers(&mut self, sweeps: usize) {
+        let edges = self.layer_edges();
+        let mut predecessors: Vec<Vec<usize>> = vec![Vec::new(); self.nodes.len()];
+        let mut successors: Vec<Vec<usize>> = vec![Vec::new(); self.nodes.len()];
+        for (source, destination) in edges {
+            predecessors[destination].push(source);
+            successors[source].push(destination);
+        }
+
+        for _ in 0..sweeps {
+            for layer in 1..self.layers.len() {
+                self.reorder_by_barycenter(layer, layer - 1, &predecessors);
+            }
+            for layer in (0..self.layers.len().saturating_sub(1)).rev() {
+                self.reorder_by_barycenter(layer, layer + 1, &successors);
+            }
+        }
+    }
+
+    fn reorder_by_barycenter(&mut self, layer: usize, fixed: usize, neighbours: &[Vec<usize>]) {
+        let rank: HashMap<usize, usize> = self.layers[fixed]
+            .iter()
+            .enumerate()
+            .map(|(rank, &node)| (node, rank))
+            .collect();
+        let mut keyed: Vec<(f64, usize)> = self.layers[layer]
+            .iter()
+            .enumerate()
+            .map(|(current, &node)| {
+                let ranks: Vec<usize> = neighbours[node]
+                    .iter()
+                    .filter_map(|n| rank.get(n).copied())
+                    .collect();
+                let barycenter = if ranks.is_empty() {
+                    current as f64
+                } else {
+                    ranks.iter().sum::<usize>() as f64 / ranks.len() as f64
+                };
+                (barycenter, node)
+            })
+            .collect();
+        keyed.sort_by(|a, b| a.0.total_cmp(&b.0));
+        self.layers[layer] = keyed.into_iter().map(|(_, node)| node).collect();
+    }
+
+    fn assign_coordinates(&mut self, options: &LayoutOptions) {
+        let layer_width = |layer: &Vec<usize>, nodes: &Vec<Node>| {
+            let widths: f64 = layer.iter().map(|&node| nodes[node].width).sum();
+            widths + options.horizontal_spacing * layer.len().saturating_sub(1) as f64
+        };
+        let widest = self
+            .layers
+            .iter()
+            .map(|layer| layer_width(layer, &self.nodes))
+            .fold(0.0, f64::max);
+
+        let mut y = options.origin_y;
+        for layer in &self.layers {
+            let height = layer
+                .iter()
+                .map(|&node| self.nodes[node].height)
+                .fold(0.0, f64::max);
+            let mut x = options.origin_x + (widest - layer_width(layer, &self.nodes)) / 2.0;
+            for &node in layer {
+                let node = &mut self.nodes[node];
+                node.x = x;
+                node.y = y + (height - node.height) / 2.0;
+                x += node.width + options.horizontal_spacing;
+            }
+            y += height + options.vertical_spacing;
+        }
+    }
+
+    /// Bend points for a connection from `source` to `destination`.
+    ///
+    /// `duplicate` is the number of connections between the same two nodes
+    /// seen before this one, used to keep parallel connections apart.
+    fn bends(
+        &self,
+        source: usize,
+        destination: usize,
+        duplicate: usize,
+        options: &LayoutOptions,
+    ) -> Vec<Position> {
+        let offset = options.horizontal_spacing / 2.0;
+        if source == destination {
+            let node = &self.nodes[source];
+            let (_, center_y) = node.center();
+            let x = node.x + node.width + offset * (duplicate + 1) as f64;
+            return vec![point(x, center_y - 25.0), point(x, center_y + 25.0)];
+        }
+
+        let reversed = self.reversed.contains(&(source, destination));
+        let chain: Vec<usize> = if reversed {
+            let mut chain = self
+                .chains
+                .get(&(destination, source))
+                .cloned()
+                .unwrap_or_default();
+            chain.reverse();
+            chain
+        } else {
+            self.chains
+                .get(&(source, destination))
+                .cloned()
+                .unwrap_or_default()
+        };
+
+        // Back edges and parallel connections are pushed sideways so they do
+        // not render on top of the forward edge.
+        let shift = (duplicate + usize::from(reversed)) as f64 * offset;
+        if chain.is_empty() {
+            if shift == 0.0 {
+                return Vec::new();
+            }
+            let (source_x, source_y) = self.nodes[source].center();
+            let (destination_x, destination_y) = self.nodes[destination].center();
+            return vec![point(
+                (source_x + destination_x) / 2.0 + shift,
+                (source_y + destination_y) / 2.0,
+            )];
+        }
+        chain
+            .iter()
+            .map(|&node| {
+                let (x, y) = self.nodes[node].center();
+                point(x + shift, y)
+            })
+            .collect()
+    }
+}
+
+fn point(x: f64, y: f64) -> Position {
+    Position {
+        x: Some(x),
+        y: Some(y),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::proxy::v260::api::{
+        ConnectableComponentType, VersionedConnection, VersionedLabel, VersionedProcessor,
+    };
+
+    fn processor(id: &str) -> VersionedProcessor {
+        VersionedProcessor {
+            identifier: Some(id.to_string()),
+            name: Some(id.to_string()),
+            ..Default::default()
+        }
+    }
+
+    fn connection(source: &str, destination: &str) -> VersionedConnection {
+        let endpoint = |id: &str| ConnectableComponent {
+            comments: None,
+            group_id: Some("root".to_string()),
+            id: Some(id.to_string()),
+            instance_identifier: None,
+            name: None,
+            type_: Some(ConnectableComponentType::Processor),
+        };
+        VersionedConnection {
+            source: Some(endpoint(source)),
+            destination: Some(endpoint(destination)),
+            ..Default::default()
+        }
+    }
+
+    fn position_of(group: &VersionedProcessGroup, id: &str) -> (f64, f64) {
+        let position = group
+            .processors
+            .iter()
+            .flatten()
+            .find(|p| p.identifier.as_deref() == Some(id))
+            .and_then(|p| p.position.clone())
+            .expect("processor should have a position");
+        (position.x.unwrap(), position.y.unwrap())
+    }
+
+    #[test]
+    fn test_layout_layers_follow_connections() {
+        // --- 1. Setup: a -> b -> c, a -> c, c -> a (cycle), c -> c (self loop) ---
+        let mut group = VersionedProcessGroup {
+            identifier: Some("root".to_string()),
+            processors: Some(vec![processor("a"), processor("b"), processor("c")]),
+            connections: Some(vec![
+                connection("a", "b"),
+                connection("b", "c"),
+                connection("a", "c"),
+                connection("c", "a"),
+                connection("c", "c"),
+            ]),
+            labels: Some(vec![VersionedLabel::default()]),
+            ..Default::default()
+        };
+
+        // --- 2. Layout ---
+        LayoutEngine::default().apply(&mut group);
+
+        // --- 3. Assert over layers ---
+        let (a, b, c) = (
+            position_of(&group, "a"),
+            position_of(&group, "b"),
+            position_of(&group, "c"),
+        );
+        assert!(
+            a.1 < b.1 && b.1 < c.1,
+            "layers out of order: {a:?} {b:?} {c:?}"
+        );
+
+        // --- 4. Assert over bends ---
+        let connections = group.connections.as_ref().unwrap();
+        assert!(connections[0].bends.is_empty(), "a -> b spans one layer");
+        assert_eq!(connections[2].bends.len(), 1, "a -> c crosses b's layer");
+        assert_eq!(connections[3].bends.len(), 1, "c -> a is routed back");
+        assert_eq!(connections[4].bends.len(), 2, "self loop needs two bends");
+        assert_eq!(connections[4].label_index, Some(1));
+
+        // --- 5. Assert over labels ---
+        let label = group.labels.as_ref().unwrap()[0].position.clone().unwrap();
+        assert!(label.x.unwrap() > a.0 + 352.0);
+    }
+
+    #[test]
+    fn test_layout_does_not_overlap_within_a_layer() {
+        let mut group = VersionedProcessGroup {
+            identifier: Some("root".to_string()),
+            processors: Some(vec![processor("src"), processor("x"), processor("y")]),
+            connections: Some(vec![connection("src", "x"), connection("src", "y")]),
+            ..Default::default()
+        };
+
+        LayoutEngine::default().apply(&mut group);
+
+        let (x, y) = (position_of(&group, "x"), position_of(&group, "y"));
+        assert_eq!(x.1, y.1);
+        assert!((x.0 - y.0).abs() >= 352.0, "overlapping: {x:?} {y:?}");
+    }
+
+    #[test]
+    fn test_layout_preserves_existing_positions() {
+        let mut fixed = processor("fixed");
+        fixed.position = Some(point(1000.0, 1000.0));
+        let mut group = VersionedProcessGroup {
+            identifier: Some("root".to_string()),
+            processors: Some(vec![fixed, processor("free")]),
+            ..Default::default()
+        };
+
+        LayoutEngine::new(LayoutOptions {
+            preserve_existing: true,
+            ..Default::default()
+        })
+        .apply(&mut group);
+
+        assert_eq!(position_of(&group, "fixed"), (1000.0, 1000.0));
+        assert!(group.processors.as_ref().unwrap()[1].position.is_some());
+    }
+}
@@ -0,0 +1,385 @@
+//! # Lint Module
+//!
+//! Offline checks over a `FlowSnapshot`, complementary to the flow analysis
+//! rules NiFi runs on the server.
+//!
+//! A `Linter` runs a set of `Rule`s (the built-in ones live in [`rules`]) and
+//! collects their `Finding`s into a `LintReport`. The built-in
+//! `unconnected-relationship` rule only knows the relationships of the
+//! standard NiFi processors; register those of other processors with
+//! `UnconnectedRelationships::with_relationships`. Rules are pluggable: any
+//! type implementing `Rule` can be added, and the severity of every rule can
+//! be overridden, or the rule disabled, by id. Reports serialize to JSON or,
+//! through [`sarif`], to SARIF 2.1.0 for code-scanning UIs.
+
+pub mod rules;
+pub mod sarif;
+
+use crate::deploy::state::ComponentKind;
+use crate::proxy::v260::FlowSnapshot;
+use crate::proxy::v260::api::{VersionedParameterContext, VersionedProcessGroup};
+use anyhow::Context;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap};
+use std::path::Path;
+
+/// How serious a finding is, from least to most.
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[serde(rename_all = "camelCase")]
+pub enum Severity {
+    Note,
+    Warning,
+    Error,
+}
+
+/// Where in the flow a violation was found.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Location {
+    /// Names of the process groups from the root, joined with `/`.
+    pub group_path: String,
+    pub kind: ComponentKind,
+    pub component_id: Option<String>,
+    pub component_name: Option<String>,
+}
+
+impl Location {
+    pub fn new(
+        group: &GroupContext<'_>,
+        kind: ComponentKind,
+        id: &Option<String>,
+        name: &Option<String>,
+    ) -> Self {
+        Self {
+            group_path: group.path.clone(),
+            kind,
+            component_id: id.clone(),
+            component_name: name.clone(),
+        }
+    }
+
+    /// The group path followed by the component name (or id).
+    pub fn qualified_name(&self) -> String {
+        match self.component_name.as_ref().or(self.component_id.as_ref()) {
+            Some(name) => format!("{}/{}", self.group_path, name),
+            None => self.group_path.clone(),
+        }
+    }
+}
+
+/// A problem reported by a rule.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Violation {
+    pub message: String,
+    pub location: Location,
+}
+
+/// A check over a flow.
+pub trait Rule: Send + Sync {
+    /// A stable, kebab-case id, used to configure the rule and in SARIF output.
+    fn id(&self) -> &str;
+
+    /// One sentence describing what the rule looks for.
+    fn description(&self) -> &str;
+
+    /// The severity of the findings unless the `Linter` overrides it.
+    fn default_severity(&self) -> Severity;
+
+    fn check(&self, flow: &LintContext<'_>) -> Vec<Violation>;
+}
+
+/// A process group, with its path from the root.
+#[derive(Debug, Clone)]
+pub struct GroupContext<'a> {
+    pub group: &'a VersionedProcessGroup,
+    pub path: String,
+}
+
+/// The flow being linted, with every process group flattened.
+#[derive(Debug)]
+pub struct LintContext<'a> {
+    pub snapshot: &'a FlowSnapshot,
+    /// All process groups, the root first.
+    pub groups: Vec<GroupContext<'a>>,
+}
+
+impl<'a> LintContext<'a> {
+    pub fn new(snapshot: &'a FlowSnapshot) -> Self {
+        let mut groups = Vec::new();
+        collect_groups(&snapshot.flow_contents, String::new(), &mut groups);
+        Self { snapshot, groups }
+    }
+
+    /// The names of the parameters available to a group, through its bound
+    /// context and the contexts that one inherits. `None` when the group has
+    /// no parameter context.
+    pub fn parameter_names(&self, group: &VersionedProcessGroup) -> Option<BTreeSet<String>> {
+        let context = group.parameter_context_name.as_ref()?;
+        let mut names = BTreeSet::new();
+        let mut visited = BTreeSet::new();
+        self.collect_parameters(context, &mut names, &mut visited);
+        Some(names)
+    }
+
+    fn collect_parameters(
+        &self,
+        context: &str,
+        names: &mut BTreeSet<String>,
+        visited: &mut BTreeSet<String>,
+    ) {
+        if !visited.insert(context.to_string()) {
+            return;
+        }
+        let Some(context) = self.context(context) else {
+            return;
+        };
+        names.extend(
+            context
+                .parameters
+                .iter()
+                .flatten()
+                .filter_map(|parameter| parameter.name.clone()),
+        );
+        for inherited in &context.inherited_parameter_contexts {
+            self.collect_parameters(inherited, names, visited);
+        }
+    }
+
+    /// Parameter contexts are keyed by name, but look at `name` too.
+    fn context(&self, name: &str) -> Option<&'a VersionedParameterContext> {
+        self.snapshot.parameter_contexts.get(name).or_else(|| {
+            self.snapshot
+                .parameter_contexts
+                .values()
+                .find(|context| context.name.as_deref() == Some(name))
+        })
+    }
+}
+
+fn collect_groups<'a>(
+    group: &'a VersionedProcessGroup,
+    parent: String,
+    groups: &mut Vec<GroupContext<'a>>,
+) {
+    let name = group.name.as_deref().unwrap_or("root");
+    let path = if parent.is_empty() {
+        name.to_string()
+    } else {
+        format!("{}/{}", parent, name)
+    };
+    groups.push(GroupContext {
+        group,
+        path: path.clone(),
+    });
+    for child in group.process_groups.iter().flatten() {
+        collect_groups(child, path.clone(), groups);
+    }
+}
+
+/// A violation, with the rule that found it and its effective severity.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Finding {
+    pub rule_id: String,
+    pub severity: Severity,
+    pub message: String,
+    pub location: Location,
+}
+
+/// A rule as it was run.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct RuleInfo {
+    pub id: String,
+    pub description: String,
+    pub severity: Severity,
+}
+
+/// The result of linting a flow.
+#[derive(Serialize, Debug, Clone, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct LintReport {
+    pub rules: Vec<RuleInfo>,
+    pub findings: Vec<Finding>,
+}
+
+impl LintReport {
+    pub fn is_clean(&self) -> bool {
+        self.findings.is_empty()
+    }
+
+    /// The number of findings of a severity.
+    pub fn count(&self, severity: Severity) -> usize {
+        self.findings
+            .iter()
+            .filter(|finding| finding.severity == severity)
+            .count()
+    }
+
+    /// The most serious severity found, if any.
+    pub fn max_severity(&self) -> Option<Severity> {
+        self.findings.iter().map(|finding| finding.severity).max()
+    }
+
+    pub fn has_errors(&self) -> bool {
+        self.max_severity() == Some(Severity::Error)
+    }
+}
+
+/// Runs rules over flows.
+pub struct Linter {
+    rules: Vec<Box<dyn Rule>>,
+    /// Per rule id: the severity to report, or `None` to disable the rule.
+    overrides: HashMap<String, Option<Severity>>,
+}
+
+impl Default for Linter {
+    /// Every built-in rule, with its default settings.
+    fn default() -> Self {
+        Self {
+            rules: rules::default_rules(),
+            overrides: HashMap::new(),
+        }
+    }
+}
+
+impl std::fmt::Debug for Linter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Linter")
+            .field(
+                "rules",
+                &self.rules.iter().map(|rule| rule.id()).collect::<Vec<_>>(),
+            )
+            .field("overrides", &self.overrides)
+            .finish()
+    }
+}
+
+impl Linter {
+    /// A linter without any rule.
+    pub fn new() -> Self {
+        Self {
+            rules: Vec::new(),
+            overrides: HashMap::new(),
+        }
+    }
+
+    /// Adds a rule, replacing any rule with the same id.
+    pub fn with_rule(mut self, rule: Box<dyn Rule>) -> Self {
+        self.rules.retain(|existing| existing.id() != rule.id());
+        self.rules.push(rule);
+        self
+    }
+
+    /// Reports the findings of a rule with this severity.
+    pub fn severity(mut self, rule_id: &str, severity: Severity) -> Self {
+        self.overrides.insert(rule_id.to_string(), Some(severity));
+        self
+    }
+
+    /// Stops running a rule.
+    pub fn disable(mut self, rule_id: &str) -> Self {
+        self.overrides.insert(rule_id.to_string(), None);
+        self
+    }
+
+    /// Runs every enabled rule over the flow.
+    pub fn lint(&self, snapshot: &FlowSnapshot) -> LintReport {
+        let context = LintContext::new(snapshot);
+        let mut report = LintReport::default();
+        for rule in &self.rules {
+            let severity = match self.overrides.get(rule.id()) {
+                Some(None) => continue,
+                Some(Some(severity)) => *severity,
+                None => rule.default_severity(),
+            };
+            report.rules.push(RuleInfo {
+                id: rule.id().to_string(),
+                description: rule.description().to_string(),
+                severity,
+            });
+            report
+                .findings
+                .extend(rule.check(&context).into_iter().map(|violation| Finding {
+                    rule_id: rule.id().to_string(),
+                    severity,
+                    message: violation.message,
+                    location: violation.location,
+                }));
+        }
+        report
+    }
+
+    /// Reads a `FlowSnapshot` JSON file (as exported by NiFi) and lints it.
+    ///
+    /// # Errors
+    /// Returns an error if the file cannot be read or is not a flow snapshot.
+    pub fn lint_file(&self, path: &Path) -> anyhow::Result<LintReport> {
+        let json = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        let snapshot: FlowSnapshot = serde_json::from_str(&json)
+            .with_context(|| format!("{} is not a flow snapshot", path.display()))?;
+        Ok(self.lint(&snapshot))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use serde_json::json;
+
+    pub(crate) fn snapshot(flow_contents: serde_json::Value) -> FlowSnapshot {
+        serde_json::from_value(json!({
+            "flowContents": flow_contents,
+            "externalControllerServices": {},
+            "parameterContexts": {
+                "base": {"name": "base", "parameters": [{"name": "db.url", "value": "jdbc:x"}]},
+                "app": {"name": "app", "inheritedParameterContexts": ["base"],
+                        "parameters": [{"name": "db.user", "value": "app"}]},
+            },
+            "parameterProviders": {},
+            "flowEncodingVersion": "1.0",
+            "latest": true,
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_lint() {
+        let flow = snapshot(json!({
+            "identifier": "root", "name": "root", "parameterContextName": "app",
+            "processors": [
+                {"identifier": "p1", "name": "Query", "concurrentlySchedulableTaskCount": 1,
+                 "properties": {"Password": "hunter2", "URL": "#{db.url}", "User": "#{db.user}"},
+                 "propertyDescriptors": {"Password": {"name": "Password", "sensitive": false}},
+                 "autoTerminatedRelationships": ["failure"]},
+            ],
+        }));
+
+        let report = Linter::default()
+            .severity("unconnected-relationship", Severity::Note)
+            .disable("missing-concurrency")
+            .lint(&flow);
+        let findings: Vec<(&str, Severity)> = report
+            .findings
+            .iter()
+            .map(|finding| (finding.rule_id.as_str(), finding.severity))
+            .collect();
+
+        assert_eq!(
+            findings,
+            vec![("hardcoded-secret", Severity::Error)],
+            "{:#?}",
+            report
+        );
+        assert!(report.has_errors());
+        assert!(
+            report
+                .rules
+                .iter()
+                .all(|rule| rule.id != "missing-concurrency")
+        );
+        assert_eq!(report.findings[0].location.qualified_name(), "root/Query");
+    }
+}
@@ -0,0 +1,143 @@
+//! SARIF 2.1.0 output for lint reports.
+//!
+//! Each finding becomes a result with a logical location (the component,
+//! qualified by its group path) and, when the path of the linted file is
+//! given, a physical location pointing at that file so code-scanning UIs can
+//! attach it to the repository.
+
+use crate::deploy::lint::{LintReport, Severity};
+use serde_json::{Value, json};
+
+const SARIF_SCHEMA: &str = "https://json.schemastore.org/sarif-2.1.0.json";
+
+fn level(severity: Severity) -> &'static str {
+    match severity {
+        Severity::Note => "note",
+        Severity::Warning => "warning",
+        Severity::Error => "error",
+    }
+}
+
+/// The SARIF `kind` of a logical location.
+fn kind(kind: &impl serde::Serialize) -> Value {
+    serde_json::to_value(kind).unwrap_or(Value::Null)
+}
+
+impl LintReport {
+    /// Renders the report as a SARIF 2.1.0 log with a single run.
+    ///
+    /// `artifact_uri` is the path of the linted flow file, relative to the
+    /// repository root.
+    pub fn to_sarif(&self, artifact_uri: Option<&str>) -> Value {
+        let rules: Vec<Value> = self
+            .rules
+            .iter()
+            .map(|rule| {
+                json!({
+                    "id": rule.id,
+                    "shortDescription": {"text": rule.description},
+                    "defaultConfiguration": {"level": level(rule.severity)},
+                })
+            })
+            .collect();
+
+        let results: Vec<Value> = self
+            .findings
+            .iter()
+            .map(|finding| {
+                let mut location = json!({
+                    "logicalLocations": [{
+                        "fullyQualifiedName": finding.location.qualified_name(),
+                        "name": finding.location.component_name,
+                        "kind": kind(&finding.location.kind),
+                    }],
+                });
+                if let Some(uri) = artifact_uri {
+                    location["physicalLocation"] = json!({"artifactLocation": {"uri": uri}});
+                }
+                let mut result = json!({
+                    "ruleId": finding.rule_id,
+                    "level": level(finding.severity),
+                    "message": {"text": finding.message},
+                    "locations": [location],
+                });
+                if let Some(index) = self
+                    .rules
+                    .iter()
+                    .position(|rule| rule.id == finding.rule_id)
+                {
+                    result["ruleIndex"] = json!(index);
+                }
+                if let Some(id) = &finding.location.component_id {
+                    result["partialFingerprints"] = json!({
+                        "componentId/v1": format!("{}:{}", id, finding.message),
+                    });
+                }
+                result
+            })
+            .collect();
+
+        json!({
+            "$schema": SARIF_SCHEMA,
+            "version": "2.1.0",
+            "runs": [{
+                "tool": {
+                    "driver": {
+                        "name": env!("CARGO_PKG_NAME"),
+                        "version": env!("CARGO_PKG_VERSION"),
+                        "rules": rules,
+                    },
+                },
+                "results": results,
+            }],
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::deploy::lint::Linter;
+    use crate::deploy::lint::rules::MissingConcurrency;
+    use crate::deploy::lint::test::snapshot;
+    use serde_json::json;
+
+    #[test]
+    fn test_to_sarif() {
+        let flow = snapshot(json!({
+            "identifier": "root", "name": "root",
+            "processors": [{"identifier": "p1", "name": "Generate", "properties": {}}],
+        }));
+        let report = Linter::new()
+            .with_rule(Box::new(MissingConcurrency))
+            .lint(&flow);
+
+        let sarif = report.to_sarif(Some("flows/main.json"));
+        let run = &sarif["runs"][0];
+
+        assert_eq!(sarif["version"], "2.1.0");
+        assert_eq!(
+            run["tool"]["driver"]["rules"][0]["id"],
+            "missing-concurrency"
+        );
+        assert_eq!(
+            run["results"][0],
+            json!({
+                "ruleId": "missing-concurrency",
+                "ruleIndex": 0,
+                "level": "warning",
+                "message": {"text": "Concurrent tasks are not configured"},
+                "locations": [{
+                    "logicalLocations": [{
+                        "fullyQualifiedName": "root/Generate",
+                        "name": "Generate",
+                        "kind": "processor",
+                    }],
+                    "physicalLocation": {"artifactLocation": {"uri": "flows/main.json"}},
+                }],
+                "partialFingerprints": {
+                    "componentId/v1": "p1:Concurrent tasks are not configured",
+                },
+            })
+        );
+    }
+}
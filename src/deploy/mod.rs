@@ -0,0 +1,28 @@
+//! # Deploy Module
+//!
+//! Building blocks for declaring NiFi flows and deploying them repeatedly
+//! against the same instance.
+//!
+//! * `diff` - Offline comparison of two flow snapshots, for reviewing flow
+//!   changes without a running NiFi.
+//! * `hooks` - Lifecycle hooks that can observe or veto the steps of a deployment,
+//!   and the stream of events it emits.
+//! * `layout` - Automatic canvas layout of generated flows before they are uploaded.
+//! * `lint` - Offline, pluggable checks over a flow snapshot, with SARIF output.
+//! * `orchestrator` - Runs a deployment plan step by step, calling the hooks and
+//!   publishing events, with rollback on failure.
+//! * `preflight` - Checks the permissions a deployment needs before it starts,
+//!   listing every missing one.
+//! * `rollback` - Snapshots of the affected process groups, restored automatically
+//!   when a deployment fails halfway.
+//! * `state` - A persistent mapping from declared (logical) component names to
+//!   the UUIDs NiFi assigned to them, per environment.
+
+pub mod diff;
+pub mod hooks;
+pub mod layout;
+pub mod lint;
+pub mod orchestrator;
+pub mod preflight;
+pub mod rollback;
+pub mod state;
@@ -0,0 +1,372 @@
+//! # Orchestrator Module
+//!
+//! Runs a `Plan` on top of a `Deployment`: the hooks registered with
+//! `Deployment::with_hook` are called around every step, each step is
+//! reported as a `DeployEvent` to the streams from `Deployment::events`, and
+//! the snapshotted groups are rolled back when a step fails or a hook aborts
+//! (see the `rollback` module).
+
+use crate::deploy::hooks::{
+    DeployEvent, DeployEventStream, DeployHook, DeploySummary, HookDecision, Plan,
+};
+use crate::deploy::rollback::{DeployError, Deployment};
+use std::future::Future;
+use std::sync::Arc;
+use tokio::sync::mpsc::unbounded_channel;
+use tracing::{info, warn};
+
+impl Deployment {
+    /// Registers a hook called by `deploy`. Hooks run in registration order.
+    pub fn with_hook(mut self, hook: Arc<dyn DeployHook>) -> Self {
+        self.hooks.push(hook);
+        self
+    }
+
+    /// Returns a stream receiving every event published from now on.
+    ///
+    /// Can be called several times; each stream gets its own copy of the events.
+    pub fn events(&mut self) -> DeployEventStream {
+        let (sender, receiver) = unbounded_channel();
+        self.subscribers.push(sender);
+        DeployEventStream::new(receiver)
+    }
+
+    /// Plans and applies a deployment, change by change.
+    ///
+    /// The sequence is:
+    /// 1. `DeployHook::before_plan` on every hook, then `planner` builds the plan.
+    /// 2. Every group in `group_ids` is snapshotted.
+    /// 3. For each step, `DeployHook::before_apply` decides whether it is applied,
+    ///    skipped, or the deployment is aborted; applied steps are followed by
+    ///    `DeployHook::after_apply`.
+    /// 4. On failure (or an `Abort`), `DeployHook::on_error` is called and the
+    ///    groups are rolled back. An `Abort` before any step was applied
+    ///    leaves nothing to roll back, so the rollback is skipped.
+    /// 5. `DeployHook::after_deploy` is called with the `DeploySummary`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `DeployError::Aborted` if a hook or the planner fails before
+    /// anything is snapshotted, or a hook aborts before any step is applied,
+    /// `DeployError::SnapshotError` if a group cannot be snapshotted, or
+    /// `DeployError::RolledBack` if a step fails or is aborted later on.
+    pub async fn deploy<F, Fut>(
+        &mut self,
+        group_ids: &[&str],
+        planner: F,
+    ) -> Result<DeploySummary, DeployError>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = anyhow::Result<Plan>>,
+    {
+        self.publish(DeployEvent::PlanStarted);
+        let plan = match self.prepare(group_ids, planner).await {
+            Ok(plan) => plan,
+            Err(err) => {
+                let source = match &err {
+                    DeployError::Aborted(source)
+                    | DeployError::SnapshotError(source)
+                    | DeployError::RolledBack { source, .. } => source,
+                };
+                for hook in &self.hooks {
+                    hook.on_error(source).await;
+                }
+                let summary = DeploySummary {
+                    error: Some(source.to_string()),
+                    ..DeploySummary::default()
+                };
+                self.finish(&summary).await;
+                return Err(err);
+            },
+        };
+
+        let mut summary = DeploySummary::default();
+        let mut failure = None;
+        let mut aborted = false;
+        'steps: for step in plan.steps {
+            let change = step.change.clone();
+            for hook in &self.hooks {
+                match hook.before_apply(&change).await {
+                    HookDecision::Proceed => {},
+                    HookDecision::Skip(reason) => {
+                        info!("Skipping {}: {}", change, reason);
+                        self.publish(DeployEvent::ChangeSkipped {
+                            change: change.clone(),
+                            reason: reason.clone(),
+                        });
+                        summary.skipped.push((change, reason));
+                        continue 'steps;
+                    },
+                    HookDecision::Abort(reason) => {
+                        failure = Some(anyhow::anyhow!("{} vetoed: {}", change, reason));
+                        aborted = true;
+                        break 'steps;
+                    },
+                }
+            }
+
+            self.publish(DeployEvent::ChangeStarted {
+                change: change.clone(),
+            });
+            if let Err(err) = step.run().await {
+                self.publish(DeployEvent::ChangeFailed {
+                    change: change.clone(),
+                    error: err.to_string(),
+                });
+                failure = Some(err.context(format!("{} failed", change)));
+                break;
+            }
+            for hook in &self.hooks {
+                hook.after_apply(&change).await;
+            }
+            self.publish(DeployEvent::ChangeApplied {
+                change: change.clone(),
+            });
+            summary.applied.push(change);
+        }
+
+        match failure {
+            None => {
+                self.finish(&summary).await;
+                Ok(summary)
+            },
+            Some(source) if aborted && summary.applied.is_empty() => {
+                warn!("Deployment aborted before any change: {}", source);
+                for hook in &self.hooks {
+                    hook.on_error(&source).await;
+                }
+                summary.error = Some(source.to_string());
+                self.finish(&summary).await;
+                Err(DeployError::Aborted(source))
+            },
+            Some(source) => {
+                warn!("Deployment failed, rolling back: {}", source);
+                for hook in &self.hooks {
+                    hook.on_error(&source).await;
+                }
+                let report = self.rollback().await;
+                self.publish(DeployEvent::RolledBack {
+                    report: report.clone(),
+                });
+                summary.error = Some(source.to_string());
+                summary.rollback = Some(report.clone());
+                self.finish(&summary).await;
+                Err(DeployError::RolledBack { source, report })
+            },
+        }
+    }
+
+    async fn prepare<F, Fut>(&mut self, group_ids: &[&str], planner: F) -> Result<Plan, DeployError>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = anyhow::Result<Plan>>,
+    {
+        for hook in &self.hooks {
+            hook.before_plan().await.map_err(DeployError::Aborted)?;
+        }
+        let plan = planner().await.map_err(DeployError::Aborted)?;
+        self.publish(DeployEvent::PlanReady {
+            changes: plan.changes(),
+        });
+        for id in group_ids {
+            self.snapshot_group(id)
+                .await
+                .map_err(DeployError::SnapshotError)?;
+            self.publish(DeployEvent::GroupSnapshotted {
+                group_id: id.to_string(),
+            });
+        }
+        Ok(plan)
+    }
+
+    async fn finish(&mut self, summary: &DeploySummary) {
+        for hook in &self.hooks {
+            hook.after_deploy(summary).await;
+        }
+        self.publish(DeployEvent::Finished {
+            summary: summary.clone(),
+        });
+    }
+
+    /// Sends `event` to every live subscriber, forgetting the dropped ones.
+    fn publish(&mut self, event: DeployEvent) {
+        self.subscribers
+            .retain(|subscriber| subscriber.send(event.clone()).is_ok());
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::common::client::HttpClient;
+    use crate::common::config::Config;
+    use crate::deploy::hooks::{ChangeAction, PlannedChange, Step};
+    use crate::deploy::state::ComponentKind;
+    use async_trait::async_trait;
+    use futures::StreamExt;
+    use futures::future::BoxFuture;
+    use std::collections::HashMap;
+
+    /// Refuses to delete connections that still hold FlowFiles.
+    ///
+    /// `queued` returns the number of FlowFiles queued in a connection; in a
+    /// real deployment it reads `flowFilesQueued` from
+    /// `Status::get_connection_status`.
+    struct KeepQueuedConnections<F> {
+        queued: F,
+    }
+
+    #[async_trait]
+    impl<F> DeployHook for KeepQueuedConnections<F>
+    where
+        F: Fn(String) -> BoxFuture<'static, anyhow::Result<i32>> + Send + Sync,
+    {
+        async fn before_apply(&self, change: &PlannedChange) -> HookDecision {
+            if change.kind != ComponentKind::Connection || change.action != ChangeAction::Delete {
+                return HookDecision::Proceed;
+            }
+            let Some(id) = change.component_id.clone() else {
+                return HookDecision::Proceed;
+            };
+            match (self.queued)(id).await {
+                Ok(0) => HookDecision::Proceed,
+                Ok(count) => HookDecision::Skip(format!("{} FlowFiles still queued", count)),
+                Err(err) => HookDecision::Abort(format!("queue size unknown: {}", err)),
+            }
+        }
+    }
+
+    fn change(kind: ComponentKind, action: ChangeAction, name: &str) -> PlannedChange {
+        PlannedChange {
+            kind,
+            action,
+            logical_id: Some(name.to_string()),
+            component_id: Some(format!("{}-id", name)),
+            group_id: None,
+            name: Some(name.to_string()),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_deploy_hooks_and_events() {
+        // --- 1. Setup: no groups to snapshot, so nothing hits the network ---
+        let queues: HashMap<String, i32> = [
+            ("router-out-id".to_string(), 12),
+            ("drained-id".to_string(), 0),
+        ]
+        .into();
+        let hook = KeepQueuedConnections {
+            queued: move |id: String| -> BoxFuture<'static, anyhow::Result<i32>> {
+                let count = queues.get(&id).copied().unwrap_or(0);
+                Box::pin(async move { Ok(count) })
+            },
+        };
+        let mut deployment =
+            Deployment::new(Arc::new(HttpClient::new()), Arc::new(Config::default()))
+                .with_hook(Arc::new(hook));
+        let events = deployment.events();
+
+        // --- 2. Deploy ---
+        let summary = deployment
+            .deploy(&[], || async {
+                let mut plan = Plan::new();
+                plan.push(Step::new(
+                    change(ComponentKind::Processor, ChangeAction::Update, "router"),
+                    || async { Ok(()) },
+                ));
+                plan.push(Step::new(
+                    change(
+                        ComponentKind::Connection,
+                        ChangeAction::Delete,
+                        "router-out",
+                    ),
+                    || async { anyhow::bail!("must not run") },
+                ));
+                plan.push(Step::new(
+                    change(ComponentKind::Connection, ChangeAction::Delete, "drained"),
+                    || async { Ok(()) },
+                ));
+                Ok(plan)
+            })
+            .await
+            .unwrap();
+        drop(deployment);
+
+        // --- 3. Assert ---
+        assert!(summary.is_success());
+        assert_eq!(summary.applied.len(), 2);
+        assert_eq!(summary.skipped.len(), 1);
+        assert_eq!(summary.skipped[0].0.name.as_deref(), Some("router-out"));
+        assert_eq!(summary.skipped[0].1, "12 FlowFiles still queued");
+
+        let events: Vec<DeployEvent> = events.collect().await;
+        assert!(matches!(events.first(), Some(DeployEvent::PlanStarted)));
+        assert!(
+            events
+                .iter()
+                .any(|event| matches!(event, DeployEvent::ChangeSkipped { .. }))
+        );
+        assert!(matches!(events.last(), Some(DeployEvent::Finished { .. })));
+    }
+
+    #[tokio::test]
+    async fn test_deploy_failure_is_rolled_back() {
+        let mut deployment =
+            Deployment::new(Arc::new(HttpClient::new()), Arc::new(Config::default()));
+        let result = deployment
+            .deploy(&[], || async {
+                let mut plan = Plan::new();
+                plan.push(Step::new(
+                    change(ComponentKind::Processor, ChangeAction::Create, "broken"),
+                    || async { anyhow::bail!("409 Conflict") },
+                ));
+                Ok(plan)
+            })
+            .await;
+        match result {
+            Err(DeployError::RolledBack { report, .. }) => assert!(report.is_complete()),
+            other => panic!("unexpected result: {:?}", other.map(|_| ())),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_deploy_abort_before_any_change_skips_rollback() {
+        // --- 1. Setup: the queue size of the first change cannot be read ---
+        let hook = KeepQueuedConnections {
+            queued: |_: String| -> BoxFuture<'static, anyhow::Result<i32>> {
+                Box::pin(async { anyhow::bail!("503 Service Unavailable") })
+            },
+        };
+        let mut deployment =
+            Deployment::new(Arc::new(HttpClient::new()), Arc::new(Config::default()))
+                .with_hook(Arc::new(hook));
+        let events = deployment.events();
+
+        // --- 2. Deploy ---
+        let result = deployment
+            .deploy(&[], || async {
+                let mut plan = Plan::new();
+                plan.push(Step::new(
+                    change(
+                        ComponentKind::Connection,
+                        ChangeAction::Delete,
+                        "router-out",
+                    ),
+                    || async { anyhow::bail!("must not run") },
+                ));
+                Ok(plan)
+            })
+            .await;
+        drop(deployment);
+
+        // --- 3. Assert ---
+        assert!(matches!(result, Err(DeployError::Aborted(_))));
+        let events: Vec<DeployEvent> = events.collect().await;
+        assert!(
+            !events
+                .iter()
+                .any(|event| matches!(event, DeployEvent::RolledBack { .. }))
+        );
+        assert!(matches!(events.last(), Some(DeployEvent::Finished { .. })));
+    }
+}
@@ -0,0 +1,396 @@
+//! Pre-flight permission checks.
+//!
+//! A deployment declares the `Operation`s it is about to perform; `Preflight`
+//! checks them against `/flow/current-user` and the `PermissionsDto` of every
+//! process group and parameter context involved, and lists *every* missing
+//! permission at once, so a deployment fails before changing anything instead
+//! of dying halfway with a 403.
+
+use crate::common::client::{HttpClient, HttpClientError};
+use crate::common::config::Config;
+use crate::proxy::v260::api::{CurrentUserEntity, PermissionsDto};
+use crate::proxy::v260::flow::Flow;
+use crate::proxy::v260::parameter_context::ParameterContext;
+use crate::proxy::v260::process_group::ProcessGroup;
+use reqwest::StatusCode;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Arc;
+use thiserror::Error;
+
+/// An operation a deployment plans to perform.
+#[derive(Serialize, Debug, Clone, PartialEq, Eq, Hash)]
+#[serde(rename_all = "camelCase")]
+pub enum Operation {
+    ReadProcessGroup(String),
+    /// Adding, changing or removing components in a process group, including
+    /// its controller services.
+    WriteProcessGroup(String),
+    /// Creating a controller service in a process group, or at controller
+    /// level when `None`.
+    CreateControllerService(Option<String>),
+    /// Changing the controller: reporting tasks, registry clients, parameter
+    /// providers, cluster nodes.
+    WriteController,
+    CreateParameterContext,
+    WriteParameterContext(String),
+    /// Creating a restricted component; with the id of its required
+    /// permission (e.g. `read-filesystem`) when known.
+    CreateRestrictedComponent(Option<String>),
+    VersionFlows,
+    ReadProvenance,
+    WritePolicies,
+    WriteTenants,
+}
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Permission {
+    Read,
+    Write,
+}
+
+impl fmt::Display for Permission {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Permission::Read => f.write_str("read"),
+            Permission::Write => f.write_str("write"),
+        }
+    }
+}
+
+/// Where the permissions needed by an operation come from.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum Scope {
+    ProcessGroup(String),
+    ParameterContext(String),
+    /// A top-level permission of the current user.
+    User,
+}
+
+impl Operation {
+    fn requirement(&self) -> (Scope, String, Permission) {
+        match self {
+            Operation::ReadProcessGroup(id) => (
+                Scope::ProcessGroup(id.clone()),
+                format!("/process-groups/{}", id),
+                Permission::Read,
+            ),
+            Operation::WriteProcessGroup(id) | Operation::CreateControllerService(Some(id)) => (
+                Scope::ProcessGroup(id.clone()),
+                format!("/process-groups/{}", id),
+                Permission::Write,
+            ),
+            Operation::WriteParameterContext(id) => (
+                Scope::ParameterContext(id.clone()),
+                format!("/parameter-contexts/{}", id),
+                Permission::Write,
+            ),
+            Operation::CreateControllerService(None) | Operation::WriteController => {
+                (Scope::User, "/controller".to_string(), Permission::Write)
+            },
+            Operation::CreateParameterContext => (
+                Scope::User,
+                "/parameter-contexts".to_string(),
+                Permission::Write,
+            ),
+            Operation::CreateRestrictedComponent(None) => (
+                Scope::User,
+                "/restricted-components".to_string(),
+                Permission::Write,
+            ),
+            Operation::CreateRestrictedComponent(Some(restriction)) => (
+                Scope::User,
+                format!("/restricted-components/{}", restriction),
+                Permission::Write,
+            ),
+            Operation::VersionFlows => (
+                Scope::User,
+                "/flow (versioning)".to_string(),
+                Permission::Write,
+            ),
+            Operation::ReadProvenance => (Scope::User, "/provenance".to_string(), Permission::Read),
+            Operation::WritePolicies => (Scope::User, "/policies".to_string(), Permission::Write),
+            Operation::WriteTenants => (Scope::User, "/tenants".to_string(), Permission::Write),
+        }
+    }
+
+    /// Whether the current user is granted a top-level operation.
+    fn granted_by(&self, user: &CurrentUserEntity) -> bool {
+        let write = |permissions: &Option<PermissionsDto>| allows(permissions, Permission::Write);
+        match self {
+            Operation::CreateControllerService(None) | Operation::WriteController => {
+                write(&user.controller_permissions)
+            },
+            Operation::CreateParameterContext => write(&user.parameter_context_permissions),
+            Operation::CreateRestrictedComponent(restriction) => {
+                write(&user.restricted_components_permissions)
+                    || restriction.as_ref().is_some_and(|restriction| {
+                        user.component_restriction_permissions
+                            .iter()
+                            .flatten()
+                            .any(|granted| {
+                                granted
+                                    .required_permission
+                                    .as_ref()
+                                    .and_then(|required| required.id.as_ref())
+                                    == Some(restriction)
+                                    && write(&granted.permissions)
+                            })
+                    })
+            },
+            Operation::VersionFlows => user.can_version_flows.unwrap_or(false),
+            Operation::ReadProvenance => allows(&user.provenance_permissions, Permission::Read),
+            Operation::WritePolicies => write(&user.policies_permissions),
+            Operation::WriteTenants => write(&user.tenants_permissions),
+            Operation::ReadProcessGroup(_)
+            | Operation::WriteProcessGroup(_)
+            | Operation::CreateControllerService(Some(_))
+            | Operation::WriteParameterContext(_) => false,
+        }
+    }
+}
+
+fn allows(permissions: &Option<PermissionsDto>, permission: Permission) -> bool {
+    permissions
+        .as_ref()
+        .is_some_and(|permissions| match permission {
+            Permission::Read => permissions.can_read.unwrap_or(false),
+            Permission::Write => permissions.can_write.unwrap_or(false),
+        })
+}
+
+/// A permission the current user lacks.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct MissingPermission {
+    /// The first planned operation that needs it.
+    pub operation: Operation,
+    pub resource: String,
+    pub permission: Permission,
+}
+
+impl fmt::Display for MissingPermission {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} on {} (needed by {:?})",
+            self.permission, self.resource, self.operation
+        )
+    }
+}
+
+/// The result of a pre-flight check.
+#[derive(Serialize, Debug, Clone, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct PreflightReport {
+    pub identity: Option<String>,
+    pub missing: Vec<MissingPermission>,
+}
+
+impl PreflightReport {
+    /// `true` when every planned operation is permitted.
+    pub fn is_ok(&self) -> bool {
+        self.missing.is_empty()
+    }
+
+    /// Turns a report with missing permissions into an error.
+    pub fn into_result(self) -> Result<(), PreflightError> {
+        if self.is_ok() {
+            Ok(())
+        } else {
+            Err(PreflightError::MissingPermissions(self))
+        }
+    }
+}
+
+impl fmt::Display for PreflightReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} lacks {} permission(s)",
+            self.identity.as_deref().unwrap_or("anonymous"),
+            self.missing.len()
+        )?;
+        for missing in &self.missing {
+            write!(f, "\n  - {}", missing)?;
+        }
+        Ok(())
+    }
+}
+
+/// Represents the ways a pre-flight check can fail.
+#[derive(Debug, Error)]
+pub enum PreflightError {
+    #[error("PreflightError::MissingPermissions - {0}")]
+    MissingPermissions(PreflightReport),
+}
+
+/// Checks planned operations against the permissions of the logged-in identity.
+#[derive(Debug)]
+pub struct Preflight {
+    flow: Flow,
+    process_group: ProcessGroup,
+    parameter_context: ParameterContext,
+}
+
+impl Preflight {
+    pub fn new(client: Arc<HttpClient>, config: Arc<Config>) -> Self {
+        Self {
+            flow: Flow::new(client.clone(), config.clone()),
+            process_group: ProcessGroup::new(client.clone(), config.clone()),
+            parameter_context: ParameterContext::new(client, config),
+        }
+    }
+
+    /// Checks every operation and reports all the missing permissions. A
+    /// process group or parameter context the user may not even read counts
+    /// as granting nothing.
+    ///
+    /// # Errors
+    /// Returns an error if a request fails for another reason than 403.
+    pub async fn check(&self, operations: &[Operation]) -> anyhow::Result<PreflightReport> {
+        let user = self.flow.get_current_user().await?;
+        let mut scopes: HashMap<Scope, Option<PermissionsDto>> = HashMap::new();
+        for operation in operations {
+            let (scope, _, _) = operation.requirement();
+            if scope == Scope::User || scopes.contains_key(&scope) {
+                continue;
+            }
+            let permissions = self.permissions_of(&scope).await?;
+            scopes.insert(scope, permissions);
+        }
+        Ok(evaluate(operations, &user, &scopes))
+    }
+
+    async fn permissions_of(&self, scope: &Scope) -> anyhow::Result<Option<PermissionsDto>> {
+        let permissions = match scope {
+            Scope::ProcessGroup(id) => self
+                .process_group
+                .get_process_group(id)
+                .await
+                .map(|entity| entity.permissions),
+            Scope::ParameterContext(id) => self
+                .parameter_context
+                .get_parameter_context_by_id(id)
+                .await
+                .map(|entity| entity.permissions),
+            Scope::User => return Ok(None),
+        };
+        match permissions {
+            Ok(permissions) => Ok(permissions),
+            Err(err) => match err.downcast::<HttpClientError>() {
+                Ok(HttpClientError::HttpError { status, .. })
+                    if status == StatusCode::FORBIDDEN =>
+                {
+                    Ok(None)
+                },
+                Ok(err) => Err(err.into()),
+                Err(err) => Err(err),
+            },
+        }
+    }
+}
+
+/// Checks operations against the current user and the permissions fetched
+/// for each process group and parameter context. Each missing permission is
+/// reported once.
+fn evaluate(
+    operations: &[Operation],
+    user: &CurrentUserEntity,
+    scopes: &HashMap<Scope, Option<PermissionsDto>>,
+) -> PreflightReport {
+    let mut missing: Vec<MissingPermission> = Vec::new();
+    for operation in operations {
+        let (scope, resource, permission) = operation.requirement();
+        let granted = match &scope {
+            Scope::User => operation.granted_by(user),
+            scope => allows(scopes.get(scope).unwrap_or(&None), permission),
+        };
+        let reported = missing
+            .iter()
+            .any(|m| m.resource == resource && m.permission == permission);
+        if !granted && !reported {
+            missing.push(MissingPermission {
+                operation: operation.clone(),
+                resource,
+                permission,
+            });
+        }
+    }
+    PreflightReport {
+        identity: user.identity.clone(),
+        missing,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::proxy::v260::api::{ComponentRestrictionPermissionDto, RequiredPermissionDto};
+
+    fn permissions(can_read: bool, can_write: bool) -> Option<PermissionsDto> {
+        Some(PermissionsDto {
+            can_read: Some(can_read),
+            can_write: Some(can_write),
+        })
+    }
+
+    #[test]
+    fn test_evaluate() {
+        let user = CurrentUserEntity {
+            identity: Some("deployer".to_string()),
+            controller_permissions: permissions(true, false),
+            parameter_context_permissions: permissions(true, true),
+            component_restriction_permissions: Some(vec![ComponentRestrictionPermissionDto {
+                permissions: permissions(true, true),
+                required_permission: Some(RequiredPermissionDto {
+                    id: Some("read-filesystem".to_string()),
+                    label: None,
+                }),
+            }]),
+            ..Default::default()
+        };
+        let scopes = HashMap::from([
+            (
+                Scope::ProcessGroup("pg-1".to_string()),
+                permissions(true, true),
+            ),
+            (
+                Scope::ProcessGroup("pg-2".to_string()),
+                permissions(true, false),
+            ),
+            (Scope::ProcessGroup("pg-3".to_string()), None),
+        ]);
+        let operations = [
+            Operation::WriteProcessGroup("pg-1".to_string()),
+            Operation::ReadProcessGroup("pg-2".to_string()),
+            Operation::WriteProcessGroup("pg-2".to_string()),
+            Operation::CreateControllerService(Some("pg-2".to_string())),
+            Operation::ReadProcessGroup("pg-3".to_string()),
+            Operation::CreateControllerService(None),
+            Operation::CreateParameterContext,
+            Operation::CreateRestrictedComponent(Some("read-filesystem".to_string())),
+            Operation::CreateRestrictedComponent(Some("execute-code".to_string())),
+            Operation::VersionFlows,
+        ];
+
+        let report = evaluate(&operations, &user, &scopes);
+        let missing: Vec<String> = report.missing.iter().map(|m| m.to_string()).collect();
+
+        assert_eq!(
+            missing,
+            vec![
+                "write on /process-groups/pg-2 (needed by WriteProcessGroup(\"pg-2\"))",
+                "read on /process-groups/pg-3 (needed by ReadProcessGroup(\"pg-3\"))",
+                "write on /controller (needed by CreateControllerService(None))",
+                "write on /restricted-components/execute-code (needed by CreateRestrictedComponent(Some(\"execute-code\")))",
+                "write on /flow (versioning) (needed by VersionFlows)",
+            ]
+        );
+        assert!(matches!(
+            report.into_result(),
+            Err(PreflightError::MissingPermissions(_))
+        ));
+    }
+}
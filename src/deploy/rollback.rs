@@ -0,0 +1,429 @@
+//! # Rollback Module
+//!
+//! Multi-step deployments can fail halfway: the update-request of a parameter
+//! context times out after processors were already changed, a connection is
+//! rejected, etc. Without a way back, NiFi is left half-deployed.
+//!
+//! `Deployment` snapshots every affected process group before anything is
+//! changed (through `/process-groups/{id}/download`), together with the run
+//! state of its processors and ports. If the deployment fails, each group is
+//! restored with a replace-request and its components are started or stopped
+//! to match the state they had before. A `RollbackReport` tells what was
+//! rolled back and what could not be.
+//!
+//! `Deployment::deploy` goes one step further and runs a `Plan` change by
+//! change, calling the registered hooks and publishing events (see the
+//! `orchestrator` and `hooks` modules).
+
+use crate::common::client::HttpClient;
+use crate::common::config::Config;
+use crate::common::polling::PollOptions;
+use crate::deploy::hooks::{DeployEvent, DeployHook};
+use crate::proxy::v260::api::{
+    RegisteredFlowSnapshot, RevisionDto, ScheduleComponentsEntity, ScheduleComponentsEntityState,
+};
+use crate::proxy::v260::process_group::{
+    GroupComponents, ProcessGroup, RunState, Schedulable, ScheduledComponentKind,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::future::Future;
+use std::sync::Arc;
+use thiserror::Error;
+use tokio::sync::mpsc::UnboundedSender;
+use tracing::{info, warn};
+
+/// The state of a process group right before a deployment touched it.
+#[derive(Debug, Clone)]
+pub struct GroupSnapshot {
+    pub group_id: String,
+    /// The full definition of the group, as downloaded.
+    pub flow: RegisteredFlowSnapshot,
+    /// Ids of the processors and ports that were running.
+    pub running: HashSet<String>,
+    /// `versionedComponentId`s of the processors and ports that were running,
+    /// used to recognise components recreated by the replace.
+    pub running_versioned: HashSet<String>,
+    /// Kind, group path and name of the running processors and ports without
+    /// a `versionedComponentId` (their group was never under version
+    /// control). Once recreated by the replace, they can only be recognised
+    /// this way.
+    pub running_unversioned: HashSet<ComponentKey>,
+}
+
+/// A component identified by kind, path of its group (below the snapshotted
+/// one) and name.
+pub type ComponentKey = (ScheduledComponentKind, String, String);
+
+fn component_key(schedulable: &Schedulable) -> Option<ComponentKey> {
+    let component = &schedulable.component;
+    Some((
+        component.kind,
+        schedulable.group_path.clone(),
+        component.name.clone()?,
+    ))
+}
+
+/// A process group restored by `Deployment::rollback`.
+#[derive(Deserialize, Serialize, Debug, Clone, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct RestoredGroup {
+    pub group_id: String,
+    /// Components started again to match their previous run state.
+    pub started: Vec<String>,
+    /// Components stopped to match their previous run state.
+    pub stopped: Vec<String>,
+}
+
+/// A process group that could not be restored.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct RollbackFailure {
+    pub group_id: String,
+    pub reason: String,
+}
+
+/// What `Deployment::rollback` did.
+#[derive(Deserialize, Serialize, Debug, Clone, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct RollbackReport {
+    pub restored: Vec<RestoredGroup>,
+    pub failed: Vec<RollbackFailure>,
+}
+
+impl RollbackReport {
+    /// `true` when every snapshotted group was restored.
+    pub fn is_complete(&self) -> bool {
+        self.failed.is_empty()
+    }
+}
+
+/// Represents the ways a guarded deployment can fail.
+#[derive(Debug, Error)]
+pub enum DeployError {
+    /// The affected groups could not be snapshotted; nothing was changed.
+    #[error("DeployError::SnapshotError - {0}")]
+    SnapshotError(anyhow::Error),
+
+    /// A hook or the planner refused to go on before anything was changed.
+    #[error("DeployError::Aborted - {0}")]
+    Aborted(anyhow::Error),
+
+    /// The deployment failed and the snapshotted groups were rolled back.
+    #[error("DeployError::RolledBack - {source}")]
+    RolledBack {
+        #[source]
+        source: anyhow::Error,
+        report: RollbackReport,
+    },
+}
+
+/// A deployment guarded by snapshots of the process groups it affects.
+///
+/// Use `deploy` to run a `Plan` with hooks and events, `execute` to guard an
+/// arbitrary closure, or `snapshot_group` and `rollback` directly for finer
+/// control.
+pub struct Deployment {
+    process_group: ProcessGroup,
+    poll: PollOptions,
+    snapshots: Vec<GroupSnapshot>,
+    pub(super) hooks: Vec<Arc<dyn DeployHook>>,
+    pub(super) subscribers: Vec<UnboundedSender<DeployEvent>>,
+}
+
+impl fmt::Debug for Deployment {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Deployment")
+            .field("process_group", &self.process_group)
+            .field("poll", &self.poll)
+            .field("snapshots", &self.snapshots)
+            .field("hooks", &self.hooks.len())
+            .field("subscribers", &self.subscribers.len())
+            .finish()
+    }
+}
+
+impl Deployment {
+    /// Creates a new `Deployment` with no snapshots.
+    ///
+    /// # Arguments
+    ///
+    /// * `client` - The shared `HttpClient` to be used for requests.
+    /// * `config` - The application configuration (containing `api_base_url`).
+    pub fn new(client: Arc<HttpClient>, config: Arc<Config>) -> Self {
+        Self {
+            process_group: ProcessGroup::new(client, config),
+            poll: PollOptions::default(),
+            snapshots: Vec::new(),
+            hooks: Vec::new(),
+            subscribers: Vec::new(),
+        }
+    }
+
+    /// Sets how long to wait for each replace-request during a rollback.
+    pub fn with_poll_options(mut self, poll: PollOptions) -> Self {
+        self.poll = poll;
+        self
+    }
+
+    /// The snapshots taken so far, in the order they were taken.
+    pub fn snapshots(&self) -> &[GroupSnapshot] {
+        &self.snapshots
+    }
+
+    /// Downloads the definition and run states of a process group so it can
+    /// be restored later.
+    ///
+    /// # Errors
+    /// Returns an error if the group cannot be downloaded or read.
+    pub async fn snapshot_group(&mut self, id: &str) -> anyhow::Result<()> {
+        let flow = self.process_group.download_process_group(id).await?;
+        let components = self.process_group.collect_components(id).await?;
+        let running = components
+            .schedulable
+            .values()
+            .filter(|schedulable| schedulable.state == RunState::Running);
+
+        let snapshot = GroupSnapshot {
+            group_id: id.to_string(),
+            flow,
+            running: running
+                .clone()
+                .map(|schedulable| schedulable.component.id.clone())
+                .collect(),
+            running_versioned: running
+                .clone()
+                .filter_map(|schedulable| schedulable.versioned_id.clone())
+                .collect(),
+            running_unversioned: running
+                .filter(|schedulable| schedulable.versioned_id.is_none())
+                .filter_map(component_key)
+                .collect(),
+        };
+        info!(
+            "Snapshotted process group {} ({} running components)",
+            id,
+            snapshot.running.len()
+        );
+        self.snapshots.push(snapshot);
+        Ok(())
+    }
+
+    /// Restores every snapshotted group, most recent first.
+    ///
+    /// A group that fails to restore does not stop the others from being
+    /// restored; it is reported in `RollbackReport::failed`.
+    pub async fn rollback(&self) -> RollbackReport {
+        let mut report = RollbackReport::default();
+        for snapshot in self.snapshots.iter().rev() {
+            warn!("Rolling back process group {}", snapshot.group_id);
+            match self.restore(snapshot).await {
+                Ok(restored) => report.restored.push(restored),
+                Err(err) => {
+                    warn!(
+                        "Rollback of process group {} failed: {}",
+                        snapshot.group_id, err
+                    );
+                    report.failed.push(RollbackFailure {
+                        group_id: snapshot.group_id.clone(),
+                        reason: err.to_string(),
+                    });
+                },
+            }
+        }
+        report
+    }
+
+    /// Snapshots `group_ids`, runs `deploy`, and rolls back if it fails.
+    ///
+    /// # Errors
+    ///
+    /// Returns `DeployError::SnapshotError` if a group cannot be snapshotted
+    /// (before `deploy` runs), or `DeployError::RolledBack` with the
+    /// `RollbackReport` if `deploy` fails.
+    pub async fn execute<T, F, Fut>(
+        &mut self,
+        group_ids: &[&str],
+        deploy: F,
+    ) -> Result<T, DeployError>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = anyhow::Result<T>>,
+    {
+        for id in group_ids {
+            self.snapshot_group(id)
+                .await
+                .map_err(DeployError::SnapshotError)?;
+        }
+        match deploy().await {
+            Ok(value) => Ok(value),
+            Err(source) => {
+                warn!("Deployment failed, rolling back: {}", source);
+                let report = self.rollback().await;
+                Err(DeployError::RolledBack { source, report })
+            },
+        }
+    }
+
+    async fn restore(&self, snapshot: &GroupSnapshot) -> anyhow::Result<RestoredGroup> {
+        self.process_group
+            .replace_process_group(&snapshot.group_id, &snapshot.flow, self.poll)
+            .await?;
+
+        let current = self
+            .process_group
+            .collect_components(&snapshot.group_id)
+            .await?;
+        let (start, stop) = run_state_changes(snapshot, &current);
+        if !start.is_empty() {
+            self.schedule(
+                &snapshot.group_id,
+                &start,
+                ScheduleComponentsEntityState::Running,
+            )
+            .await?;
+        }
+        if !stop.is_empty() {
+            self.schedule(
+                &snapshot.group_id,
+                &stop,
+                ScheduleComponentsEntityState::Stopped,
+            )
+            .await?;
+        }
+
+        info!("Process group {} rolled back", snapshot.group_id);
+        Ok(RestoredGroup {
+            group_id: snapshot.group_id.clone(),
+            started: start.into_keys().collect(),
+            stopped: stop.into_keys().collect(),
+        })
+    }
+
+    async fn schedule(
+        &self,
+        group_id: &str,
+        components: &HashMap<String, RevisionDto>,
+        state: ScheduleComponentsEntityState,
+    ) -> anyhow::Result<()> {
+        self.process_group
+            .put_schedule_components(
+                group_id,
+                &ScheduleComponentsEntity {
+                    components: components.clone(),
+                    disconnected_node_acknowledged: None,
+                    id: Some(group_id.to_string()),
+                    state: Some(state),
+                },
+            )
+            .await?;
+        Ok(())
+    }
+}
+
+/// Components to start and to stop so that `current` matches the run states
+/// recorded in `snapshot`. Disabled components are never touched.
+///
+/// A component is matched by id, then by `versionedComponentId`, then (for
+/// components that had none) by kind, group path and name.
+fn run_state_changes(
+    snapshot: &GroupSnapshot,
+    current: &GroupComponents,
+) -> (HashMap<String, RevisionDto>, HashMap<String, RevisionDto>) {
+    let mut start = HashMap::new();
+    let mut stop = HashMap::new();
+    for schedulable in current.schedulable.values() {
+        let was_running = snapshot.running.contains(&schedulable.component.id)
+            || schedulable
+                .versioned_id
+                .as_ref()
+                .is_some_and(|id| snapshot.running_versioned.contains(id))
+            || component_key(schedulable)
+                .is_some_and(|key| snapshot.running_unversioned.contains(&key));
+        let revision = schedulable.revision.clone().unwrap_or_default();
+        match (was_running, schedulable.state) {
+            (true, RunState::Stopped) => {
+                start.insert(schedulable.component.id.clone(), revision);
+            },
+            (false, RunState::Running) => {
+                stop.insert(schedulable.component.id.clone(), revision);
+            },
+            _ => {},
+        }
+    }
+    (start, stop)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::proxy::v260::process_group::ComponentRef;
+
+    fn schedulable(id: &str, versioned_id: Option<&str>, state: RunState) -> Schedulable {
+        Schedulable {
+            component: ComponentRef {
+                id: id.to_string(),
+                name: Some(id.to_string()),
+                kind: ScheduledComponentKind::Processor,
+            },
+            versioned_id: versioned_id.map(str::to_string),
+            group_path: String::new(),
+            revision: None,
+            state,
+        }
+    }
+
+    #[test]
+    fn test_run_state_changes() {
+        // --- 1. Setup: "a", "recreated" and the unversioned "ingest/fetch"
+        // ran before; "b" did not ---
+        let snapshot = GroupSnapshot {
+            group_id: "group".to_string(),
+            flow: RegisteredFlowSnapshot::default(),
+            running: ["a".to_string()].into(),
+            running_versioned: ["v-recreated".to_string()].into(),
+            running_unversioned: [(
+                ScheduledComponentKind::Processor,
+                "ingest".to_string(),
+                "fetch".to_string(),
+            )]
+            .into(),
+        };
+        let mut fetch = schedulable("new-fetch", Some("v-generated"), RunState::Stopped);
+        fetch.component.name = Some("fetch".to_string());
+        fetch.group_path = "ingest".to_string();
+        let mut other_fetch = fetch.clone();
+        other_fetch.component.id = "other-fetch".to_string();
+        other_fetch.group_path = "egress".to_string();
+        let mut current = GroupComponents::default();
+        for component in [
+            schedulable("a", None, RunState::Stopped),
+            schedulable("b", None, RunState::Running),
+            schedulable("new-id", Some("v-recreated"), RunState::Stopped),
+            schedulable("disabled", Some("v-recreated"), RunState::Disabled),
+            fetch,
+            other_fetch,
+        ] {
+            current
+                .schedulable
+                .insert(component.component.id.clone(), component);
+        }
+
+        // --- 2. Compute ---
+        let (start, stop) = run_state_changes(&snapshot, &current);
+
+        // --- 3. Assert ---
+        let mut started: Vec<_> = start.into_keys().collect();
+        started.sort();
+        assert_eq!(
+            started,
+            vec![
+                "a".to_string(),
+                "new-fetch".to_string(),
+                "new-id".to_string()
+            ]
+        );
+        assert_eq!(stop.into_keys().collect::<Vec<_>>(), vec!["b".to_string()]);
+    }
+}
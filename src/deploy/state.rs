@@ -0,0 +1,711 @@
+//! # Deployment State Module
+//!
+//! Keeps a lasting mapping from the logical ids we declare components with
+//! (e.g. `"ingest/http-listener"`) to the UUIDs NiFi assigned to them.
+//!
+//! With this mapping, redeploying the same declaration updates the components
+//! already living in NiFi instead of creating new copies of them. Each
+//! environment (`dev`, `pre`, `prod`, ...) has its own, independent mapping.
+//!
+//! Storage is pluggable through the `StateStore` trait. Two stores are provided:
+//!
+//! * `JsonFileStateStore` - A single JSON file holding every environment.
+//! * `MemoryStateStore` - A non-persistent store, useful for tests and dry runs.
+//!
+//! `import_process_group` deploys a flow definition through the state: the
+//! group recorded for its logical id is updated in place, and only created
+//! when there is none. `EnvironmentState::record_deployment` brings the
+//! mapping up to date after any deployment of a group.
+//!
+//! Components inside a deployed group are keyed by the group's logical id and
+//! their versioned identifier (see `component_id`), so two groups deployed
+//! from the same flow definition never share entries.
+
+use crate::common::client::HttpClientError;
+use crate::common::polling::PollOptions;
+use crate::proxy::v260::api::{ProcessGroupEntity, RegisteredFlowSnapshot, VersionedProcessGroup};
+use crate::proxy::v260::process_group::ProcessGroup;
+use anyhow::Context;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use reqwest::StatusCode;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use tokio::sync::{Mutex, RwLock};
+
+/// The kind of NiFi component a logical id refers to.
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "camelCase")]
+pub enum ComponentKind {
+    ProcessGroup,
+    Processor,
+    Connection,
+    InputPort,
+    OutputPort,
+    Funnel,
+    Label,
+    ControllerService,
+    RemoteProcessGroup,
+    ParameterContext,
+    ParameterProvider,
+}
+
+/// A single recorded mapping: what NiFi calls the component we declared.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct StateEntry {
+    pub kind: ComponentKind,
+    /// The UUID NiFi assigned to the component.
+    pub uuid: String,
+    /// When this entry was last recorded.
+    pub updated_at: DateTime<Utc>,
+}
+
+/// The whole mapping for one environment, keyed by logical id.
+#[derive(Deserialize, Serialize, Debug, Clone, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct EnvironmentState {
+    pub environment: String,
+    pub components: BTreeMap<String, StateEntry>,
+}
+
+/// The outcome of checking an `EnvironmentState` against a declaration and a
+/// live NiFi instance.
+#[derive(Deserialize, Serialize, Debug, Clone, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct StateReport {
+    /// Logical ids recorded in the state whose UUID no longer exists in NiFi
+    /// (someone deleted the component by hand). They will be recreated.
+    pub missing: Vec<String>,
+    /// Logical ids recorded in the state that are no longer declared. The
+    /// components they point to are left behind in NiFi.
+    pub orphaned: Vec<String>,
+    /// Declared logical ids with no recorded UUID yet. They will be created.
+    pub untracked: Vec<String>,
+}
+
+impl StateReport {
+    /// `true` when the state, the declaration and NiFi all agree.
+    pub fn is_clean(&self) -> bool {
+        self.missing.is_empty() && self.orphaned.is_empty() && self.untracked.is_empty()
+    }
+}
+
+impl EnvironmentState {
+    /// Creates an empty state for `environment`.
+    pub fn new(environment: &str) -> Self {
+        Self {
+            environment: environment.to_string(),
+            components: BTreeMap::new(),
+        }
+    }
+
+    /// Returns the NiFi UUID recorded for `logical_id`, if any.
+    ///
+    /// Callers creating components should use this first: a `Some` means the
+    /// component must be updated in place rather than created again. A
+    /// component inside a deployed group is looked up by its `component_id`.
+    pub fn resolve(&self, logical_id: &str) -> Option<&str> {
+        self.components
+            .get(logical_id)
+            .map(|entry| entry.uuid.as_str())
+    }
+
+    /// Records (or overwrites) the UUID NiFi assigned to `logical_id`.
+    pub fn record(&mut self, logical_id: &str, kind: ComponentKind, uuid: &str) {
+        self.components.insert(
+            logical_id.to_string(),
+            StateEntry {
+                kind,
+                uuid: uuid.to_string(),
+                updated_at: Utc::now(),
+            },
+        );
+    }
+
+    /// Removes the mapping for `logical_id`, returning it if it existed. A
+    /// component inside a deployed group is forgotten by its `component_id`.
+    pub fn forget(&mut self, logical_id: &str) -> Option<StateEntry> {
+        self.components.remove(logical_id)
+    }
+
+    /// Brings the mapping of a deployed process group up to date.
+    ///
+    /// Records the group under `logical_id` and every component inside it
+    /// under the `component_id` of its versioned `identifier`, which stays the
+    /// same across exports of the flow. Components of `previous` (the group as
+    /// it was before the deployment) that `live` no longer has are forgotten.
+    ///
+    /// Both groups should come from `/process-groups/{id}/download`, where
+    /// `instanceIdentifier` holds the real UUID of each component.
+    pub fn record_deployment(
+        &mut self,
+        logical_id: &str,
+        previous: Option<&VersionedProcessGroup>,
+        live: &VersionedProcessGroup,
+    ) {
+        let mut deployed = Vec::new();
+        for child in live.process_groups.iter().flatten() {
+            collect_components(child, ComponentKind::ProcessGroup, &mut deployed);
+        }
+        collect_children(live, &mut deployed);
+
+        if let Some(previous) = previous {
+            let mut removed = Vec::new();
+            for child in previous.process_groups.iter().flatten() {
+                collect_components(child, ComponentKind::ProcessGroup, &mut removed);
+            }
+            collect_children(previous, &mut removed);
+            for (id, _, _) in removed {
+                if !deployed
+                    .iter()
+                    .any(|(deployed_id, _, _)| *deployed_id == id)
+                {
+                    self.forget(&component_id(logical_id, &id));
+                }
+            }
+        }
+
+        if let Some(uuid) = &live.instance_identifier {
+            self.record(logical_id, ComponentKind::ProcessGroup, uuid);
+        }
+        for (id, kind, uuid) in deployed {
+            self.record(&component_id(logical_id, &id), kind, &uuid);
+        }
+    }
+
+    /// Compares the recorded mapping with the declared logical ids and the
+    /// UUIDs that currently exist in NiFi.
+    ///
+    /// # Arguments
+    ///
+    /// * `declared` - Every logical id in the current declaration, with the
+    ///   components of deployed groups keyed as `declared_ids` does.
+    /// * `live_ids` - Every component UUID currently present in NiFi, e.g.
+    ///   collected with `live_instance_ids`.
+    pub fn check<'a, I>(&self, declared: I, live_ids: &HashSet<String>) -> StateReport
+    where
+        I: IntoIterator<Item = &'a str>,
+    {
+        let declared: HashSet<&str> = declared.into_iter().collect();
+
+        let missing = self
+            .components
+            .iter()
+            .filter(|(_, entry)| !live_ids.contains(&entry.uuid))
+            .map(|(logical_id, _)| logical_id.clone())
+            .collect();
+        let orphaned = self
+            .components
+            .keys()
+            .filter(|logical_id| !declared.contains(logical_id.as_str()))
+            .cloned()
+            .collect();
+        let mut untracked: Vec<String> = declared
+            .into_iter()
+            .filter(|logical_id| !self.components.contains_key(*logical_id))
+            .map(str::to_string)
+            .collect();
+        untracked.sort();
+
+        StateReport {
+            missing,
+            orphaned,
+            untracked,
+        }
+    }
+}
+
+/// Returns the key under which the component with versioned `identifier`,
+/// inside the group deployed as `group_logical_id`, is recorded.
+pub fn component_id(group_logical_id: &str, identifier: &str) -> String {
+    format!("{}/{}", group_logical_id, identifier)
+}
+
+/// Returns the logical ids a deployment of `group` as `logical_id` records:
+/// `logical_id` itself and the `component_id` of everything inside it.
+///
+/// The result can be handed to `EnvironmentState::check` as the declaration.
+pub fn declared_ids(logical_id: &str, group: &VersionedProcessGroup) -> Vec<String> {
+    let mut components = Vec::new();
+    for child in group.process_groups.iter().flatten() {
+        collect_components(child, ComponentKind::ProcessGroup, &mut components);
+    }
+    collect_children(group, &mut components);
+    std::iter::once(logical_id.to_string())
+        .chain(
+            components
+                .into_iter()
+                .map(|(id, _, _)| component_id(logical_id, &id)),
+        )
+        .collect()
+}
+
+/// Collects the instance UUIDs of every component inside `group`, including
+/// the group itself and all its descendants.
+///
+/// The group should come from a live NiFi instance (e.g.
+/// `/process-groups/{id}/download`), where `instanceIdentifier` holds the
+/// real UUID of each component.
+pub fn live_instance_ids(group: &VersionedProcessGroup) -> HashSet<String> {
+    let mut ids = HashSet::new();
+    collect_instance_ids(group, &mut ids);
+    ids
+}
+
+fn collect_instance_ids(group: &VersionedProcessGroup, ids: &mut HashSet<String>) {
+    ids.extend(group.instance_identifier.clone());
+    for processor in group.processors.iter().flatten() {
+        ids.extend(processor.instance_identifier.clone());
+    }
+    for connection in group.connections.iter().flatten() {
+        ids.extend(connection.instance_identifier.clone());
+    }
+    for port in group
+        .input_ports
+        .iter()
+        .flatten()
+        .chain(group.output_ports.iter().flatten())
+    {
+        ids.extend(port.instance_identifier.clone());
+    }
+    for funnel in group.funnels.iter().flatten() {
+        ids.extend(funnel.instance_identifier.clone());
+    }
+    for label in group.labels.iter().flatten() {
+        ids.extend(label.instance_identifier.clone());
+    }
+    for service in group.controller_services.iter().flatten() {
+        ids.extend(service.instance_identifier.clone());
+    }
+    for remote in group.remote_process_groups.iter().flatten() {
+        ids.extend(remote.instance_identifier.clone());
+    }
+    for child in group.process_groups.iter().flatten() {
+        collect_instance_ids(child, ids);
+    }
+}
+
+/// A component of a deployed group: its versioned identifier, kind and UUID.
+type DeployedComponent = (String, ComponentKind, String);
+
+/// Collects `group` itself and, recursively, everything inside it.
+fn collect_components(
+    group: &VersionedProcessGroup,
+    kind: ComponentKind,
+    components: &mut Vec<DeployedComponent>,
+) {
+    if let (Some(id), Some(uuid)) = (&group.identifier, &group.instance_identifier) {
+        components.push((id.clone(), kind, uuid.clone()));
+    }
+    collect_children(group, components);
+    for child in group.process_groups.iter().flatten() {
+        collect_components(child, ComponentKind::ProcessGroup, components);
+    }
+}
+
+/// Collects the components directly inside `group`, child groups excluded.
+fn collect_children(group: &VersionedProcessGroup, components: &mut Vec<DeployedComponent>) {
+    let mut push = |kind, id: &Option<String>, uuid: &Option<String>| {
+        if let (Some(id), Some(uuid)) = (id, uuid) {
+            components.push((id.clone(), kind, uuid.clone()));
+        }
+    };
+    for processor in group.processors.iter().flatten() {
+        push(
+            ComponentKind::Processor,
+            &processor.identifier,
+            &processor.instance_identifier,
+        );
+    }
+    for connection in group.connections.iter().flatten() {
+        push(
+            ComponentKind::Connection,
+            &connection.identifier,
+            &connection.instance_identifier,
+        );
+    }
+    for port in group.input_ports.iter().flatten() {
+        push(
+            ComponentKind::InputPort,
+            &port.identifier,
+            &port.instance_identifier,
+        );
+    }
+    for port in group.output_ports.iter().flatten() {
+        push(
+            ComponentKind::OutputPort,
+            &port.identifier,
+            &port.instance_identifier,
+        );
+    }
+    for funnel in group.funnels.iter().flatten() {
+        push(
+            ComponentKind::Funnel,
+            &funnel.identifier,
+            &funnel.instance_identifier,
+        );
+    }
+    for label in group.labels.iter().flatten() {
+        push(
+            ComponentKind::Label,
+            &label.identifier,
+            &label.instance_identifier,
+        );
+    }
+    for service in group.controller_services.iter().flatten() {
+        push(
+            ComponentKind::ControllerService,
+            &service.identifier,
+            &service.instance_identifier,
+        );
+    }
+    for remote in group.remote_process_groups.iter().flatten() {
+        push(
+            ComponentKind::RemoteProcessGroup,
+            &remote.identifier,
+            &remote.instance_identifier,
+        );
+    }
+}
+
+/// Deploys `snapshot` as the process group `logical_id` of `state`.
+///
+/// When the state resolves `logical_id` to a group that still exists in
+/// NiFi, that group is updated in place through a replace-request. Otherwise
+/// a new group is created under `parent_id` (named `name`, or after the
+/// snapshot). Either way the state is then updated with `record_deployment`;
+/// persisting it through a `StateStore` is up to the caller.
+///
+/// # Errors
+/// Returns an error if the group cannot be read, created or replaced. The
+/// state is left unchanged in that case.
+pub async fn import_process_group(
+    service: &ProcessGroup,
+    state: &mut EnvironmentState,
+    logical_id: &str,
+    parent_id: &str,
+    name: Option<&str>,
+    snapshot: &RegisteredFlowSnapshot,
+    poll: PollOptions,
+) -> anyhow::Result<ProcessGroupEntity> {
+    let existing = match state.resolve(logical_id) {
+        Some(uuid) => match service.download_process_group(uuid).await {
+            Ok(previous) => Some((uuid.to_string(), previous)),
+            Err(err) => match err.downcast::<HttpClientError>() {
+                // Deleted by hand: create it again.
+                Ok(HttpClientError::HttpError { status, .. })
+                    if status == StatusCode::NOT_FOUND =>
+                {
+                    None
+                },
+                Ok(err) => return Err(err.into()),
+                Err(err) => return Err(err),
+            },
+        },
+        None => None,
+    };
+
+    let (group, previous) = match existing {
+        Some((uuid, previous)) => {
+            service.replace_process_group(&uuid, snapshot, poll).await?;
+            (
+                service.get_process_group(&uuid).await?,
+                previous.flow_contents,
+            )
+        },
+        None => (
+            service
+                .import_process_group(parent_id, name, snapshot, poll)
+                .await?,
+            None,
+        ),
+    };
+    let Some(id) = group.id.as_deref() else {
+        anyhow::bail!("Imported process group has no id");
+    };
+    let live = service.download_process_group(id).await?;
+    state.record_deployment(
+        logical_id,
+        previous.as_ref(),
+        &live.flow_contents.unwrap_or_default(),
+    );
+    Ok(group)
+}
+
+/// A pluggable backend for persisting `EnvironmentState`s.
+#[async_trait]
+pub trait StateStore: Send + Sync {
+    /// Loads the state of `environment`. An environment that was never saved
+    /// yields an empty state, not an error.
+    async fn load(&self, environment: &str) -> anyhow::Result<EnvironmentState>;
+
+    /// Persists `state`, replacing whatever was stored for its environment.
+    async fn save(&self, state: &EnvironmentState) -> anyhow::Result<()>;
+}
+
+/// On-disk layout of `JsonFileStateStore`.
+#[derive(Deserialize, Serialize, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+struct StateFile {
+    environments: BTreeMap<String, EnvironmentState>,
+}
+
+/// A `StateStore` backed by a single, human-readable JSON file.
+///
+/// The file is meant to be committed next to the flow declarations, so every
+/// environment lives in the same file. Writes go to a temporary sibling file
+/// first and are then renamed over the original, so a crash never leaves a
+/// half-written state behind.
+#[derive(Debug)]
+pub struct JsonFileStateStore {
+    path: PathBuf,
+    /// Serializes read-modify-write cycles within this process.
+    lock: Mutex<()>,
+}
+
+impl JsonFileStateStore {
+    /// Creates a store over `path`. The file is created on first `save`.
+    pub fn new(path: impl AsRef<Path>) -> Self {
+        Self {
+            path: path.as_ref().to_path_buf(),
+            lock: Mutex::new(()),
+        }
+    }
+
+    async fn read_file(&self) -> anyhow::Result<StateFile> {
+        match tokio::fs::read_to_string(&self.path).await {
+            Ok(content) => serde_json::from_str(&content)
+                .with_context(|| format!("Invalid state file {}", self.path.display())),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(StateFile::default()),
+            Err(err) => {
+                Err(err).with_context(|| format!("Cannot read state file {}", self.path.display()))
+            },
+        }
+    }
+}
+
+#[async_trait]
+impl StateStore for JsonFileStateStore {
+    async fn load(&self, environment: &str) -> anyhow::Result<EnvironmentState> {
+        let _guard = self.lock.lock().await;
+        let mut file = self.read_file().await?;
+        Ok(file
+            .environments
+            .remove(environment)
+            .unwrap_or_else(|| EnvironmentState::new(environment)))
+    }
+
+    async fn save(&self, state: &EnvironmentState) -> anyhow::Result<()> {
+        let _guard = self.lock.lock().await;
+        let mut file = self.read_file().await?;
+        file.environments
+            .insert(state.environment.clone(), state.clone());
+
+        let tmp_path = self.path.with_extension("json.tmp");
+        tokio::fs::write(&tmp_path, serde_json::to_string_pretty(&file)?)
+            .await
+            .with_context(|| format!("Cannot write state file {}", tmp_path.display()))?;
+        tokio::fs::rename(&tmp_path, &self.path)
+            .await
+            .with_context(|| format!("Cannot replace state file {}", self.path.display()))?;
+        Ok(())
+    }
+}
+
+/// A `StateStore` that only lives in memory.
+#[derive(Debug, Default)]
+pub struct MemoryStateStore {
+    states: RwLock<HashMap<String, EnvironmentState>>,
+}
+
+impl MemoryStateStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl StateStore for MemoryStateStore {
+    async fn load(&self, environment: &str) -> anyhow::Result<EnvironmentState> {
+        let guard = self.states.read().await;
+        Ok(guard
+            .get(environment)
+            .cloned()
+            .unwrap_or_else(|| EnvironmentState::new(environment)))
+    }
+
+    async fn save(&self, state: &EnvironmentState) -> anyhow::Result<()> {
+        let mut guard = self.states.write().await;
+        guard.insert(state.environment.clone(), state.clone());
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::proxy::v260::api::VersionedProcessor;
+    use tracing_test::traced_test;
+
+    #[tokio::test]
+    #[traced_test]
+    async fn test_json_file_state_store_roundtrip() {
+        // --- 1. Setup ---
+        let path =
+            std::env::temp_dir().join(format!("nifi-rs-state-{}.json", uuid::Uuid::new_v4()));
+        let store = JsonFileStateStore::new(&path);
+
+        // --- 2. Unknown environments are empty ---
+        let dev = store.load("dev").await;
+        assert!(dev.is_ok(), "load call error: {:?}", dev);
+        let mut dev = dev.unwrap();
+        assert!(dev.components.is_empty());
+
+        // --- 3. Save two environments into the same file ---
+        dev.record("ingest", ComponentKind::ProcessGroup, "uuid-dev");
+        let mut prod = EnvironmentState::new("prod");
+        prod.record("ingest", ComponentKind::ProcessGroup, "uuid-prod");
+        assert!(store.save(&dev).await.is_ok());
+        assert!(store.save(&prod).await.is_ok());
+
+        // --- 4. Assert over reloaded state ---
+        let reloaded = JsonFileStateStore::new(&path);
+        assert_eq!(
+            reloaded.load("dev").await.unwrap().resolve("ingest"),
+            Some("uuid-dev")
+        );
+        assert_eq!(
+            reloaded.load("prod").await.unwrap().resolve("ingest"),
+            Some("uuid-prod")
+        );
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    async fn test_memory_state_store() {
+        let store = MemoryStateStore::new();
+        let mut state = store.load("dev").await.unwrap();
+        state.record("listener", ComponentKind::Processor, "uuid-1");
+        store.save(&state).await.unwrap();
+
+        let mut state = store.load("dev").await.unwrap();
+        assert_eq!(state.resolve("listener"), Some("uuid-1"));
+        assert!(state.forget("listener").is_some());
+        assert_eq!(state.resolve("listener"), None);
+    }
+
+    #[test]
+    fn test_check_reports_missing_orphaned_and_untracked() {
+        // --- 1. Setup ---
+        let mut state = EnvironmentState::new("dev");
+        state.record("group", ComponentKind::ProcessGroup, "uuid-group");
+        state.record("kept", ComponentKind::Processor, "uuid-kept");
+        state.record("deleted-by-hand", ComponentKind::Processor, "uuid-gone");
+        state.record("no-longer-declared", ComponentKind::Processor, "uuid-old");
+
+        let live = VersionedProcessGroup {
+            instance_identifier: Some("uuid-group".to_string()),
+            processors: Some(vec![
+                VersionedProcessor {
+                    instance_identifier: Some("uuid-kept".to_string()),
+                    ..Default::default()
+                },
+                VersionedProcessor {
+                    instance_identifier: Some("uuid-old".to_string()),
+                    ..Default::default()
+                },
+            ]),
+            ..Default::default()
+        };
+
+        // --- 2. Check ---
+        let report = state.check(
+            ["group", "kept", "deleted-by-hand", "new-one"],
+            &live_instance_ids(&live),
+        );
+
+        // --- 3. Assert over report ---
+        assert!(!report.is_clean());
+        assert_eq!(report.missing, vec!["deleted-by-hand".to_string()]);
+        assert_eq!(report.orphaned, vec!["no-longer-declared".to_string()]);
+        assert_eq!(report.untracked, vec!["new-one".to_string()]);
+    }
+
+    #[test]
+    fn test_record_deployment() {
+        let group = |processors: serde_json::Value| -> VersionedProcessGroup {
+            serde_json::from_value(serde_json::json!({
+                "identifier": "flow", "instanceIdentifier": "uuid-group",
+                "processors": processors,
+                "processGroups": [{"identifier": "child", "instanceIdentifier": "uuid-child"}],
+            }))
+            .unwrap()
+        };
+        let previous = group(serde_json::json!([
+            {"identifier": "fetch", "instanceIdentifier": "uuid-fetch"},
+            {"identifier": "legacy", "instanceIdentifier": "uuid-legacy"},
+        ]));
+        let live = group(serde_json::json!([
+            {"identifier": "fetch", "instanceIdentifier": "uuid-fetch"},
+            {"identifier": "publish", "instanceIdentifier": "uuid-publish"},
+        ]));
+        let mut state = EnvironmentState::new("dev");
+        state.record("ingest/legacy", ComponentKind::Processor, "uuid-legacy");
+
+        state.record_deployment("ingest", Some(&previous), &live);
+
+        let recorded: Vec<(&str, &str)> = state
+            .components
+            .iter()
+            .map(|(id, entry)| (id.as_str(), entry.uuid.as_str()))
+            .collect();
+        assert_eq!(
+            recorded,
+            vec![
+                ("ingest", "uuid-group"),
+                ("ingest/child", "uuid-child"),
+                ("ingest/fetch", "uuid-fetch"),
+                ("ingest/publish", "uuid-publish"),
+            ]
+        );
+        assert_eq!(
+            state.components["ingest/child"].kind,
+            ComponentKind::ProcessGroup
+        );
+        assert_eq!(
+            declared_ids("ingest", &live),
+            vec!["ingest", "ingest/child", "ingest/fetch", "ingest/publish"]
+        );
+    }
+
+    #[test]
+    fn test_record_deployment_keeps_groups_of_the_same_flow_apart() {
+        let group = |suffix: &str| -> VersionedProcessGroup {
+            serde_json::from_value(serde_json::json!({
+                "identifier": "flow", "instanceIdentifier": format!("uuid-group-{suffix}"),
+                "processors": [
+                    {"identifier": "fetch", "instanceIdentifier": format!("uuid-fetch-{suffix}")},
+                ],
+            }))
+            .unwrap()
+        };
+        let (first, second) = (group("a"), group("b"));
+        let mut state = EnvironmentState::new("dev");
+
+        state.record_deployment("ingest-a", None, &first);
+        state.record_deployment("ingest-b", None, &second);
+        // Redeploying "ingest-b" without its processor must not touch "ingest-a".
+        let emptied = VersionedProcessGroup {
+            processors: Some(Vec::new()),
+            ..second.clone()
+        };
+        state.record_deployment("ingest-b", Some(&second), &emptied);
+
+        assert_eq!(state.resolve("ingest-a/fetch"), Some("uuid-fetch-a"));
+        assert_eq!(state.resolve("ingest-b/fetch"), None);
+        assert_eq!(state.resolve("ingest-b"), Some("uuid-group-b"));
+    }
+}
@@ -5,4 +5,5 @@
 //!
 
 pub mod common;
+pub mod deploy;
 pub mod proxy;
@@ -73,15 +73,14 @@ impl Access {
     /// Returns `HttpClientError` if the `DELETE` request fails.
     pub async fn logout(&self) -> anyhow::Result<()> {
         // Call the logout endpoint. We expect an empty '()' response.
-        let response = self
-            .client
+        self.client
             .delete::<()>(&format!("{}/access/logout", self.config.api_base_url))
             .await?;
 
         // Clear the token from the shared client
         self.client.clear_auth_token().await?;
 
-        Ok(response)
+        Ok(())
     }
 }
 
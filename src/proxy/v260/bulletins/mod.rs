@@ -0,0 +1,447 @@
+//! # Bulletins Module
+//!
+//! Provides high-level bindings for bulletins:
+//!
+//! * `/flow/bulletin-board` - the bulletins of the flow, read incrementally
+//!   with the `after` cursor.
+//! * `/flow/controller/bulletins` - controller-level bulletins (controller
+//!   services, reporting tasks, registry clients...).
+//! * `/controller/bulletin` - posting a custom bulletin.
+//!
+//! `Bulletins::stream` polls both endpoints and yields every new bulletin
+//! once, filtered by a `BulletinFilter`.
+
+use crate::common::client::HttpClient;
+use crate::common::config::Config;
+use crate::proxy::v260::api::{BulletinBoardEntity, BulletinEntity, ControllerBulletinsEntity};
+use futures::StreamExt;
+use futures::stream::BoxStream;
+use reqwest::Url;
+use std::collections::{HashSet, VecDeque};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// How many bulletin keys are remembered for deduplication. NiFi only keeps
+/// the last few hundred bulletins, so older ones cannot come back.
+const SEEN_CAPACITY: usize = 10_000;
+
+/// The level of a bulletin, from `BulletinDto::level`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Severity {
+    Debug,
+    Info,
+    Warning,
+    Error,
+}
+
+impl Severity {
+    pub fn parse(level: &str) -> Option<Self> {
+        match level.to_ascii_uppercase().as_str() {
+            "DEBUG" => Some(Severity::Debug),
+            "INFO" => Some(Severity::Info),
+            "WARN" | "WARNING" => Some(Severity::Warning),
+            "ERROR" => Some(Severity::Error),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Severity::Debug => "DEBUG",
+            Severity::Info => "INFO",
+            Severity::Warning => "WARNING",
+            Severity::Error => "ERROR",
+        }
+    }
+}
+
+/// Which bulletins to keep.
+#[derive(Debug, Clone, Default)]
+pub struct BulletinFilter {
+    /// Only bulletins of this process group.
+    pub group_id: Option<String>,
+    /// Only bulletins of this component.
+    pub source_id: Option<String>,
+    /// Only bulletins of components with this exact name.
+    pub source_name: Option<String>,
+    /// Only bulletins at this level or above.
+    pub min_severity: Option<Severity>,
+}
+
+impl BulletinFilter {
+    pub fn matches(&self, entity: &BulletinEntity) -> bool {
+        let bulletin = entity.bulletin.as_ref();
+        let group_id = entity
+            .group_id
+            .as_deref()
+            .or_else(|| bulletin?.group_id.as_deref());
+        let source_id = entity
+            .source_id
+            .as_deref()
+            .or_else(|| bulletin?.source_id.as_deref());
+        let source_name = bulletin.and_then(|bulletin| bulletin.source_name.as_deref());
+        let severity = bulletin
+            .and_then(|bulletin| bulletin.level.as_deref())
+            .and_then(Severity::parse);
+
+        self.group_id
+            .as_deref()
+            .is_none_or(|id| group_id == Some(id))
+            && self
+                .source_id
+                .as_deref()
+                .is_none_or(|id| source_id == Some(id))
+            && self
+                .source_name
+                .as_deref()
+                .is_none_or(|name| source_name == Some(name))
+            && self
+                .min_severity
+                .is_none_or(|min| severity.is_some_and(|severity| severity >= min))
+    }
+}
+
+/// A query of the bulletin board.
+#[derive(Debug, Clone, Default)]
+pub struct BulletinBoardQuery {
+    /// Only bulletins with a greater id.
+    pub after: Option<i64>,
+    pub group_id: Option<String>,
+    pub source_id: Option<String>,
+    /// A regular expression matched against the source name.
+    pub source_name: Option<String>,
+    /// A regular expression matched against the message.
+    pub message: Option<String>,
+    pub limit: Option<u32>,
+}
+
+impl BulletinBoardQuery {
+    fn query(&self) -> Vec<(&'static str, String)> {
+        [
+            ("after", self.after.map(|after| after.to_string())),
+            ("groupId", self.group_id.clone()),
+            ("sourceId", self.source_id.clone()),
+            ("sourceName", self.source_name.clone()),
+            ("message", self.message.clone()),
+            ("limit", self.limit.map(|limit| limit.to_string())),
+        ]
+        .into_iter()
+        .filter_map(|(key, value)| Some((key, value?)))
+        .collect()
+    }
+}
+
+/// A service for reading and posting bulletins.
+///
+/// This service is instantiated with shared (`Arc`) instances of `HttpClient` and `Config`.
+#[derive(Debug)]
+pub struct Bulletins {
+    client: Arc<HttpClient>,
+    config: Arc<Config>,
+}
+
+impl Bulletins {
+    /// Creates a new instance of the `Bulletins` service.
+    ///
+    /// # Arguments
+    ///
+    /// * `client` - The shared `HttpClient` to be used for requests.
+    /// * `config` - The application configuration (containing `api_base_url`).
+    pub fn new(client: Arc<HttpClient>, config: Arc<Config>) -> Self {
+        Self { client, config }
+    }
+
+    /// Retrieves the bulletin board.
+    ///
+    /// Sends a `GET` request to `/flow/bulletin-board`.
+    ///
+    /// # Errors
+    /// Returns `HttpClientError` if the request fails.
+    pub async fn get_bulletin_board(
+        &self,
+        query: &BulletinBoardQuery,
+    ) -> anyhow::Result<BulletinBoardEntity> {
+        let url = Url::parse_with_params(
+            &format!("{}/flow/bulletin-board", self.config.api_base_url),
+            query.query(),
+        )?;
+        Ok(self
+            .client
+            .get_json::<BulletinBoardEntity>(url.as_str())
+            .await?)
+    }
+
+    /// Retrieves the controller-level bulletins.
+    ///
+    /// Sends a `GET` request to `/flow/controller/bulletins`.
+    ///
+    /// # Errors
+    /// Returns `HttpClientError` if the request fails.
+    pub async fn get_controller_bulletins(&self) -> anyhow::Result<ControllerBulletinsEntity> {
+        let url = format!("{}/flow/controller/bulletins", self.config.api_base_url);
+        Ok(self
+            .client
+            .get_json::<ControllerBulletinsEntity>(&url)
+            .await?)
+    }
+
+    /// Posts a custom bulletin.
+    ///
+    /// Sends a `POST` request to `/controller/bulletin`.
+    ///
+    /// # Errors
+    /// Returns `HttpClientError` if the request fails.
+    pub async fn post_bulletin(&self, payload: &BulletinEntity) -> anyhow::Result<BulletinEntity> {
+        let url = format!("{}/controller/bulletin", self.config.api_base_url);
+        Ok(self
+            .client
+            .post_json::<BulletinEntity, BulletinEntity>(&url, payload)
+            .await?)
+    }
+
+    /// Streams new bulletins, polling every `interval`.
+    ///
+    /// The bulletin board is read with the `after` cursor and merged with the
+    /// controller-level bulletins; each bulletin is yielded once, if it
+    /// matches `filter`. Bulletin ids are only unique per node, so on a
+    /// cluster the cursor is dropped and the whole board is read each time. Request errors are yielded as items and polling
+    /// continues afterwards. The stream never ends on its own.
+    pub fn stream(
+        &self,
+        filter: BulletinFilter,
+        interval: Duration,
+    ) -> BoxStream<'_, anyhow::Result<BulletinEntity>> {
+        let state = StreamState {
+            filter,
+            after: None,
+            clustered: false,
+            seen: Seen::default(),
+            pending: VecDeque::new(),
+            first: true,
+        };
+        futures::stream::unfold(state, move |mut state| async move {
+            loop {
+                if let Some(bulletin) = state.pending.pop_front() {
+                    return Some((Ok(bulletin), state));
+                }
+                if !state.first {
+                    tokio::time::sleep(interval).await;
+                }
+                state.first = false;
+                if let Err(err) = self.poll_once(&mut state).await {
+                    return Some((Err(err), state));
+                }
+            }
+        })
+        .boxed()
+    }
+
+    async fn poll_once(&self, state: &mut StreamState) -> anyhow::Result<()> {
+        let query = BulletinBoardQuery {
+            after: state.after,
+            group_id: state.filter.group_id.clone(),
+            source_id: state.filter.source_id.clone(),
+            ..Default::default()
+        };
+        let board = self
+            .get_bulletin_board(&query)
+            .await?
+            .bulletin_board
+            .unwrap_or_default();
+        let controller = self.get_controller_bulletins().await?;
+        state.advance(&board.bulletins);
+
+        let mut bulletins: Vec<BulletinEntity> = board
+            .bulletins
+            .into_iter()
+            .chain(controller.bulletins)
+            .chain(controller.controller_service_bulletins)
+            .chain(controller.reporting_task_bulletins)
+            .chain(controller.flow_registry_client_bulletins)
+            .chain(controller.parameter_provider_bulletins)
+            .chain(controller.flow_analysis_rule_bulletins)
+            .collect();
+        bulletins.sort_by_key(|bulletin| bulletin.id);
+        let accepted = state.accept(bulletins);
+        state.pending.extend(accepted);
+        Ok(())
+    }
+}
+
+/// The state carried between the polls of `Bulletins::stream`.
+struct StreamState {
+    filter: BulletinFilter,
+    after: Option<i64>,
+    /// Set once a bulletin came from a cluster node; `after` stays `None`.
+    clustered: bool,
+    seen: Seen,
+    pending: VecDeque<BulletinEntity>,
+    first: bool,
+}
+
+impl StreamState {
+    /// Moves the `after` cursor past the bulletins read from the board.
+    ///
+    /// Only board ids advance the cursor: it is a board query parameter. Ids
+    /// of different cluster nodes cannot be compared, so a cluster is read
+    /// without a cursor and relies on `seen` alone.
+    fn advance(&mut self, board: &[BulletinEntity]) {
+        if board.iter().any(|bulletin| bulletin.node_address.is_some()) {
+            self.clustered = true;
+            self.after = None;
+        }
+        if self.clustered {
+            return;
+        }
+        if let Some(max) = board.iter().filter_map(|bulletin| bulletin.id).max() {
+            self.after = Some(self.after.map_or(max, |after| after.max(max)));
+        }
+    }
+
+    /// Keeps the new, matching bulletins.
+    fn accept(&mut self, bulletins: Vec<BulletinEntity>) -> Vec<BulletinEntity> {
+        let mut accepted = Vec::new();
+        for bulletin in bulletins {
+            if self.seen.insert(key(&bulletin)) && self.filter.matches(&bulletin) {
+                accepted.push(bulletin);
+            }
+        }
+        accepted
+    }
+}
+
+/// A bounded set of the bulletins already yielded.
+#[derive(Default)]
+struct Seen {
+    keys: HashSet<BulletinKey>,
+    order: VecDeque<BulletinKey>,
+}
+
+impl Seen {
+    /// Returns `false` if the key was already seen.
+    fn insert(&mut self, key: BulletinKey) -> bool {
+        if !self.keys.insert(key.clone()) {
+            return false;
+        }
+        self.order.push_back(key);
+        if self.order.len() > SEEN_CAPACITY
+            && let Some(oldest) = self.order.pop_front()
+        {
+            self.keys.remove(&oldest);
+        }
+        true
+    }
+}
+
+/// Bulletin ids are unique per node, and controller bulletins may repeat
+/// those of the board.
+type BulletinKey = (Option<i64>, Option<String>, Option<String>);
+
+fn key(bulletin: &BulletinEntity) -> BulletinKey {
+    let dto = bulletin.bulletin.as_ref();
+    (
+        bulletin.id,
+        bulletin.node_address.clone(),
+        dto.and_then(|dto| dto.message.clone()),
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::proxy::v260::api::BulletinDto;
+
+    fn bulletin(id: i64, group_id: &str, level: &str) -> BulletinEntity {
+        BulletinEntity {
+            id: Some(id),
+            group_id: Some(group_id.to_string()),
+            source_id: Some(format!("source-{}", id)),
+            bulletin: Some(BulletinDto {
+                id: Some(id),
+                level: Some(level.to_string()),
+                message: Some(format!("message {}", id)),
+                ..Default::default()
+            }),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_accept_filters_and_deduplicates() {
+        let mut state = StreamState {
+            filter: BulletinFilter {
+                group_id: Some("pg".to_string()),
+                min_severity: Some(Severity::Warning),
+                ..Default::default()
+            },
+            after: None,
+            clustered: false,
+            seen: Seen::default(),
+            pending: VecDeque::new(),
+            first: true,
+        };
+
+        let first = state.accept(vec![
+            bulletin(1, "pg", "ERROR"),
+            bulletin(2, "pg", "INFO"),
+            bulletin(3, "other", "ERROR"),
+            bulletin(1, "pg", "ERROR"),
+        ]);
+        let second = state.accept(vec![bulletin(1, "pg", "ERROR"), bulletin(4, "pg", "WARN")]);
+
+        let ids = |bulletins: Vec<BulletinEntity>| -> Vec<i64> {
+            bulletins
+                .iter()
+                .filter_map(|bulletin| bulletin.id)
+                .collect()
+        };
+        assert_eq!(ids(first), vec![1]);
+        assert_eq!(ids(second), vec![4]);
+    }
+
+    #[test]
+    fn test_advance_drops_the_cursor_on_clusters() {
+        let mut state = StreamState {
+            filter: BulletinFilter::default(),
+            after: None,
+            clustered: false,
+            seen: Seen::default(),
+            pending: VecDeque::new(),
+            first: true,
+        };
+        let on_node = |id: i64, node: &str| BulletinEntity {
+            node_address: Some(node.to_string()),
+            ..bulletin(id, "pg", "ERROR")
+        };
+
+        state.advance(&[bulletin(3, "pg", "ERROR"), bulletin(7, "pg", "ERROR")]);
+        assert_eq!(state.after, Some(7));
+        state.advance(&[bulletin(5, "pg", "ERROR")]);
+        assert_eq!(state.after, Some(7));
+
+        // Node b is far behind node a: a shared cursor would skip its bulletins.
+        state.advance(&[on_node(120, "a:8443"), on_node(4, "b:8443")]);
+        assert_eq!(state.after, None);
+        state.advance(&[bulletin(9, "pg", "ERROR")]);
+        assert_eq!(state.after, None);
+    }
+
+    #[test]
+    fn test_board_query() {
+        let query = BulletinBoardQuery {
+            after: Some(10),
+            group_id: Some("pg".to_string()),
+            limit: Some(50),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            query.query(),
+            vec![
+                ("after", "10".to_string()),
+                ("groupId", "pg".to_string()),
+                ("limit", "50".to_string()),
+            ]
+        );
+    }
+}
@@ -0,0 +1,396 @@
+//! # Cluster Module
+//!
+//! Provides high-level bindings for clustered NiFi:
+//!
+//! * `/controller/cluster` - the nodes of the cluster.
+//! * `/controller/cluster/nodes/{id}` - a single node: read, request a state
+//!   transition (disconnect, connect, offload) or remove it from the cluster.
+//! * `/flow/cluster/summary` - connected and total node counts.
+//!
+//! State transitions are asynchronous; `disconnect_node`, `connect_node` and
+//! `offload_node` poll until the node reaches the target state.
+//! `rolling_maintenance` takes the nodes out one at a time, leaving the node
+//! behind `api_base_url` for last.
+
+use crate::common::client::{HttpClient, JsonResponse};
+use crate::common::config::Config;
+use crate::common::polling::{PollOptions, poll_until};
+use crate::proxy::v260::api::{ClusterEntity, ClusterSummaryEntity, NodeDto, NodeEntity};
+use anyhow::{Context, bail};
+use futures::future::BoxFuture;
+use reqwest::Url;
+use std::fmt;
+use std::sync::Arc;
+use tracing::info;
+
+/// The connection state of a node, from `NodeDto::status`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum NodeStatus {
+    Connecting,
+    Connected,
+    Disconnecting,
+    Disconnected,
+    Offloading,
+    Offloaded,
+}
+
+impl NodeStatus {
+    pub fn parse(status: &str) -> Option<Self> {
+        match status {
+            "CONNECTING" => Some(NodeStatus::Connecting),
+            "CONNECTED" => Some(NodeStatus::Connected),
+            "DISCONNECTING" => Some(NodeStatus::Disconnecting),
+            "DISCONNECTED" => Some(NodeStatus::Disconnected),
+            "OFFLOADING" => Some(NodeStatus::Offloading),
+            "OFFLOADED" => Some(NodeStatus::Offloaded),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            NodeStatus::Connecting => "CONNECTING",
+            NodeStatus::Connected => "CONNECTED",
+            NodeStatus::Disconnecting => "DISCONNECTING",
+            NodeStatus::Disconnected => "DISCONNECTED",
+            NodeStatus::Offloading => "OFFLOADING",
+            NodeStatus::Offloaded => "OFFLOADED",
+        }
+    }
+
+    /// The status of a node, if known.
+    pub fn of(node: &NodeDto) -> Option<Self> {
+        node.status.as_deref().and_then(Self::parse)
+    }
+}
+
+impl fmt::Display for NodeStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// A state transition that can be requested on a node.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Transition {
+    Disconnect,
+    Connect,
+    Offload,
+}
+
+impl Transition {
+    /// The status to request, and the status that ends the transition.
+    fn statuses(self) -> (NodeStatus, NodeStatus) {
+        match self {
+            Transition::Disconnect => (NodeStatus::Disconnecting, NodeStatus::Disconnected),
+            Transition::Connect => (NodeStatus::Connecting, NodeStatus::Connected),
+            Transition::Offload => (NodeStatus::Offloading, NodeStatus::Offloaded),
+        }
+    }
+}
+
+/// The work to do on a node while it is out of the cluster.
+pub type Maintenance<'a> = Box<dyn FnMut(NodeDto) -> BoxFuture<'a, anyhow::Result<()>> + Send + 'a>;
+
+/// A service for managing the nodes of a cluster.
+///
+/// This service is instantiated with shared (`Arc`) instances of `HttpClient` and `Config`.
+#[derive(Debug)]
+pub struct Cluster {
+    client: Arc<HttpClient>,
+    config: Arc<Config>,
+}
+
+impl Cluster {
+    /// Creates a new instance of the `Cluster` service.
+    ///
+    /// # Arguments
+    ///
+    /// * `client` - The shared `HttpClient` to be used for requests.
+    /// * `config` - The application configuration (containing `api_base_url`).
+    pub fn new(client: Arc<HttpClient>, config: Arc<Config>) -> Self {
+        Self { client, config }
+    }
+
+    /// Retrieves the nodes of the cluster.
+    ///
+    /// Sends a `GET` request to `/controller/cluster`.
+    ///
+    /// # Errors
+    /// Returns `HttpClientError` if the request fails (409 when not clustered).
+    pub async fn get_cluster(&self) -> anyhow::Result<ClusterEntity> {
+        let url = format!("{}/controller/cluster", self.config.api_base_url);
+        Ok(self.client.get_json::<ClusterEntity>(&url).await?)
+    }
+
+    /// Retrieves the number of connected nodes.
+    ///
+    /// Sends a `GET` request to `/flow/cluster/summary`.
+    ///
+    /// # Errors
+    /// Returns `HttpClientError` if the request fails.
+    pub async fn get_cluster_summary(&self) -> anyhow::Result<ClusterSummaryEntity> {
+        let url = format!("{}/flow/cluster/summary", self.config.api_base_url);
+        Ok(self.client.get_json::<ClusterSummaryEntity>(&url).await?)
+    }
+
+    /// Retrieves a node.
+    ///
+    /// Sends a `GET` request to `/controller/cluster/nodes/{id}`.
+    ///
+    /// # Errors
+    /// Returns `HttpClientError` if the request fails.
+    pub async fn get_node(&self, id: &str) -> anyhow::Result<NodeEntity> {
+        let url = format!(
+            "{}/controller/cluster/nodes/{}",
+            self.config.api_base_url, id
+        );
+        Ok(self.client.get_json::<NodeEntity>(&url).await?)
+    }
+
+    /// Requests a state transition of a node (through its `status`).
+    ///
+    /// Sends a `PUT` request to `/controller/cluster/nodes/{id}`.
+    ///
+    /// # Errors
+    /// Returns `HttpClientError` if the request fails.
+    pub async fn put_node(&self, id: &str, payload: &NodeEntity) -> anyhow::Result<NodeEntity> {
+        let url = format!(
+            "{}/controller/cluster/nodes/{}",
+            self.config.api_base_url, id
+        );
+        Ok(self
+            .client
+            .put_json::<NodeEntity, NodeEntity>(&url, payload)
+            .await?)
+    }
+
+    /// Removes a disconnected node from the cluster.
+    ///
+    /// Sends a `DELETE` request to `/controller/cluster/nodes/{id}`.
+    ///
+    /// # Errors
+    /// Returns `HttpClientError` if the request fails.
+    pub async fn delete_node(&self, id: &str) -> anyhow::Result<NodeEntity> {
+        let url = format!(
+            "{}/controller/cluster/nodes/{}",
+            self.config.api_base_url, id
+        );
+        let response = self.client.delete::<JsonResponse<NodeEntity>>(&url).await?;
+        Ok(response.0)
+    }
+
+    /// Disconnects a node and waits until it is `DISCONNECTED`.
+    ///
+    /// # Errors
+    /// Returns an error if a request fails or the node does not reach the
+    /// state within `poll.timeout`.
+    pub async fn disconnect_node(&self, id: &str, poll: PollOptions) -> anyhow::Result<NodeDto> {
+        self.transition(id, Transition::Disconnect, poll).await
+    }
+
+    /// Connects a node and waits until it is `CONNECTED`.
+    ///
+    /// # Errors
+    /// Returns an error if a request fails or the node does not reach the
+    /// state within `poll.timeout`.
+    pub async fn connect_node(&self, id: &str, poll: PollOptions) -> anyhow::Result<NodeDto> {
+        self.transition(id, Transition::Connect, poll).await
+    }
+
+    /// Offloads the FlowFiles of a disconnected node to the other nodes and
+    /// waits until it is `OFFLOADED`.
+    ///
+    /// # Errors
+    /// Returns an error if a request fails or the node does not reach the
+    /// state within `poll.timeout`.
+    pub async fn offload_node(&self, id: &str, poll: PollOptions) -> anyhow::Result<NodeDto> {
+        self.transition(id, Transition::Offload, poll).await
+    }
+
+    /// Takes the connected nodes out of the cluster one at a time: disconnect,
+    /// offload, run `maintenance`, reconnect, and only then move on to the
+    /// next node, so the cluster never loses more than one node.
+    ///
+    /// Nodes are processed in `node_id` order, except the node serving
+    /// `api_base_url` (matched on `NodeDto::address` and `api_port`): a
+    /// disconnected node cannot drive the cluster, so it goes last and is
+    /// handled through the address of a node already done. In a single-node
+    /// cluster that node is skipped. When `api_base_url` points at a load
+    /// balancer no node matches and every node is processed in order.
+    ///
+    /// Returns the ids of the nodes that went through maintenance.
+    ///
+    /// # Errors
+    /// Stops at the first failure, leaving the current node as it is so an
+    /// operator can inspect it.
+    pub async fn rolling_maintenance(
+        &self,
+        poll: PollOptions,
+        mut maintenance: Maintenance<'_>,
+    ) -> anyhow::Result<Vec<String>> {
+        let cluster = self.get_cluster().await?.cluster.unwrap_or_default();
+        let mut nodes: Vec<NodeDto> = cluster
+            .nodes
+            .into_iter()
+            .filter(|node| NodeStatus::of(node) == Some(NodeStatus::Connected))
+            .collect();
+        nodes.sort_by(|a, b| a.node_id.cmp(&b.node_id));
+        let (serving, others): (Vec<NodeDto>, Vec<NodeDto>) = nodes
+            .into_iter()
+            .partition(|node| serves(&self.config.api_base_url, node));
+
+        let mut done = Vec::new();
+        for node in &others {
+            if let Some(id) = self.maintain(node, poll, &mut maintenance).await? {
+                done.push(id);
+            }
+        }
+        for node in &serving {
+            let Some(other) = others.first() else {
+                info!(
+                    "Skipping node {}: no other node to drive the cluster through",
+                    node_name(node)
+                );
+                continue;
+            };
+            let config = Config {
+                api_base_url: node_base_url(&self.config.api_base_url, other)?,
+                ..(*self.config).clone()
+            };
+            let through = Cluster::new(self.client.clone(), Arc::new(config));
+            if let Some(id) = through.maintain(node, poll, &mut maintenance).await? {
+                done.push(id);
+            }
+        }
+        Ok(done)
+    }
+
+    /// Runs one node through maintenance; `None` if it has no id.
+    async fn maintain(
+        &self,
+        node: &NodeDto,
+        poll: PollOptions,
+        maintenance: &mut Maintenance<'_>,
+    ) -> anyhow::Result<Option<String>> {
+        let Some(id) = node.node_id.clone() else {
+            return Ok(None);
+        };
+        let name = node_name(node);
+        info!("Disconnecting node {}", name);
+        self.disconnect_node(&id, poll).await?;
+        info!("Offloading node {}", name);
+        let offloaded = self.offload_node(&id, poll).await?;
+        maintenance(offloaded)
+            .await
+            .with_context(|| format!("Maintenance of node {} failed", name))?;
+        info!("Reconnecting node {}", name);
+        self.connect_node(&id, poll).await?;
+        Ok(Some(id))
+    }
+
+    async fn transition(
+        &self,
+        id: &str,
+        transition: Transition,
+        poll: PollOptions,
+    ) -> anyhow::Result<NodeDto> {
+        let (requested, target) = transition.statuses();
+        let payload = NodeEntity {
+            node: Some(NodeDto {
+                node_id: Some(id.to_string()),
+                status: Some(requested.as_str().to_string()),
+                ..Default::default()
+            }),
+        };
+        self.put_node(id, &payload).await?;
+
+        let node = poll_until(poll, || async {
+            let node = self.get_node(id).await?.node.unwrap_or_default();
+            Ok((NodeStatus::of(&node) == Some(target)).then_some(node))
+        })
+        .await;
+        match node {
+            Ok(node) => Ok(node),
+            Err(err) => bail!("Node {} did not become {}: {}", id, target, err),
+        }
+    }
+}
+
+fn node_name(node: &NodeDto) -> String {
+    node.address
+        .clone()
+        .or_else(|| node.node_id.clone())
+        .unwrap_or_default()
+}
+
+/// `true` when `api_base_url` points at `node` (same host and API port).
+fn serves(api_base_url: &str, node: &NodeDto) -> bool {
+    let Ok(url) = Url::parse(api_base_url) else {
+        return false;
+    };
+    let port = url.port_or_known_default().map(i32::from);
+    node.address.is_some() && url.host_str() == node.address.as_deref() && port == node.api_port
+}
+
+/// `api_base_url` with its host and port replaced by those of `node`.
+fn node_base_url(api_base_url: &str, node: &NodeDto) -> anyhow::Result<String> {
+    let mut url = Url::parse(api_base_url)?;
+    let Some(address) = node.address.as_deref() else {
+        bail!("Node {} has no address", node_name(node));
+    };
+    url.set_host(Some(address))?;
+    let port = node.api_port.and_then(|port| u16::try_from(port).ok());
+    if url.set_port(port).is_err() {
+        bail!("Cannot set the port of {}", api_base_url);
+    }
+    Ok(url.as_str().trim_end_matches('/').to_string())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_node_status() {
+        let node = NodeDto {
+            status: Some("OFFLOADED".to_string()),
+            ..Default::default()
+        };
+
+        assert_eq!(NodeStatus::of(&node), Some(NodeStatus::Offloaded));
+        assert_eq!(
+            Transition::Offload.statuses(),
+            (NodeStatus::Offloading, NodeStatus::Offloaded)
+        );
+        for status in [
+            NodeStatus::Connecting,
+            NodeStatus::Connected,
+            NodeStatus::Disconnecting,
+            NodeStatus::Disconnected,
+            NodeStatus::Offloading,
+            NodeStatus::Offloaded,
+        ] {
+            assert_eq!(NodeStatus::parse(status.as_str()), Some(status));
+        }
+    }
+
+    #[test]
+    fn test_node_addresses() {
+        let node = |address: &str, api_port: i32| NodeDto {
+            node_id: Some(format!("{}-id", address)),
+            address: Some(address.to_string()),
+            api_port: Some(api_port),
+            ..Default::default()
+        };
+        let base = "https://nifi-1:8443/nifi-api";
+
+        assert!(serves(base, &node("nifi-1", 8443)));
+        assert!(!serves(base, &node("nifi-2", 8443)));
+        assert!(!serves(base, &node("nifi-1", 9443)));
+        assert_eq!(
+            node_base_url(base, &node("nifi-2", 9443)).unwrap(),
+            "https://nifi-2:9443/nifi-api"
+        );
+    }
+}
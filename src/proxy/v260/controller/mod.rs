@@ -5,9 +5,9 @@
 //!
 //! * The main `Controller` struct for interacting with the API.
 //! * Data Transfer Objects (Dtos) like `ParameterProviderEntity` and `ParameterProviderDto`
-//!     which map directly to the JSON aPI.
+//!   which map directly to the JSON aPI.
 //! * Various supporting enums and Dtos that describe properties, allowable values,
-//!     and component status.
+//!   and component status.
 //!
 //! The primary entities are serialized and deserialized with `serde` using `camelCase`
 //! conventions to match the target JSON API.
@@ -48,8 +48,8 @@ impl Controller {
     /// # Arguments
     ///
     /// * `payload` - A `ParameterProviderEntity` describing the new provider.
-    ///               The `Default::default()` implementation is a good
-    ///               starting point.
+    ///   The `Default::default()` implementation is a good
+    ///   starting point.
     ///
     /// # Errors
     ///
@@ -75,6 +75,7 @@ impl Controller {
 }
 
 #[cfg(test)]
+#[allow(clippy::field_reassign_with_default)]
 mod test {
     use super::super::access::Access;
     use super::*;
@@ -1,6 +1,6 @@
 use crate::common::client::HttpClient;
 use crate::common::config::Config;
-use crate::proxy::v260::api::RegisteredFlowSnapshot;
+use crate::proxy::v260::api::{CurrentUserEntity, RegisteredFlowSnapshot};
 use std::sync::Arc;
 
 #[derive(Debug)]
@@ -26,6 +26,23 @@ impl Flow {
             .await?;
         Ok(response)
     }
+
+    /// Retrieves the identity and top-level permissions of the authenticated user.
+    ///
+    /// Sends a `GET` request to `/flow/current-user`.
+    ///
+    /// # Errors
+    /// Returns `HttpClientError` if the request fails (e.g., 401 without a valid token).
+    pub async fn get_current_user(&self) -> anyhow::Result<CurrentUserEntity> {
+        let response = self
+            .client
+            .get_json::<CurrentUserEntity>(&format!(
+                "{}/flow/current-user",
+                self.config.api_base_url
+            ))
+            .await?;
+        Ok(response)
+    }
 }
 
 #[cfg(test)]
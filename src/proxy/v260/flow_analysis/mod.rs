@@ -0,0 +1,466 @@
+//! # Flow Analysis Module
+//!
+//! Provides bindings for server-side flow analysis:
+//!
+//! * `/controller/flow-analysis-rules` - CRUD, run status (enable/disable),
+//!   property descriptors and component state of flow analysis rules.
+//! * `/flow/flow-analysis/results[/{processGroupId}]` - the rule violations
+//!   found in the flow, mapped to typed `RuleViolation`s.
+//!
+//! `AnalysisResults::check` turns violations of rules with an `ENFORCE`
+//! policy into an error, so deploy tooling can refuse to go on.
+
+use crate::common::client::{HttpClient, JsonResponse};
+use crate::common::config::Config;
+use crate::common::polling::{PollOptions, poll_until};
+use crate::proxy::v260::api::{
+    ComponentStateEntity, FlowAnalysisResultEntity, FlowAnalysisRuleEntity,
+    FlowAnalysisRuleRunStatusEntity, FlowAnalysisRuleRunStatusEntityState,
+    FlowAnalysisRuleViolationDto, FlowAnalysisRulesEntity, PropertyDescriptorEntity,
+};
+use anyhow::bail;
+use reqwest::Url;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Arc;
+use thiserror::Error;
+
+/// What happens when a rule is violated.
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum EnforcementPolicy {
+    /// The violating component cannot be started.
+    Enforce,
+    Warn,
+}
+
+impl EnforcementPolicy {
+    pub fn parse(policy: &str) -> Option<Self> {
+        match policy {
+            "ENFORCE" => Some(EnforcementPolicy::Enforce),
+            "WARN" => Some(EnforcementPolicy::Warn),
+            _ => None,
+        }
+    }
+}
+
+/// A violation of a flow analysis rule by a component (or a whole group).
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct RuleViolation {
+    pub rule_id: String,
+    /// The name of the rule, when it is among the rules of the result.
+    pub rule_name: Option<String>,
+    /// `None` when NiFi reports a policy this module does not know.
+    pub enforcement: Option<EnforcementPolicy>,
+    pub component_id: Option<String>,
+    pub component_name: Option<String>,
+    pub component_type: Option<String>,
+    pub group_id: Option<String>,
+    /// The id of the violation, unique per rule and component.
+    pub issue_id: Option<String>,
+    pub message: String,
+    /// `false` when the rule has since been disabled.
+    pub enabled: bool,
+}
+
+impl RuleViolation {
+    fn from_dto(dto: &FlowAnalysisRuleViolationDto, rule_names: &HashMap<&str, &str>) -> Self {
+        let rule_id = dto.rule_id.clone().unwrap_or_default();
+        Self {
+            rule_name: rule_names
+                .get(rule_id.as_str())
+                .map(|name| name.to_string()),
+            rule_id,
+            enforcement: dto
+                .enforcement_policy
+                .as_deref()
+                .and_then(EnforcementPolicy::parse),
+            component_id: dto.subject_id.clone(),
+            component_name: dto.subject_display_name.clone(),
+            component_type: dto.subject_component_type.clone(),
+            group_id: dto.group_id.clone(),
+            issue_id: dto.issue_id.clone(),
+            message: dto.violation_message.clone().unwrap_or_default(),
+            enabled: dto.enabled.unwrap_or(true),
+        }
+    }
+
+    /// `true` for an active violation of an enforced rule.
+    pub fn is_enforced(&self) -> bool {
+        self.enabled && self.enforcement == Some(EnforcementPolicy::Enforce)
+    }
+}
+
+impl fmt::Display for RuleViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let rule = self.rule_name.as_deref().unwrap_or(&self.rule_id);
+        let component = self
+            .component_name
+            .as_deref()
+            .or(self.component_id.as_deref())
+            .unwrap_or("flow");
+        write!(f, "[{}] {}: {}", rule, component, self.message)
+    }
+}
+
+/// The violations found by flow analysis.
+#[derive(Serialize, Debug, Clone, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct AnalysisResults {
+    /// `true` while an analysis is still running; the violations may be stale.
+    pub pending: bool,
+    pub violations: Vec<RuleViolation>,
+}
+
+impl AnalysisResults {
+    pub fn from_entity(entity: &FlowAnalysisResultEntity) -> Self {
+        let rule_names: HashMap<&str, &str> = entity
+            .rules
+            .iter()
+            .filter_map(|rule| Some((rule.id.as_deref()?, rule.name.as_deref()?)))
+            .collect();
+        Self {
+            pending: entity.flow_analysis_pending.unwrap_or(false),
+            violations: entity
+                .rule_violations
+                .iter()
+                .map(|dto| RuleViolation::from_dto(dto, &rule_names))
+                .collect(),
+        }
+    }
+
+    /// Active violations of enforced rules.
+    pub fn enforced(&self) -> impl Iterator<Item = &RuleViolation> {
+        self.violations.iter().filter(|v| v.is_enforced())
+    }
+
+    /// Active violations of rules that only warn.
+    pub fn warnings(&self) -> impl Iterator<Item = &RuleViolation> {
+        self.violations
+            .iter()
+            .filter(|v| v.enabled && v.enforcement == Some(EnforcementPolicy::Warn))
+    }
+
+    /// Fails when an enforced rule is violated.
+    pub fn check(&self) -> Result<(), FlowAnalysisError> {
+        let enforced: Vec<RuleViolation> = self.enforced().cloned().collect();
+        if enforced.is_empty() {
+            Ok(())
+        } else {
+            Err(FlowAnalysisError::EnforcedViolations(enforced))
+        }
+    }
+}
+
+/// Represents the ways flow analysis can block a deployment.
+#[derive(Debug, Error)]
+pub enum FlowAnalysisError {
+    #[error(
+        "FlowAnalysisError::EnforcedViolations - {}",
+        .0.iter().map(|v| v.to_string()).collect::<Vec<_>>().join("; ")
+    )]
+    EnforcedViolations(Vec<RuleViolation>),
+}
+
+/// A service for managing flow analysis rules and reading their results.
+///
+/// This service is instantiated with shared (`Arc`) instances of `HttpClient` and `Config`.
+#[derive(Debug)]
+pub struct FlowAnalysis {
+    client: Arc<HttpClient>,
+    config: Arc<Config>,
+}
+
+impl FlowAnalysis {
+    /// Creates a new instance of the `FlowAnalysis` service.
+    ///
+    /// # Arguments
+    ///
+    /// * `client` - The shared `HttpClient` to be used for requests.
+    /// * `config` - The application configuration (containing `api_base_url`).
+    pub fn new(client: Arc<HttpClient>, config: Arc<Config>) -> Self {
+        Self { client, config }
+    }
+
+    /// Retrieves all flow analysis rules.
+    ///
+    /// Sends a `GET` request to `/controller/flow-analysis-rules`.
+    ///
+    /// # Errors
+    /// Returns `HttpClientError` if the request fails.
+    pub async fn get_rules(&self) -> anyhow::Result<FlowAnalysisRulesEntity> {
+        let url = format!(
+            "{}/controller/flow-analysis-rules",
+            self.config.api_base_url
+        );
+        Ok(self
+            .client
+            .get_json::<FlowAnalysisRulesEntity>(&url)
+            .await?)
+    }
+
+    /// Creates a flow analysis rule.
+    ///
+    /// Sends a `POST` request to `/controller/flow-analysis-rules`.
+    /// The `payload` must contain a revision with version `0`, a `type` and a `bundle`.
+    ///
+    /// # Errors
+    /// Returns `HttpClientError` if the request fails.
+    pub async fn post_rule(
+        &self,
+        payload: &FlowAnalysisRuleEntity,
+    ) -> anyhow::Result<FlowAnalysisRuleEntity> {
+        let url = format!(
+            "{}/controller/flow-analysis-rules",
+            self.config.api_base_url
+        );
+        Ok(self
+            .client
+            .post_json::<FlowAnalysisRuleEntity, FlowAnalysisRuleEntity>(&url, payload)
+            .await?)
+    }
+
+    /// Retrieves a flow analysis rule.
+    ///
+    /// Sends a `GET` request to `/controller/flow-analysis-rules/{id}`.
+    ///
+    /// # Errors
+    /// Returns `HttpClientError` if the request fails.
+    pub async fn get_rule(&self, id: &str) -> anyhow::Result<FlowAnalysisRuleEntity> {
+        let url = format!(
+            "{}/controller/flow-analysis-rules/{}",
+            self.config.api_base_url, id
+        );
+        Ok(self.client.get_json::<FlowAnalysisRuleEntity>(&url).await?)
+    }
+
+    /// Updates a flow analysis rule, e.g. its properties or enforcement policy.
+    ///
+    /// Sends a `PUT` request to `/controller/flow-analysis-rules/{id}`.
+    /// The `payload` must contain the current revision.
+    ///
+    /// # Errors
+    /// Returns `HttpClientError` if the request fails (e.g., 409 Conflict on bad version).
+    pub async fn put_rule(
+        &self,
+        id: &str,
+        payload: &FlowAnalysisRuleEntity,
+    ) -> anyhow::Result<FlowAnalysisRuleEntity> {
+        let url = format!(
+            "{}/controller/flow-analysis-rules/{}",
+            self.config.api_base_url, id
+        );
+        Ok(self
+            .client
+            .put_json::<FlowAnalysisRuleEntity, FlowAnalysisRuleEntity>(&url, payload)
+            .await?)
+    }
+
+    /// Deletes a disabled flow analysis rule at its current revision.
+    ///
+    /// Sends a `DELETE` request to `/controller/flow-analysis-rules/{id}`.
+    ///
+    /// # Errors
+    /// Returns `HttpClientError` if the request fails.
+    pub async fn delete_rule(&self, id: &str) -> anyhow::Result<FlowAnalysisRuleEntity> {
+        let rule = self.get_rule(id).await?;
+        let Some(version) = rule.revision.and_then(|revision| revision.version) else {
+            bail!("Revision was None");
+        };
+        let url = Url::parse_with_params(
+            &format!(
+                "{}/controller/flow-analysis-rules/{}",
+                self.config.api_base_url, id
+            ),
+            [("version", version.to_string())],
+        )?;
+        let response = self
+            .client
+            .delete::<JsonResponse<FlowAnalysisRuleEntity>>(url.as_str())
+            .await?;
+        Ok(response.0)
+    }
+
+    /// Enables or disables a flow analysis rule.
+    ///
+    /// Sends a `PUT` request to `/controller/flow-analysis-rules/{id}/run-status`.
+    /// The `payload` must contain the current revision.
+    ///
+    /// # Errors
+    /// Returns `HttpClientError` if the request fails.
+    pub async fn put_run_status(
+        &self,
+        id: &str,
+        payload: &FlowAnalysisRuleRunStatusEntity,
+    ) -> anyhow::Result<FlowAnalysisRuleEntity> {
+        let url = format!(
+            "{}/controller/flow-analysis-rules/{}/run-status",
+            self.config.api_base_url, id
+        );
+        Ok(self
+            .client
+            .put_json::<FlowAnalysisRuleRunStatusEntity, FlowAnalysisRuleEntity>(&url, payload)
+            .await?)
+    }
+
+    /// Enables or disables a flow analysis rule at its current revision.
+    ///
+    /// # Errors
+    /// Returns an error if a request fails (e.g., 409 Conflict when the rule is invalid).
+    pub async fn set_run_status(
+        &self,
+        id: &str,
+        state: FlowAnalysisRuleRunStatusEntityState,
+    ) -> anyhow::Result<FlowAnalysisRuleEntity> {
+        let rule = self.get_rule(id).await?;
+        let payload = FlowAnalysisRuleRunStatusEntity {
+            revision: rule.revision,
+            state: Some(state),
+            disconnected_node_acknowledged: None,
+        };
+        self.put_run_status(id, &payload).await
+    }
+
+    /// Retrieves the descriptor of a property.
+    ///
+    /// Sends a `GET` request to `/controller/flow-analysis-rules/{id}/descriptors`.
+    ///
+    /// # Errors
+    /// Returns `HttpClientError` if the request fails.
+    pub async fn get_property_descriptor(
+        &self,
+        id: &str,
+        property_name: &str,
+        sensitive: bool,
+    ) -> anyhow::Result<PropertyDescriptorEntity> {
+        let url = Url::parse_with_params(
+            &format!(
+                "{}/controller/flow-analysis-rules/{}/descriptors",
+                self.config.api_base_url, id
+            ),
+            [
+                ("propertyName", property_name.to_string()),
+                ("sensitive", sensitive.to_string()),
+            ],
+        )?;
+        Ok(self
+            .client
+            .get_json::<PropertyDescriptorEntity>(url.as_str())
+            .await?)
+    }
+
+    /// Retrieves the state stored by a flow analysis rule.
+    ///
+    /// Sends a `GET` request to `/controller/flow-analysis-rules/{id}/state`.
+    ///
+    /// # Errors
+    /// Returns `HttpClientError` if the request fails.
+    pub async fn get_state(&self, id: &str) -> anyhow::Result<ComponentStateEntity> {
+        let url = format!(
+            "{}/controller/flow-analysis-rules/{}/state",
+            self.config.api_base_url, id
+        );
+        Ok(self.client.get_json::<ComponentStateEntity>(&url).await?)
+    }
+
+    /// Clears the state of a disabled flow analysis rule.
+    ///
+    /// Sends a `POST` request to `/controller/flow-analysis-rules/{id}/state/clear-requests`.
+    ///
+    /// # Errors
+    /// Returns `HttpClientError` if the request fails.
+    pub async fn clear_state(&self, id: &str) -> anyhow::Result<ComponentStateEntity> {
+        let url = format!(
+            "{}/controller/flow-analysis-rules/{}/state/clear-requests",
+            self.config.api_base_url, id
+        );
+        Ok(self.client.post_empty::<ComponentStateEntity>(&url).await?)
+    }
+
+    /// Retrieves the rule violations of the whole flow, or of one process group.
+    ///
+    /// Sends a `GET` request to `/flow/flow-analysis/results[/{processGroupId}]`.
+    ///
+    /// # Errors
+    /// Returns `HttpClientError` if the request fails.
+    pub async fn get_results(
+        &self,
+        process_group_id: Option<&str>,
+    ) -> anyhow::Result<FlowAnalysisResultEntity> {
+        let url = match process_group_id {
+            Some(id) => format!(
+                "{}/flow/flow-analysis/results/{}",
+                self.config.api_base_url, id
+            ),
+            None => format!("{}/flow/flow-analysis/results", self.config.api_base_url),
+        };
+        Ok(self
+            .client
+            .get_json::<FlowAnalysisResultEntity>(&url)
+            .await?)
+    }
+
+    /// Waits until no analysis is pending and returns the typed violations.
+    ///
+    /// # Errors
+    /// Returns an error if a request fails or the analysis is still pending
+    /// after `poll.timeout`.
+    pub async fn analysis_results(
+        &self,
+        process_group_id: Option<&str>,
+        poll: PollOptions,
+    ) -> anyhow::Result<AnalysisResults> {
+        let results = poll_until(poll, || async {
+            let results = AnalysisResults::from_entity(&self.get_results(process_group_id).await?);
+            Ok((!results.pending).then_some(results))
+        })
+        .await?;
+        Ok(results)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::proxy::v260::api::FlowAnalysisRuleDto;
+
+    fn violation(rule_id: &str, policy: &str, enabled: bool) -> FlowAnalysisRuleViolationDto {
+        FlowAnalysisRuleViolationDto {
+            enabled: Some(enabled),
+            enforcement_policy: Some(policy.to_string()),
+            rule_id: Some(rule_id.to_string()),
+            subject_display_name: Some("GenerateFlowFile".to_string()),
+            subject_id: Some("p-1".to_string()),
+            violation_message: Some("Concurrent tasks above 4".to_string()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_analysis_results() {
+        let entity = FlowAnalysisResultEntity {
+            flow_analysis_pending: Some(false),
+            rules: vec![FlowAnalysisRuleDto {
+                id: Some("r-1".to_string()),
+                name: Some("Max Concurrency".to_string()),
+                ..Default::default()
+            }],
+            rule_violations: vec![
+                violation("r-1", "ENFORCE", true),
+                violation("r-2", "WARN", true),
+                violation("r-3", "ENFORCE", false),
+            ],
+        };
+
+        let results = AnalysisResults::from_entity(&entity);
+
+        assert!(!results.pending);
+        assert_eq!(results.enforced().count(), 1);
+        assert_eq!(results.warnings().count(), 1);
+        assert_eq!(
+            results.check().unwrap_err().to_string(),
+            "FlowAnalysisError::EnforcedViolations - [Max Concurrency] GenerateFlowFile: Concurrent tasks above 4"
+        );
+    }
+}
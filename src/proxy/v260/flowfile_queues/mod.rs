@@ -0,0 +1,541 @@
+//! # FlowFile Queues Module
+//!
+//! Provides high-level bindings to look inside connection queues and empty them:
+//!
+//! * `/flowfile-queues/{id}/listing-requests` - the async listing flow, wrapped
+//!   by `list_queue`, returning typed `FlowFileSummary`s.
+//! * `/flowfile-queues/{id}/flowfiles/{uuid}` - the attributes of a single
+//!   FlowFile and its content as a byte stream.
+//! * `/flowfile-queues/{id}/drop-requests` and
+//!   `/process-groups/{id}/empty-all-connections-requests` - emptying one
+//!   connection or every connection of a group, wrapped by `empty_queue` and
+//!   `empty_process_group`, reporting `DropProgress` while they run.
+
+use crate::common::client::{ByteStream, HttpClient, JsonResponse};
+use crate::common::config::Config;
+use crate::common::polling::{PollOptions, RequestStatus, run_async_request, with_node};
+use crate::proxy::v260::api::{
+    DropRequestDto, DropRequestEntity, FlowFileEntity, FlowFileSummaryDto, ListingRequestDto,
+    ListingRequestEntity,
+};
+use serde::Serialize;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// The two endpoints sharing `DropRequestEntity`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum DropTarget {
+    /// A single connection, by connection id.
+    Queue,
+    /// Every connection of a process group (recursively), by group id.
+    ProcessGroup,
+}
+
+impl DropTarget {
+    fn path(self, id: &str) -> String {
+        match self {
+            DropTarget::Queue => format!("flowfile-queues/{}/drop-requests", id),
+            DropTarget::ProcessGroup => {
+                format!("process-groups/{}/empty-all-connections-requests", id)
+            },
+        }
+    }
+}
+
+/// A FlowFile waiting in a queue.
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct FlowFileSummary {
+    pub uuid: String,
+    pub filename: Option<String>,
+    pub mime_type: Option<String>,
+    /// The position in the queue, starting at 1.
+    pub position: Option<i32>,
+    /// The content size in bytes.
+    pub size: i64,
+    pub queued_duration: Duration,
+    pub lineage_duration: Duration,
+    pub penalized: bool,
+    /// The node holding the FlowFile, in a cluster.
+    pub cluster_node_id: Option<String>,
+}
+
+impl FlowFileSummary {
+    fn from_dto(dto: FlowFileSummaryDto) -> Option<Self> {
+        let millis = |value: Option<i64>| Duration::from_millis(value.unwrap_or(0).max(0) as u64);
+        Some(Self {
+            uuid: dto.uuid?,
+            filename: dto.filename,
+            mime_type: dto.mime_type,
+            position: dto.position,
+            size: dto.size.unwrap_or(0),
+            queued_duration: millis(dto.queued_duration),
+            lineage_duration: millis(dto.lineage_duration),
+            penalized: dto.penalized.unwrap_or(false),
+            cluster_node_id: dto.cluster_node_id,
+        })
+    }
+}
+
+/// The result of a queue listing.
+#[derive(Serialize, Debug, Clone, PartialEq, Eq, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct QueueListing {
+    /// The first FlowFiles of the queue (NiFi lists at most 100).
+    pub flow_files: Vec<FlowFileSummary>,
+    /// The number of FlowFiles in the whole queue.
+    pub queued_count: i32,
+    /// The size of the whole queue, in bytes.
+    pub queued_bytes: i64,
+    pub source_running: bool,
+    pub destination_running: bool,
+}
+
+/// The progress of a drop request.
+#[derive(Serialize, Debug, Clone, PartialEq, Eq, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct DropProgress {
+    pub percent_completed: i32,
+    pub original_count: i32,
+    pub original_bytes: i64,
+    pub dropped_count: i32,
+    pub dropped_bytes: i64,
+    /// What is still queued.
+    pub current_count: i32,
+    pub current_bytes: i64,
+    pub state: Option<String>,
+    pub finished: bool,
+}
+
+impl DropProgress {
+    fn from_dto(dto: &DropRequestDto) -> Self {
+        Self {
+            percent_completed: dto.percent_completed.unwrap_or(0),
+            original_count: dto.original_count.unwrap_or(0),
+            original_bytes: dto.original_size.unwrap_or(0),
+            dropped_count: dto.dropped_count.unwrap_or(0),
+            dropped_bytes: dto.dropped_size.unwrap_or(0),
+            current_count: dto.current_count.unwrap_or(0),
+            current_bytes: dto.current_size.unwrap_or(0),
+            state: dto.state.clone(),
+            finished: dto.finished.unwrap_or(false),
+        }
+    }
+}
+
+/// A service for inspecting and emptying FlowFile queues.
+///
+/// This service is instantiated with shared (`Arc`) instances of `HttpClient` and `Config`.
+#[derive(Debug)]
+pub struct FlowFileQueues {
+    client: Arc<HttpClient>,
+    config: Arc<Config>,
+}
+
+impl FlowFileQueues {
+    /// Creates a new instance of the `FlowFileQueues` service.
+    ///
+    /// # Arguments
+    ///
+    /// * `client` - The shared `HttpClient` to be used for requests.
+    /// * `config` - The application configuration (containing `api_base_url`).
+    pub fn new(client: Arc<HttpClient>, config: Arc<Config>) -> Self {
+        Self { client, config }
+    }
+
+    /// Creates a listing request for the queue of a connection.
+    ///
+    /// Sends a `POST` request to `/flowfile-queues/{id}/listing-requests`.
+    ///
+    /// # Errors
+    /// Returns `HttpClientError` if the request fails.
+    pub async fn post_listing_request(
+        &self,
+        connection_id: &str,
+    ) -> anyhow::Result<ListingRequestEntity> {
+        let url = format!(
+            "{}/flowfile-queues/{}/listing-requests",
+            self.config.api_base_url, connection_id
+        );
+        Ok(self.client.post_empty::<ListingRequestEntity>(&url).await?)
+    }
+
+    /// Retrieves the state and results of a listing request.
+    ///
+    /// Sends a `GET` request to `/flowfile-queues/{id}/listing-requests/{listing-request-id}`.
+    ///
+    /// # Errors
+    /// Returns `HttpClientError` if the request fails.
+    pub async fn get_listing_request(
+        &self,
+        connection_id: &str,
+        request_id: &str,
+    ) -> anyhow::Result<ListingRequestEntity> {
+        let url = format!(
+            "{}/flowfile-queues/{}/listing-requests/{}",
+            self.config.api_base_url, connection_id, request_id
+        );
+        Ok(self.client.get_json::<ListingRequestEntity>(&url).await?)
+    }
+
+    /// Deletes a listing request.
+    ///
+    /// Sends a `DELETE` request to `/flowfile-queues/{id}/listing-requests/{listing-request-id}`.
+    ///
+    /// # Errors
+    /// Returns `HttpClientError` if the request fails.
+    pub async fn delete_listing_request(
+        &self,
+        connection_id: &str,
+        request_id: &str,
+    ) -> anyhow::Result<ListingRequestEntity> {
+        let url = format!(
+            "{}/flowfile-queues/{}/listing-requests/{}",
+            self.config.api_base_url, connection_id, request_id
+        );
+        let response = self
+            .client
+            .delete::<JsonResponse<ListingRequestEntity>>(&url)
+            .await?;
+        Ok(response.0)
+    }
+
+    /// Lists the FlowFiles queued in a connection.
+    ///
+    /// Creates a listing request, polls it until finished and deletes it
+    /// afterwards, even when polling failed.
+    ///
+    /// # Errors
+    /// Returns an error if a request fails, the listing does not finish within
+    /// `poll.timeout`, or NiFi reports a failure.
+    pub async fn list_queue(
+        &self,
+        connection_id: &str,
+        poll: PollOptions,
+    ) -> anyhow::Result<QueueListing> {
+        let request: ListingRequestDto = run_async_request(
+            &format!("Listing request for connection {}", connection_id),
+            poll,
+            async {
+                let submitted = self.post_listing_request(connection_id).await?;
+                Ok(submitted.listing_request.and_then(|request| request.id))
+            },
+            |request_id| async move {
+                Ok(self
+                    .get_listing_request(connection_id, &request_id)
+                    .await?
+                    .listing_request
+                    .unwrap_or_default())
+            },
+            |request_id| async move {
+                self.delete_listing_request(connection_id, &request_id)
+                    .await
+            },
+        )
+        .await?;
+        let queue_size = request.queue_size.unwrap_or_default();
+        Ok(QueueListing {
+            flow_files: request
+                .flow_file_summaries
+                .into_iter()
+                .filter_map(FlowFileSummary::from_dto)
+                .collect(),
+            queued_count: queue_size.object_count.unwrap_or(0),
+            queued_bytes: queue_size.byte_count.unwrap_or(0),
+            source_running: request.source_running.unwrap_or(false),
+            destination_running: request.destination_running.unwrap_or(false),
+        })
+    }
+
+    /// Retrieves a queued FlowFile, with its attributes.
+    ///
+    /// Sends a `GET` request to `/flowfile-queues/{id}/flowfiles/{flowfile-uuid}`.
+    ///
+    /// # Errors
+    /// Returns `HttpClientError` if the request fails.
+    pub async fn get_flow_file(
+        &self,
+        connection_id: &str,
+        flow_file_uuid: &str,
+        cluster_node_id: Option<&str>,
+    ) -> anyhow::Result<FlowFileEntity> {
+        let url = with_node(
+            format!(
+                "{}/flowfile-queues/{}/flowfiles/{}",
+                self.config.api_base_url, connection_id, flow_file_uuid
+            ),
+            cluster_node_id,
+        )?;
+        Ok(self.client.get_json::<FlowFileEntity>(&url).await?)
+    }
+
+    /// Downloads the content of a queued FlowFile.
+    ///
+    /// Sends a `GET` request to `/flowfile-queues/{id}/flowfiles/{flowfile-uuid}/content`
+    /// and returns the body as a stream of chunks. The whole download is
+    /// limited by `STREAM_TIMEOUT` (one hour), not the usual 30 seconds.
+    ///
+    /// # Errors
+    /// Returns `HttpClientError` if the request fails.
+    pub async fn get_flow_file_content(
+        &self,
+        connection_id: &str,
+        flow_file_uuid: &str,
+        cluster_node_id: Option<&str>,
+    ) -> anyhow::Result<ByteStream> {
+        let url = with_node(
+            format!(
+                "{}/flowfile-queues/{}/flowfiles/{}/content",
+                self.config.api_base_url, connection_id, flow_file_uuid
+            ),
+            cluster_node_id,
+        )?;
+        Ok(self.client.get_stream(&url).await?)
+    }
+
+    /// Creates a request to drop every FlowFile queued in a connection.
+    ///
+    /// Sends a `POST` request to `/flowfile-queues/{id}/drop-requests`.
+    ///
+    /// # Errors
+    /// Returns `HttpClientError` if the request fails.
+    pub async fn post_drop_request(
+        &self,
+        connection_id: &str,
+    ) -> anyhow::Result<DropRequestEntity> {
+        self.post_drop(DropTarget::Queue, connection_id).await
+    }
+
+    /// Retrieves the progress of a drop request.
+    ///
+    /// Sends a `GET` request to `/flowfile-queues/{id}/drop-requests/{drop-request-id}`.
+    ///
+    /// # Errors
+    /// Returns `HttpClientError` if the request fails.
+    pub async fn get_drop_request(
+        &self,
+        connection_id: &str,
+        request_id: &str,
+    ) -> anyhow::Result<DropRequestEntity> {
+        self.get_drop(DropTarget::Queue, connection_id, request_id)
+            .await
+    }
+
+    /// Deletes a drop request, cancelling it if still running.
+    ///
+    /// Sends a `DELETE` request to `/flowfile-queues/{id}/drop-requests/{drop-request-id}`.
+    ///
+    /// # Errors
+    /// Returns `HttpClientError` if the request fails.
+    pub async fn delete_drop_request(
+        &self,
+        connection_id: &str,
+        request_id: &str,
+    ) -> anyhow::Result<DropRequestEntity> {
+        self.delete_drop(DropTarget::Queue, connection_id, request_id)
+            .await
+    }
+
+    /// Creates a request to empty every connection of a process group.
+    ///
+    /// Sends a `POST` request to `/process-groups/{id}/empty-all-connections-requests`.
+    ///
+    /// # Errors
+    /// Returns `HttpClientError` if the request fails.
+    pub async fn post_empty_all_connections_request(
+        &self,
+        group_id: &str,
+    ) -> anyhow::Result<DropRequestEntity> {
+        self.post_drop(DropTarget::ProcessGroup, group_id).await
+    }
+
+    /// Retrieves the progress of an empty-all-connections request.
+    ///
+    /// Sends a `GET` request to `/process-groups/{id}/empty-all-connections-requests/{drop-request-id}`.
+    ///
+    /// # Errors
+    /// Returns `HttpClientError` if the request fails.
+    pub async fn get_empty_all_connections_request(
+        &self,
+        group_id: &str,
+        request_id: &str,
+    ) -> anyhow::Result<DropRequestEntity> {
+        self.get_drop(DropTarget::ProcessGroup, group_id, request_id)
+            .await
+    }
+
+    /// Deletes an empty-all-connections request, cancelling it if still running.
+    ///
+    /// Sends a `DELETE` request to `/process-groups/{id}/empty-all-connections-requests/{drop-request-id}`.
+    ///
+    /// # Errors
+    /// Returns `HttpClientError` if the request fails.
+    pub async fn delete_empty_all_connections_request(
+        &self,
+        group_id: &str,
+        request_id: &str,
+    ) -> anyhow::Result<DropRequestEntity> {
+        self.delete_drop(DropTarget::ProcessGroup, group_id, request_id)
+            .await
+    }
+
+    /// Drops every FlowFile queued in a connection.
+    ///
+    /// `on_progress` is called after every poll with the current counts.
+    ///
+    /// # Errors
+    /// Returns an error if a request fails, the drop does not finish within
+    /// `poll.timeout`, or NiFi reports a failure.
+    pub async fn empty_queue(
+        &self,
+        connection_id: &str,
+        poll: PollOptions,
+        on_progress: impl FnMut(&DropProgress) + Send,
+    ) -> anyhow::Result<DropProgress> {
+        self.run_drop(DropTarget::Queue, connection_id, poll, on_progress)
+            .await
+    }
+
+    /// Drops every FlowFile queued in the connections of a process group and
+    /// its descendants.
+    ///
+    /// `on_progress` is called after every poll with the current counts.
+    ///
+    /// # Errors
+    /// Returns an error if a request fails, the drop does not finish within
+    /// `poll.timeout`, or NiFi reports a failure.
+    pub async fn empty_process_group(
+        &self,
+        group_id: &str,
+        poll: PollOptions,
+        on_progress: impl FnMut(&DropProgress) + Send,
+    ) -> anyhow::Result<DropProgress> {
+        self.run_drop(DropTarget::ProcessGroup, group_id, poll, on_progress)
+            .await
+    }
+
+    async fn post_drop(&self, target: DropTarget, id: &str) -> anyhow::Result<DropRequestEntity> {
+        let url = format!("{}/{}", self.config.api_base_url, target.path(id));
+        Ok(self.client.post_empty::<DropRequestEntity>(&url).await?)
+    }
+
+    async fn get_drop(
+        &self,
+        target: DropTarget,
+        id: &str,
+        request_id: &str,
+    ) -> anyhow::Result<DropRequestEntity> {
+        let url = format!(
+            "{}/{}/{}",
+            self.config.api_base_url,
+            target.path(id),
+            request_id
+        );
+        Ok(self.client.get_json::<DropRequestEntity>(&url).await?)
+    }
+
+    async fn delete_drop(
+        &self,
+        target: DropTarget,
+        id: &str,
+        request_id: &str,
+    ) -> anyhow::Result<DropRequestEntity> {
+        let url = format!(
+            "{}/{}/{}",
+            self.config.api_base_url,
+            target.path(id),
+            request_id
+        );
+        let response = self
+            .client
+            .delete::<JsonResponse<DropRequestEntity>>(&url)
+            .await?;
+        Ok(response.0)
+    }
+
+    /// Submits a drop request, polls it until finished, then deletes it.
+    async fn run_drop(
+        &self,
+        target: DropTarget,
+        id: &str,
+        poll: PollOptions,
+        on_progress: impl FnMut(&DropProgress) + Send,
+    ) -> anyhow::Result<DropProgress> {
+        // The poll callback returns a future, which cannot hold a mutable
+        // borrow of `on_progress` across polls.
+        let on_progress = &Mutex::new(on_progress);
+        let request = run_async_request(
+            &format!("Drop request for {}", target.path(id)),
+            poll,
+            async {
+                let submitted = self.post_drop(target, id).await?;
+                Ok(submitted.drop_request.and_then(|request| request.id))
+            },
+            |request_id| async move {
+                let request = self
+                    .get_drop(target, id, &request_id)
+                    .await?
+                    .drop_request
+                    .unwrap_or_default();
+                if let Ok(mut on_progress) = on_progress.lock() {
+                    on_progress(&DropProgress::from_dto(&request));
+                }
+                Ok(request)
+            },
+            |request_id| async move { self.delete_drop(target, id, &request_id).await },
+        )
+        .await?;
+        Ok(DropProgress::from_dto(&request))
+    }
+}
+
+impl RequestStatus for ListingRequestDto {
+    fn is_finished(&self) -> bool {
+        self.finished.unwrap_or(false)
+    }
+
+    fn failure_reason(&self) -> Option<&str> {
+        self.failure_reason.as_deref()
+    }
+}
+
+impl RequestStatus for DropRequestDto {
+    fn is_finished(&self) -> bool {
+        self.finished.unwrap_or(false)
+    }
+
+    fn failure_reason(&self) -> Option<&str> {
+        self.failure_reason.as_deref()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_drop_progress() {
+        let dto = DropRequestDto {
+            percent_completed: Some(100),
+            original_count: Some(10),
+            original_size: Some(1024),
+            dropped_count: Some(10),
+            dropped_size: Some(1024),
+            current_count: Some(0),
+            current_size: Some(0),
+            state: Some("Completed successfully".to_string()),
+            finished: Some(true),
+            ..Default::default()
+        };
+
+        let progress = DropProgress::from_dto(&dto);
+
+        assert!(progress.finished);
+        assert_eq!(progress.dropped_count, 10);
+        assert_eq!(progress.current_count, 0);
+        assert_eq!(
+            DropTarget::ProcessGroup.path("pg"),
+            "process-groups/pg/empty-all-connections-requests"
+        );
+    }
+}
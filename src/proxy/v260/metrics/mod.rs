@@ -0,0 +1,127 @@
+//! # Metrics Module
+//!
+//! Provides a client for `/flow/metrics/{producer}`. The Prometheus output
+//! is parsed with `common::prometheus` into metric families that can be
+//! queried by component id or name.
+
+use crate::common::client::HttpClient;
+use crate::common::config::Config;
+use crate::common::prometheus::Metrics;
+use reqwest::Url;
+use std::sync::Arc;
+
+/// The metrics registries NiFi can expose.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MetricsRegistry {
+    Nifi,
+    Jvm,
+    Bulletin,
+    Connection,
+    Cluster,
+}
+
+impl MetricsRegistry {
+    fn as_str(self) -> &'static str {
+        match self {
+            MetricsRegistry::Nifi => "NIFI",
+            MetricsRegistry::Jvm => "JVM",
+            MetricsRegistry::Bulletin => "BULLETIN",
+            MetricsRegistry::Connection => "CONNECTION",
+            MetricsRegistry::Cluster => "CLUSTER",
+        }
+    }
+}
+
+/// Server-side filtering of the exposed samples.
+#[derive(Debug, Clone, Default)]
+pub struct MetricsFilter {
+    /// The registries to include; all of them when empty.
+    pub registries: Vec<MetricsRegistry>,
+    /// A regular expression applied to sample names.
+    pub sample_name: Option<String>,
+    /// A regular expression applied to sample label values.
+    pub sample_label_value: Option<String>,
+}
+
+impl MetricsFilter {
+    fn query(&self) -> Vec<(&'static str, String)> {
+        let mut query: Vec<(&'static str, String)> = self
+            .registries
+            .iter()
+            .map(|registry| ("includedRegistries", registry.as_str().to_string()))
+            .collect();
+        if let Some(sample_name) = &self.sample_name {
+            query.push(("sampleName", sample_name.clone()));
+        }
+        if let Some(value) = &self.sample_label_value {
+            query.push(("sampleLabelValue", value.clone()));
+        }
+        query
+    }
+}
+
+/// A service for reading flow metrics.
+///
+/// This service is instantiated with shared (`Arc`) instances of `HttpClient` and `Config`.
+#[derive(Debug)]
+pub struct FlowMetrics {
+    client: Arc<HttpClient>,
+    config: Arc<Config>,
+}
+
+impl FlowMetrics {
+    /// Creates a new instance of the `FlowMetrics` service.
+    ///
+    /// # Arguments
+    ///
+    /// * `client` - The shared `HttpClient` to be used for requests.
+    /// * `config` - The application configuration (containing `api_base_url`).
+    pub fn new(client: Arc<HttpClient>, config: Arc<Config>) -> Self {
+        Self { client, config }
+    }
+
+    /// Retrieves the metrics in the Prometheus text exposition format.
+    ///
+    /// Sends a `GET` request to `/flow/metrics/prometheus`.
+    ///
+    /// # Errors
+    /// Returns `HttpClientError` if the request fails.
+    pub async fn get_prometheus_text(&self, filter: &MetricsFilter) -> anyhow::Result<String> {
+        let url = Url::parse_with_params(
+            &format!("{}/flow/metrics/prometheus", self.config.api_base_url),
+            filter.query(),
+        )?;
+        Ok(self.client.get_text(url.as_str()).await?)
+    }
+
+    /// Retrieves and parses the Prometheus metrics.
+    ///
+    /// # Errors
+    /// Returns an error if the request fails or the text cannot be parsed.
+    pub async fn get_prometheus(&self, filter: &MetricsFilter) -> anyhow::Result<Metrics> {
+        let text = self.get_prometheus_text(filter).await?;
+        Ok(Metrics::parse(&text)?)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_filter_query() {
+        let filter = MetricsFilter {
+            registries: vec![MetricsRegistry::Nifi, MetricsRegistry::Connection],
+            sample_name: Some("nifi_amount_.*".to_string()),
+            sample_label_value: None,
+        };
+
+        let url =
+            Url::parse_with_params("https://nifi/flow/metrics/prometheus", filter.query()).unwrap();
+
+        assert_eq!(
+            url.query(),
+            Some("includedRegistries=NIFI&includedRegistries=CONNECTION&sampleName=nifi_amount_.*")
+        );
+    }
+}
@@ -2,7 +2,7 @@ use crate::proxy::v260::api::{
     ExternalControllerServiceReference, ParameterProviderReference, VersionedParameterContext,
     VersionedProcessGroup,
 };
-use serde::{Deserialize, Deserializer, Serialize};
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
 #[allow(warnings)]
@@ -11,9 +11,23 @@ pub mod api {
 }
 pub mod access;
 pub mod authentication;
+pub mod bulletins;
+pub mod cluster;
 pub mod controller;
 pub mod flow;
+pub mod flow_analysis;
+pub mod flowfile_queues;
+pub mod metrics;
 pub mod parameter_context;
+pub mod policies;
+pub mod process_group;
+pub mod provenance;
+pub mod reporting_tasks;
+pub mod status;
+pub mod system_diagnostics;
+pub mod tenants;
+pub mod verification;
+pub mod versions;
 
 #[derive(Deserialize, Serialize, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
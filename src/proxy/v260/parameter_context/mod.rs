@@ -4,10 +4,17 @@
 //!
 //! This module allows for creating, reading, and updating Parameter Contexts,
 //! which are collections of parameters that can be shared across Process Groups.
+//!
+//! Contexts bound to process groups must be changed through the async
+//! update-request flow, wrapped by `update_parameter_context`.
 
 use crate::common::client::{HttpClient, JsonResponse};
 use crate::common::config::Config;
-use crate::proxy::v260::api::{ParameterContextEntity, ParameterContextsEntity};
+use crate::common::polling::{PollOptions, RequestStatus, run_async_request};
+use crate::proxy::v260::api::{
+    ParameterContextEntity, ParameterContextUpdateRequestDto, ParameterContextUpdateRequestEntity,
+    ParameterContextsEntity,
+};
 use anyhow::bail;
 use std::sync::Arc;
 
@@ -146,9 +153,121 @@ impl ParameterContext {
             .await?;
         Ok(response.0)
     }
+
+    /// Submits a request to update a Parameter Context, restarting the
+    /// components that reference the changed parameters.
+    ///
+    /// Sends a `POST` request to `/parameter-contexts/{id}/update-requests`.
+    /// The `payload` must contain the current revision.
+    ///
+    /// # Errors
+    /// Returns `HttpClientError` if the request fails.
+    pub async fn post_update_request(
+        &self,
+        id: &str,
+        payload: &ParameterContextEntity,
+    ) -> anyhow::Result<ParameterContextUpdateRequestEntity> {
+        let response = self
+            .client
+            .post_json::<ParameterContextEntity, ParameterContextUpdateRequestEntity>(
+                &format!(
+                    "{}/parameter-contexts/{}/update-requests",
+                    self.config.api_base_url, id
+                ),
+                payload,
+            )
+            .await?;
+        Ok(response)
+    }
+
+    /// Retrieves the progress of an update request.
+    ///
+    /// Sends a `GET` request to `/parameter-contexts/{id}/update-requests/{request_id}`.
+    ///
+    /// # Errors
+    /// Returns `HttpClientError` if the request fails.
+    pub async fn get_update_request(
+        &self,
+        id: &str,
+        request_id: &str,
+    ) -> anyhow::Result<ParameterContextUpdateRequestEntity> {
+        let response = self
+            .client
+            .get_json::<ParameterContextUpdateRequestEntity>(&format!(
+                "{}/parameter-contexts/{}/update-requests/{}",
+                self.config.api_base_url, id, request_id
+            ))
+            .await?;
+        Ok(response)
+    }
+
+    /// Deletes a (finished) update request.
+    ///
+    /// Sends a `DELETE` request to `/parameter-contexts/{id}/update-requests/{request_id}`.
+    ///
+    /// # Errors
+    /// Returns `HttpClientError` if the request fails.
+    pub async fn delete_update_request(
+        &self,
+        id: &str,
+        request_id: &str,
+    ) -> anyhow::Result<ParameterContextUpdateRequestEntity> {
+        let response = self
+            .client
+            .delete::<JsonResponse<ParameterContextUpdateRequestEntity>>(&format!(
+                "{}/parameter-contexts/{}/update-requests/{}",
+                self.config.api_base_url, id, request_id
+            ))
+            .await?;
+        Ok(response.0)
+    }
+
+    /// Updates a Parameter Context and waits until NiFi finishes.
+    ///
+    /// Runs the whole update-request flow: submits `payload`, polls the request
+    /// until it completes and deletes it afterwards.
+    ///
+    /// # Errors
+    /// Returns an error if any request fails, if NiFi reports a failure reason,
+    /// or if the request does not complete within `poll.timeout`.
+    pub async fn update_parameter_context(
+        &self,
+        id: &str,
+        payload: &ParameterContextEntity,
+        poll: PollOptions,
+    ) -> anyhow::Result<ParameterContextUpdateRequestDto> {
+        run_async_request(
+            &format!("Update request for parameter context {}", id),
+            poll,
+            async {
+                let submitted = self.post_update_request(id, payload).await?;
+                Ok(submitted.request.and_then(|request| request.request_id))
+            },
+            |request_id| async move {
+                Ok(self
+                    .get_update_request(id, &request_id)
+                    .await?
+                    .request
+                    .unwrap_or_default())
+            },
+            |request_id| async move { self.delete_update_request(id, &request_id).await },
+        )
+        .await
+    }
+}
+
+impl RequestStatus for ParameterContextUpdateRequestDto {
+    fn is_finished(&self) -> bool {
+        self.complete.unwrap_or(false)
+    }
+
+    fn failure_reason(&self) -> Option<&str> {
+        self.failure_reason.as_deref()
+    }
 }
 
 #[cfg(test)]
+#[allow(clippy::field_reassign_with_default)]
 mod test {
     use super::*;
     use crate::proxy::v260::access::Access;
@@ -0,0 +1,205 @@
+//! # Policies Module
+//!
+//! Provides bindings for access policies (`/policies`) and the resources
+//! they can protect (`/resources`).
+//!
+//! NiFi answers `GET /policies/{action}/{resource}` with the *effective*
+//! policy, which may be inherited from a parent resource. `find_policy` only
+//! returns a policy defined on the requested resource itself, which is what
+//! `sync` (see [`sync`]) needs to manage policies as code.
+
+pub mod sync;
+
+use crate::common::client::{HttpClient, HttpClientError, JsonResponse};
+use crate::common::config::Config;
+use crate::proxy::v260::api::{AccessPolicyDtoAction, AccessPolicyEntity, ResourcesEntity};
+use anyhow::bail;
+use reqwest::{StatusCode, Url};
+use std::sync::Arc;
+
+/// A service for managing access policies.
+///
+/// This service is instantiated with shared (`Arc`) instances of `HttpClient` and `Config`.
+#[derive(Debug)]
+pub struct Policies {
+    client: Arc<HttpClient>,
+    config: Arc<Config>,
+}
+
+impl Policies {
+    /// Creates a new instance of the `Policies` service.
+    ///
+    /// # Arguments
+    ///
+    /// * `client` - The shared `HttpClient` to be used for requests.
+    /// * `config` - The application configuration (containing `api_base_url`).
+    pub fn new(client: Arc<HttpClient>, config: Arc<Config>) -> Self {
+        Self { client, config }
+    }
+
+    /// Retrieves the resources that can be protected by a policy.
+    ///
+    /// Sends a `GET` request to `/resources`.
+    ///
+    /// # Errors
+    /// Returns `HttpClientError` if the request fails.
+    pub async fn get_resources(&self) -> anyhow::Result<ResourcesEntity> {
+        let url = format!("{}/resources", self.config.api_base_url);
+        Ok(self.client.get_json::<ResourcesEntity>(&url).await?)
+    }
+
+    /// Retrieves the effective policy for an action on a resource (e.g.
+    /// `/flow` or `/process-groups/{id}`). It may be inherited from a parent
+    /// resource.
+    ///
+    /// Sends a `GET` request to `/policies/{action}/{resource}`.
+    ///
+    /// # Errors
+    /// Returns `HttpClientError` if the request fails (404 when no policy applies).
+    pub async fn get_policy(
+        &self,
+        action: AccessPolicyDtoAction,
+        resource: &str,
+    ) -> anyhow::Result<AccessPolicyEntity> {
+        let url = format!(
+            "{}/policies/{}/{}",
+            self.config.api_base_url,
+            action,
+            resource.trim_start_matches('/')
+        );
+        Ok(self.client.get_json::<AccessPolicyEntity>(&url).await?)
+    }
+
+    /// Retrieves a policy.
+    ///
+    /// Sends a `GET` request to `/policies/{id}`.
+    ///
+    /// # Errors
+    /// Returns `HttpClientError` if the request fails.
+    pub async fn get_policy_by_id(&self, id: &str) -> anyhow::Result<AccessPolicyEntity> {
+        let url = format!("{}/policies/{}", self.config.api_base_url, id);
+        Ok(self.client.get_json::<AccessPolicyEntity>(&url).await?)
+    }
+
+    /// Creates a policy.
+    ///
+    /// Sends a `POST` request to `/policies`.
+    /// The `payload` must contain a revision with version `0`.
+    ///
+    /// # Errors
+    /// Returns `HttpClientError` if the request fails.
+    pub async fn post_policy(
+        &self,
+        payload: &AccessPolicyEntity,
+    ) -> anyhow::Result<AccessPolicyEntity> {
+        let url = format!("{}/policies", self.config.api_base_url);
+        Ok(self
+            .client
+            .post_json::<AccessPolicyEntity, AccessPolicyEntity>(&url, payload)
+            .await?)
+    }
+
+    /// Updates a policy, replacing its users and user groups.
+    ///
+    /// Sends a `PUT` request to `/policies/{id}`.
+    /// The `payload` must contain the current revision.
+    ///
+    /// # Errors
+    /// Returns `HttpClientError` if the request fails (e.g., 409 Conflict on bad version).
+    pub async fn put_policy(
+        &self,
+        id: &str,
+        payload: &AccessPolicyEntity,
+    ) -> anyhow::Result<AccessPolicyEntity> {
+        let url = format!("{}/policies/{}", self.config.api_base_url, id);
+        Ok(self
+            .client
+            .put_json::<AccessPolicyEntity, AccessPolicyEntity>(&url, payload)
+            .await?)
+    }
+
+    /// Deletes a policy at its current revision.
+    ///
+    /// Sends a `DELETE` request to `/policies/{id}`.
+    ///
+    /// # Errors
+    /// Returns `HttpClientError` if the request fails.
+    pub async fn delete_policy(&self, id: &str) -> anyhow::Result<AccessPolicyEntity> {
+        let policy = self.get_policy_by_id(id).await?;
+        let Some(version) = policy.revision.and_then(|revision| revision.version) else {
+            bail!("Revision was None");
+        };
+        let url = Url::parse_with_params(
+            &format!("{}/policies/{}", self.config.api_base_url, id),
+            [("version", version.to_string())],
+        )?;
+        let response = self
+            .client
+            .delete::<JsonResponse<AccessPolicyEntity>>(url.as_str())
+            .await?;
+        Ok(response.0)
+    }
+
+    /// Finds the policy defined on exactly this resource. Returns `None`
+    /// when no policy applies, or when the effective one is inherited.
+    ///
+    /// # Errors
+    /// Returns an error if the request fails for another reason than 404.
+    pub async fn find_policy(
+        &self,
+        action: AccessPolicyDtoAction,
+        resource: &str,
+    ) -> anyhow::Result<Option<AccessPolicyEntity>> {
+        match self.get_policy(action, resource).await {
+            Ok(policy) if policy_resource(&policy) == Some(resource) => Ok(Some(policy)),
+            Ok(_) => Ok(None),
+            Err(err) => match err.downcast::<HttpClientError>() {
+                Ok(HttpClientError::HttpError { status, .. })
+                    if status == StatusCode::NOT_FOUND =>
+                {
+                    Ok(None)
+                },
+                Ok(err) => Err(err.into()),
+                Err(err) => Err(err),
+            },
+        }
+    }
+}
+
+/// The resource a policy is defined on.
+pub fn policy_resource(policy: &AccessPolicyEntity) -> Option<&str> {
+    policy.component.as_ref()?.resource.as_deref()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::proxy::v260::access::Access;
+    use tracing_test::traced_test;
+
+    #[tokio::test]
+    #[traced_test]
+    async fn test_get_resources() {
+        // --- 1. Setup ---
+        let client = Arc::new(HttpClient::new());
+        let config = Arc::new(Config::default());
+        let access = Access::new(client.clone(), config.clone());
+        let _ = access.get_access_token().await;
+
+        // --- 2. Check the flow resource is protectable ---
+        let policies = Policies::new(client.clone(), config.clone());
+        let resources = policies.get_resources().await;
+        assert!(
+            resources.is_ok(),
+            "test_get_resources call error: {:?}",
+            resources
+        );
+        assert!(
+            resources
+                .unwrap()
+                .resources
+                .iter()
+                .any(|resource| resource.identifier.as_deref() == Some("/flow"))
+        );
+    }
+}
@@ -0,0 +1,337 @@
+//! Declarative policy sync.
+//!
+//! `DeclaredPolicies` maps a (resource, action) pair to the users and user
+//! groups, by identity, that should be granted it. `Policies::sync` makes the
+//! live policies match: missing policies are created, and users and groups are
+//! added to or removed from existing ones. Pairs that are not declared are
+//! left alone, and an empty declaration empties the policy without deleting it.
+
+use crate::proxy::v260::api::{
+    AccessPolicyDto, AccessPolicyDtoAction, AccessPolicyEntity, RevisionDto, TenantEntity,
+};
+use crate::proxy::v260::policies::Policies;
+use crate::proxy::v260::tenants::{Tenants, group_identity, user_identity};
+use anyhow::bail;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use tracing::info;
+
+/// The users and user groups, by identity, granted a policy.
+#[derive(Deserialize, Serialize, Debug, Clone, Default, PartialEq)]
+pub struct Principals {
+    #[serde(default)]
+    pub users: BTreeSet<String>,
+    #[serde(default)]
+    pub groups: BTreeSet<String>,
+}
+
+impl Principals {
+    fn of(policy: &AccessPolicyEntity) -> Self {
+        let identities = |tenants: Option<&Vec<TenantEntity>>| {
+            tenants
+                .into_iter()
+                .flatten()
+                .filter_map(|tenant| tenant.component.as_ref()?.identity.clone())
+                .collect()
+        };
+        let component = policy.component.as_ref();
+        Self {
+            users: identities(component.and_then(|c| c.users.as_ref())),
+            groups: identities(component.and_then(|c| c.user_groups.as_ref())),
+        }
+    }
+}
+
+/// One declared policy, as read from a configuration file.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+pub struct PolicyDeclaration {
+    pub resource: String,
+    pub action: AccessPolicyDtoAction,
+    #[serde(flatten)]
+    pub principals: Principals,
+}
+
+/// The desired state of the managed policies.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DeclaredPolicies {
+    pub policies: BTreeMap<(String, AccessPolicyDtoAction), Principals>,
+}
+
+impl DeclaredPolicies {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Declares a policy with no users or groups.
+    pub fn declare(mut self, resource: &str, action: AccessPolicyDtoAction) -> Self {
+        self.entry(resource, action);
+        self
+    }
+
+    pub fn grant_user(mut self, resource: &str, action: AccessPolicyDtoAction, user: &str) -> Self {
+        self.entry(resource, action).users.insert(user.to_string());
+        self
+    }
+
+    pub fn grant_group(
+        mut self,
+        resource: &str,
+        action: AccessPolicyDtoAction,
+        group: &str,
+    ) -> Self {
+        self.entry(resource, action)
+            .groups
+            .insert(group.to_string());
+        self
+    }
+
+    /// Merges declarations; the same pair may appear several times.
+    pub fn from_declarations(declarations: impl IntoIterator<Item = PolicyDeclaration>) -> Self {
+        let mut declared = Self::new();
+        for declaration in declarations {
+            let entry = declared.entry(&declaration.resource, declaration.action);
+            entry.users.extend(declaration.principals.users);
+            entry.groups.extend(declaration.principals.groups);
+        }
+        declared
+    }
+
+    fn entry(&mut self, resource: &str, action: AccessPolicyDtoAction) -> &mut Principals {
+        self.policies
+            .entry((resource.to_string(), action))
+            .or_default()
+    }
+}
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TenantKind {
+    User,
+    UserGroup,
+}
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChangeKind {
+    Added,
+    Removed,
+}
+
+/// A user or group added to or removed from a policy.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct PolicyChange {
+    pub resource: String,
+    pub action: AccessPolicyDtoAction,
+    pub tenant: TenantKind,
+    pub identity: String,
+    pub kind: ChangeKind,
+}
+
+/// What `sync` changed (or would change, in a dry run).
+#[derive(Serialize, Debug, Clone, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct SyncReport {
+    /// Policies that did not exist on the resource itself.
+    pub created: Vec<(String, AccessPolicyDtoAction)>,
+    pub changes: Vec<PolicyChange>,
+}
+
+impl SyncReport {
+    pub fn is_empty(&self) -> bool {
+        self.created.is_empty() && self.changes.is_empty()
+    }
+
+    pub fn additions(&self) -> impl Iterator<Item = &PolicyChange> {
+        self.changes
+            .iter()
+            .filter(|change| change.kind == ChangeKind::Added)
+    }
+
+    pub fn removals(&self) -> impl Iterator<Item = &PolicyChange> {
+        self.changes
+            .iter()
+            .filter(|change| change.kind == ChangeKind::Removed)
+    }
+}
+
+/// The changes that turn `current` into `declared`, users first.
+pub fn plan(
+    resource: &str,
+    action: AccessPolicyDtoAction,
+    declared: &Principals,
+    current: &Principals,
+) -> Vec<PolicyChange> {
+    let change = |tenant, identity: &String, kind| PolicyChange {
+        resource: resource.to_string(),
+        action,
+        tenant,
+        identity: identity.clone(),
+        kind,
+    };
+    let mut changes = Vec::new();
+    for (tenant, declared, current) in [
+        (TenantKind::User, &declared.users, &current.users),
+        (TenantKind::UserGroup, &declared.groups, &current.groups),
+    ] {
+        changes.extend(
+            declared
+                .difference(current)
+                .map(|identity| change(tenant, identity, ChangeKind::Added)),
+        );
+        changes.extend(
+            current
+                .difference(declared)
+                .map(|identity| change(tenant, identity, ChangeKind::Removed)),
+        );
+    }
+    changes
+}
+
+impl Policies {
+    /// Makes the declared policies match `declared`. With `dry_run`, only
+    /// reports what would change.
+    ///
+    /// # Errors
+    /// Returns an error, before changing anything, if a declared user or group
+    /// does not exist; otherwise if a request fails.
+    pub async fn sync(
+        &self,
+        declared: &DeclaredPolicies,
+        dry_run: bool,
+    ) -> anyhow::Result<SyncReport> {
+        let tenants = Tenants::new(self.client.clone(), self.config.clone());
+        let users: BTreeMap<String, String> = tenants
+            .get_users()
+            .await?
+            .users
+            .iter()
+            .filter_map(|user| Some((user_identity(user)?.to_string(), user.id.clone()?)))
+            .collect();
+        let groups: BTreeMap<String, String> = tenants
+            .get_user_groups()
+            .await?
+            .user_groups
+            .iter()
+            .filter_map(|group| Some((group_identity(group)?.to_string(), group.id.clone()?)))
+            .collect();
+
+        let mut unknown = Vec::new();
+        for principals in declared.policies.values() {
+            unknown.extend(principals.users.iter().filter(|u| !users.contains_key(*u)));
+            unknown.extend(
+                principals
+                    .groups
+                    .iter()
+                    .filter(|g| !groups.contains_key(*g)),
+            );
+        }
+        if !unknown.is_empty() {
+            let unknown: BTreeSet<&String> = unknown.into_iter().collect();
+            bail!(
+                "Unknown users or groups: {}",
+                unknown.into_iter().cloned().collect::<Vec<_>>().join(", ")
+            );
+        }
+
+        let mut report = SyncReport::default();
+        for ((resource, action), principals) in &declared.policies {
+            let policy = self.find_policy(*action, resource).await?;
+            let current = policy.as_ref().map(Principals::of).unwrap_or_default();
+            let changes = plan(resource, *action, principals, &current);
+            if changes.is_empty() && policy.is_some() {
+                continue;
+            }
+
+            let tenants_of = |identities: &BTreeSet<String>, ids: &BTreeMap<String, String>| {
+                identities
+                    .iter()
+                    .map(|identity| TenantEntity {
+                        id: ids.get(identity).cloned(),
+                        ..Default::default()
+                    })
+                    .collect::<Vec<_>>()
+            };
+            let component = AccessPolicyDto {
+                resource: Some(resource.clone()),
+                action: Some(*action),
+                users: Some(tenants_of(&principals.users, &users)),
+                user_groups: Some(tenants_of(&principals.groups, &groups)),
+                ..Default::default()
+            };
+
+            match policy {
+                Some(policy) => {
+                    let Some(id) = policy.id.clone() else {
+                        bail!("Policy {} {} has no id", action, resource);
+                    };
+                    if !dry_run {
+                        info!("Updating policy {} {}", action, resource);
+                        let payload = AccessPolicyEntity {
+                            revision: policy.revision,
+                            component: Some(AccessPolicyDto {
+                                id: Some(id.clone()),
+                                ..component
+                            }),
+                            ..Default::default()
+                        };
+                        self.put_policy(&id, &payload).await?;
+                    }
+                },
+                None => {
+                    if !dry_run {
+                        info!("Creating policy {} {}", action, resource);
+                        let payload = AccessPolicyEntity {
+                            revision: Some(RevisionDto {
+                                client_id: None,
+                                last_modifier: None,
+                                version: Some(0),
+                            }),
+                            component: Some(component),
+                            ..Default::default()
+                        };
+                        self.post_policy(&payload).await?;
+                    }
+                    report.created.push((resource.clone(), *action));
+                },
+            }
+            report.changes.extend(changes);
+        }
+        Ok(report)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_plan() {
+        let declared = DeclaredPolicies::from_declarations(
+            serde_json::from_value::<Vec<PolicyDeclaration>>(serde_json::json!([
+                { "resource": "/flow", "action": "read", "users": ["alice", "bob"] },
+                { "resource": "/flow", "action": "read", "groups": ["ops"] },
+            ]))
+            .unwrap(),
+        );
+        let principals = &declared.policies[&("/flow".to_string(), AccessPolicyDtoAction::Read)];
+        let current = Principals {
+            users: BTreeSet::from(["bob".to_string(), "carol".to_string()]),
+            groups: BTreeSet::new(),
+        };
+
+        let changes = plan("/flow", AccessPolicyDtoAction::Read, principals, &current);
+        let summary: Vec<(TenantKind, &str, ChangeKind)> = changes
+            .iter()
+            .map(|change| (change.tenant, change.identity.as_str(), change.kind))
+            .collect();
+
+        assert_eq!(
+            summary,
+            vec![
+                (TenantKind::User, "alice", ChangeKind::Added),
+                (TenantKind::User, "carol", ChangeKind::Removed),
+                (TenantKind::UserGroup, "ops", ChangeKind::Added),
+            ]
+        );
+        assert!(plan("/flow", AccessPolicyDtoAction::Read, principals, principals).is_empty());
+    }
+}
@@ -0,0 +1,1165 @@
+//! # Process Group Module
+//!
+//! Provides high-level bindings for the NiFi "process-groups" and
+//! "flow/process-groups" API endpoints.
+//!
+//! Besides the plain endpoint wrappers, it offers safe, ordered `start_group`
+//! and `stop_group` operations:
+//!
+//! * Starting enables the controller services the group needs, waits for them
+//!   to be `ENABLED`, and then starts every processor and port following the
+//!   connection graph, downstream components first, so nothing is fed into a
+//!   stopped component.
+//! * Stopping stops the sources first, optionally waits until the queues of the
+//!   group are drained, and then stops the rest in topological order.
+//!
+//! Both return a `GroupScheduleResult` listing every component that changed
+//! state and every one that failed to, and report progress through `tracing`.
+//!
+//! `download_process_group` and `replace_process_group` export and restore the
+//! whole definition of a group, the latter through the async replace-request flow.
+//! `import_process_group` creates a new child group from such a definition.
+
+use crate::common::client::{HttpClient, JsonResponse};
+use crate::common::config::Config;
+use crate::common::polling::{
+    PollError, PollOptions, RequestStatus, poll_until, run_async_request,
+};
+use crate::proxy::v260::api::{
+    ActivateControllerServicesEntity, ControllerServiceDtoState, ControllerServiceEntity,
+    ControllerServiceRunStatusEntity, ControllerServiceRunStatusEntityState,
+    ControllerServicesEntity, PortDtoState, PortEntity, PortRunStatusEntity,
+    PortRunStatusEntityState, ProcessGroupDto, ProcessGroupEntity, ProcessGroupFlowEntity,
+    ProcessGroupImportEntity, ProcessGroupReplaceRequestDto, ProcessGroupReplaceRequestEntity,
+    ProcessGroupStatusEntity, ProcessorDtoState, ProcessorEntity, ProcessorRunStatusEntity,
+    ProcessorRunStatusEntityState, RegisteredFlowSnapshot, RevisionDto, ScheduleComponentsEntity,
+};
+use anyhow::bail;
+use reqwest::Url;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use tracing::{info, warn};
+
+/// A service for interacting with NiFi's Process Group endpoints.
+///
+/// This service is instantiated with shared (`Arc`) instances of `HttpClient` and `Config`.
+#[derive(Debug)]
+pub struct ProcessGroup {
+    client: Arc<HttpClient>,
+    config: Arc<Config>,
+}
+
+/// The kinds of component whose state `start_group` and `stop_group` change.
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "camelCase")]
+pub enum ScheduledComponentKind {
+    Processor,
+    InputPort,
+    OutputPort,
+    ControllerService,
+}
+
+/// A component touched by `start_group` or `stop_group`.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ComponentRef {
+    pub id: String,
+    pub name: Option<String>,
+    pub kind: ScheduledComponentKind,
+}
+
+/// A component that could not be moved to the requested state.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ComponentFailure {
+    pub component: ComponentRef,
+    pub reason: String,
+}
+
+/// The outcome of `start_group` or `stop_group`.
+#[derive(Deserialize, Serialize, Debug, Clone, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct GroupScheduleResult {
+    pub group_id: String,
+    /// Components whose state was changed, in the order they were changed.
+    pub changed: Vec<ComponentRef>,
+    /// Components that failed to change state.
+    pub failed: Vec<ComponentFailure>,
+    /// Whether the queues drained before the timeout. `None` when draining
+    /// was not requested.
+    pub queues_drained: Option<bool>,
+}
+
+impl GroupScheduleResult {
+    /// `true` when every component reached the requested state.
+    pub fn is_success(&self) -> bool {
+        self.failed.is_empty() && self.queues_drained != Some(false)
+    }
+}
+
+/// Options for `start_group`.
+#[derive(Debug, Clone)]
+pub struct StartOptions {
+    /// Enable the controller services the group needs before starting components.
+    pub enable_controller_services: bool,
+    /// How long to wait for controller services to become `ENABLED`.
+    pub poll: PollOptions,
+}
+
+impl Default for StartOptions {
+    fn default() -> Self {
+        Self {
+            enable_controller_services: true,
+            poll: PollOptions::default(),
+        }
+    }
+}
+
+/// Options for `stop_group`.
+#[derive(Debug, Clone, Default)]
+pub struct StopOptions {
+    /// After stopping the sources, wait until every queue of the group is empty
+    /// before stopping the remaining components.
+    pub wait_for_empty_queues: bool,
+    /// How long to wait for the queues to drain.
+    pub poll: PollOptions,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum RunState {
+    Running,
+    Stopped,
+    Disabled,
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct Schedulable {
+    pub(crate) component: ComponentRef,
+    /// The `versionedComponentId`, stable across a replace of the group.
+    pub(crate) versioned_id: Option<String>,
+    /// Names of the groups between the walked group and the component,
+    /// joined with `/` (empty directly inside the walked group).
+    pub(crate) group_path: String,
+    pub(crate) revision: Option<RevisionDto>,
+    pub(crate) state: RunState,
+}
+
+/// Every component of a process group and its descendants, flattened.
+#[derive(Debug, Default)]
+pub(crate) struct GroupComponents {
+    pub(crate) group_ids: HashSet<String>,
+    /// Graph nodes, in discovery order. Funnels are nodes without a run state.
+    pub(crate) nodes: Vec<String>,
+    pub(crate) schedulable: HashMap<String, Schedulable>,
+    pub(crate) edges: Vec<(String, String)>,
+}
+
+impl ProcessGroup {
+    /// Creates a new instance of the `ProcessGroup` service.
+    ///
+    /// # Arguments
+    ///
+    /// * `client` - The shared `HttpClient` to be used for requests.
+    /// * `config` - The application configuration (containing `api_base_url`).
+    pub fn new(client: Arc<HttpClient>, config: Arc<Config>) -> Self {
+        Self { client, config }
+    }
+
+    /// Retrieves a Process Group by its ID.
+    ///
+    /// Sends a `GET` request to `/process-groups/{id}`.
+    ///
+    /// # Errors
+    /// Returns `HttpClientError` if the request fails (e.g., 404 Not Found).
+    pub async fn get_process_group(&self, id: &str) -> anyhow::Result<ProcessGroupEntity> {
+        let response = self
+            .client
+            .get_json::<ProcessGroupEntity>(&format!(
+                "{}/process-groups/{}",
+                self.config.api_base_url, id
+            ))
+            .await?;
+        Ok(response)
+    }
+
+    /// Retrieves the flow (direct children and connections) of a Process Group.
+    ///
+    /// Sends a `GET` request to `/flow/process-groups/{id}`.
+    ///
+    /// # Errors
+    /// Returns `HttpClientError` if the request fails.
+    pub async fn get_process_group_flow(&self, id: &str) -> anyhow::Result<ProcessGroupFlowEntity> {
+        let response = self
+            .client
+            .get_json::<ProcessGroupFlowEntity>(&format!(
+                "{}/flow/process-groups/{}",
+                self.config.api_base_url, id
+            ))
+            .await?;
+        Ok(response)
+    }
+
+    /// Retrieves the status (queues, throughput...) of a Process Group.
+    ///
+    /// Sends a `GET` request to `/flow/process-groups/{id}/status`.
+    ///
+    /// # Errors
+    /// Returns `HttpClientError` if the request fails.
+    pub async fn get_process_group_status(
+        &self,
+        id: &str,
+        recursive: bool,
+    ) -> anyhow::Result<ProcessGroupStatusEntity> {
+        let response = self
+            .client
+            .get_json::<ProcessGroupStatusEntity>(&format!(
+                "{}/flow/process-groups/{}/status?recursive={}",
+                self.config.api_base_url, id, recursive
+            ))
+            .await?;
+        Ok(response)
+    }
+
+    /// Retrieves the controller services visible from a Process Group,
+    /// including those of its ancestors and descendants.
+    ///
+    /// Sends a `GET` request to `/flow/process-groups/{id}/controller-services`.
+    ///
+    /// # Errors
+    /// Returns `HttpClientError` if the request fails.
+    pub async fn get_controller_services(
+        &self,
+        id: &str,
+    ) -> anyhow::Result<ControllerServicesEntity> {
+        let response = self
+            .client
+            .get_json::<ControllerServicesEntity>(&format!(
+                "{}/flow/process-groups/{}/controller-services?includeAncestorGroups=true&includeDescendantGroups=true&includeReferencingComponents=true",
+                self.config.api_base_url, id
+            ))
+            .await?;
+        Ok(response)
+    }
+
+    /// Schedules all (or the given) components of a Process Group at once.
+    ///
+    /// Sends a `PUT` request to `/flow/process-groups/{id}`. Prefer
+    /// `start_group`/`stop_group` when order matters.
+    ///
+    /// # Errors
+    /// Returns `HttpClientError` if the request fails.
+    pub async fn put_schedule_components(
+        &self,
+        id: &str,
+        payload: &ScheduleComponentsEntity,
+    ) -> anyhow::Result<ScheduleComponentsEntity> {
+        let response = self
+            .client
+            .put_json::<ScheduleComponentsEntity, ScheduleComponentsEntity>(
+                &format!("{}/flow/process-groups/{}", self.config.api_base_url, id),
+                payload,
+            )
+            .await?;
+        Ok(response)
+    }
+
+    /// Enables or disables all (or the given) controller services of a Process Group.
+    ///
+    /// Sends a `PUT` request to `/flow/process-groups/{id}/controller-services`.
+    ///
+    /// # Errors
+    /// Returns `HttpClientError` if the request fails.
+    pub async fn put_activate_controller_services(
+        &self,
+        id: &str,
+        payload: &ActivateControllerServicesEntity,
+    ) -> anyhow::Result<ActivateControllerServicesEntity> {
+        let response = self
+            .client
+            .put_json::<ActivateControllerServicesEntity, ActivateControllerServicesEntity>(
+                &format!(
+                    "{}/flow/process-groups/{}/controller-services",
+                    self.config.api_base_url, id
+                ),
+                payload,
+            )
+            .await?;
+        Ok(response)
+    }
+
+    /// Changes the run status of a single processor.
+    ///
+    /// Sends a `PUT` request to `/processors/{id}/run-status`.
+    ///
+    /// # Errors
+    /// Returns `HttpClientError` if the request fails (e.g., 409 Conflict on bad version).
+    pub async fn put_processor_run_status(
+        &self,
+        id: &str,
+        payload: &ProcessorRunStatusEntity,
+    ) -> anyhow::Result<ProcessorEntity> {
+        let response = self
+            .client
+            .put_json::<ProcessorRunStatusEntity, ProcessorEntity>(
+                &format!("{}/processors/{}/run-status", self.config.api_base_url, id),
+                payload,
+            )
+            .await?;
+        Ok(response)
+    }
+
+    /// Changes the run status of a single input or output port.
+    ///
+    /// Sends a `PUT` request to `/input-ports/{id}/run-status` or
+    /// `/output-ports/{id}/run-status`.
+    ///
+    /// # Errors
+    /// Returns `HttpClientError` if the request fails.
+    pub async fn put_port_run_status(
+        &self,
+        id: &str,
+        input: bool,
+        payload: &PortRunStatusEntity,
+    ) -> anyhow::Result<PortEntity> {
+        let kind = if input { "input-ports" } else { "output-ports" };
+        let response = self
+            .client
+            .put_json::<PortRunStatusEntity, PortEntity>(
+                &format!("{}/{}/{}/run-status", self.config.api_base_url, kind, id),
+                payload,
+            )
+            .await?;
+        Ok(response)
+    }
+
+    /// Enables or disables a single controller service.
+    ///
+    /// Sends a `PUT` request to `/controller-services/{id}/run-status`.
+    ///
+    /// # Errors
+    /// Returns `HttpClientError` if the request fails.
+    pub async fn put_controller_service_run_status(
+        &self,
+        id: &str,
+        payload: &ControllerServiceRunStatusEntity,
+    ) -> anyhow::Result<ControllerServiceEntity> {
+        let response = self
+            .client
+            .put_json::<ControllerServiceRunStatusEntity, ControllerServiceEntity>(
+                &format!(
+                    "{}/controller-services/{}/run-status",
+                    self.config.api_base_url, id
+                ),
+                payload,
+            )
+            .await?;
+        Ok(response)
+    }
+
+    /// Creates a Process Group inside `parent_id`.
+    ///
+    /// Sends a `POST` request to `/process-groups/{parent_id}/process-groups`.
+    /// The `payload` must carry a revision with version `0`.
+    ///
+    /// # Errors
+    /// Returns `HttpClientError` if the request fails.
+    pub async fn post_process_group(
+        &self,
+        parent_id: &str,
+        payload: &ProcessGroupEntity,
+    ) -> anyhow::Result<ProcessGroupEntity> {
+        let response = self
+            .client
+            .post_json::<ProcessGroupEntity, ProcessGroupEntity>(
+                &format!(
+                    "{}/process-groups/{}/process-groups",
+                    self.config.api_base_url, parent_id
+                ),
+                payload,
+            )
+            .await?;
+        Ok(response)
+    }
+
+    /// Deletes a Process Group at its current revision.
+    ///
+    /// Sends a `DELETE` request to `/process-groups/{id}`. NiFi only deletes
+    /// groups whose components are stopped and whose queues are empty.
+    ///
+    /// # Errors
+    /// Returns `HttpClientError` if the request fails.
+    pub async fn delete_process_group(&self, id: &str) -> anyhow::Result<ProcessGroupEntity> {
+        let group = self.get_process_group(id).await?;
+        let Some(version) = group.revision.and_then(|revision| revision.version) else {
+            bail!("Revision was None");
+        };
+        let url = Url::parse_with_params(
+            &format!("{}/process-groups/{}", self.config.api_base_url, id),
+            [("version", version.to_string())],
+        )?;
+        let response = self
+            .client
+            .delete::<JsonResponse<ProcessGroupEntity>>(url.as_str())
+            .await?;
+        Ok(response.0)
+    }
+
+    /// Creates a new Process Group inside `parent_id` holding the contents of
+    /// `snapshot`.
+    ///
+    /// The group is created empty (named after the snapshot unless `name` is
+    /// given) and then filled through `replace_process_group`. If the replace
+    /// fails, the empty group is deleted again (best effort).
+    ///
+    /// # Errors
+    /// Returns an error if the group cannot be created or the replace fails.
+    pub async fn import_process_group(
+        &self,
+        parent_id: &str,
+        name: Option<&str>,
+        snapshot: &RegisteredFlowSnapshot,
+        poll: PollOptions,
+    ) -> anyhow::Result<ProcessGroupEntity> {
+        let name = name
+            .map(str::to_string)
+            .or_else(|| {
+                snapshot
+                    .flow_contents
+                    .as_ref()
+                    .and_then(|flow| flow.name.clone())
+            })
+            .unwrap_or_else(|| "Imported".to_string());
+        let created = self
+            .post_process_group(
+                parent_id,
+                &ProcessGroupEntity {
+                    component: Some(ProcessGroupDto {
+                        name: Some(name),
+                        ..Default::default()
+                    }),
+                    revision: Some(RevisionDto {
+                        version: Some(0),
+                        ..Default::default()
+                    }),
+                    ..Default::default()
+                },
+            )
+            .await?;
+        let Some(id) = created.id.clone() else {
+            bail!("Created process group has no id");
+        };
+        if let Err(err) = self.replace_process_group(&id, snapshot, poll).await {
+            // Do not leave an empty group behind; the replace error matters more.
+            if let Err(cleanup) = self.delete_process_group(&id).await {
+                warn!("Could not delete process group {}: {}", id, cleanup);
+            }
+            return Err(err);
+        }
+        self.get_process_group(&id).await
+    }
+
+    /// Downloads the flow definition of a Process Group, including the
+    /// controller services it references from outside.
+    ///
+    /// Sends a `GET` request to `/process-groups/{id}/download`.
+    ///
+    /// # Errors
+    /// Returns `HttpClientError` if the request fails.
+    pub async fn download_process_group(&self, id: &str) -> anyhow::Result<RegisteredFlowSnapshot> {
+        let response = self
+            .client
+            .get_json::<RegisteredFlowSnapshot>(&format!(
+                "{}/process-groups/{}/download?includeReferencedServices=true",
+                self.config.api_base_url, id
+            ))
+            .await?;
+        Ok(response)
+    }
+
+    /// Submits a request to replace the contents of a Process Group.
+    ///
+    /// Sends a `POST` request to `/process-groups/{id}/replace-requests`.
+    /// The `payload` must contain the current `processGroupRevision`.
+    ///
+    /// # Errors
+    /// Returns `HttpClientError` if the request fails.
+    pub async fn post_replace_request(
+        &self,
+        id: &str,
+        payload: &ProcessGroupImportEntity,
+    ) -> anyhow::Result<ProcessGroupReplaceRequestEntity> {
+        let response = self
+            .client
+            .post_json::<ProcessGroupImportEntity, ProcessGroupReplaceRequestEntity>(
+                &format!(
+                    "{}/process-groups/{}/replace-requests",
+                    self.config.api_base_url, id
+                ),
+                payload,
+            )
+            .await?;
+        Ok(response)
+    }
+
+    /// Retrieves the progress of a replace request.
+    ///
+    /// Sends a `GET` request to `/process-groups/replace-requests/{id}`.
+    ///
+    /// # Errors
+    /// Returns `HttpClientError` if the request fails.
+    pub async fn get_replace_request(
+        &self,
+        request_id: &str,
+    ) -> anyhow::Result<ProcessGroupReplaceRequestEntity> {
+        let response = self
+            .client
+            .get_json::<ProcessGroupReplaceRequestEntity>(&format!(
+                "{}/process-groups/replace-requests/{}",
+                self.config.api_base_url, request_id
+            ))
+            .await?;
+        Ok(response)
+    }
+
+    /// Deletes a (finished) replace request.
+    ///
+    /// Sends a `DELETE` request to `/process-groups/replace-requests/{id}`.
+    ///
+    /// # Errors
+    /// Returns `HttpClientError` if the request fails.
+    pub async fn delete_replace_request(
+        &self,
+        request_id: &str,
+    ) -> anyhow::Result<ProcessGroupReplaceRequestEntity> {
+        let response = self
+            .client
+            .delete::<JsonResponse<ProcessGroupReplaceRequestEntity>>(&format!(
+                "{}/process-groups/replace-requests/{}",
+                self.config.api_base_url, request_id
+            ))
+            .await?;
+        Ok(response.0)
+    }
+
+    /// Replaces the contents of a Process Group with `snapshot` and waits
+    /// until NiFi finishes.
+    ///
+    /// Runs the whole replace-request flow: reads the current revision, submits
+    /// the request, polls it until it completes and deletes it afterwards.
+    ///
+    /// # Errors
+    /// Returns an error if any request fails, if NiFi reports a failure reason,
+    /// or if the request does not complete within `poll.timeout`.
+    pub async fn replace_process_group(
+        &self,
+        id: &str,
+        snapshot: &RegisteredFlowSnapshot,
+        poll: PollOptions,
+    ) -> anyhow::Result<ProcessGroupReplaceRequestDto> {
+        run_async_request(
+            &format!("Replace request for process group {}", id),
+            poll,
+            async {
+                let group = self.get_process_group(id).await?;
+                let submitted = self
+                    .post_replace_request(
+                        id,
+                        &ProcessGroupImportEntity {
+                            disconnected_node_acknowledged: None,
+                            process_group_revision: group.revision,
+                            versioned_flow_snapshot: Some(snapshot.clone()),
+                        },
+                    )
+                    .await?;
+                Ok(submitted.request.and_then(|request| request.request_id))
+            },
+            |request_id| async move {
+                Ok(self
+                    .get_replace_request(&request_id)
+                    .await?
+                    .request
+                    .unwrap_or_default())
+            },
+            |request_id| async move { self.delete_replace_request(&request_id).await },
+        )
+        .await
+    }
+
+    /// Starts a Process Group and all its descendants, in a safe order.
+    ///
+    /// 1. Enables every controller service the group needs (its own, its
+    ///    descendants', and ancestor services referenced from inside it) and
+    ///    waits until they are `ENABLED`.
+    /// 2. Starts processors and ports following the connection graph,
+    ///    downstream components first. Disabled components are left alone.
+    ///
+    /// A component failing to start does not abort the operation; it is
+    /// reported in `GroupScheduleResult::failed`.
+    ///
+    /// # Errors
+    /// Returns an error only if the group itself cannot be read.
+    pub async fn start_group(
+        &self,
+        id: &str,
+        options: &StartOptions,
+    ) -> anyhow::Result<GroupScheduleResult> {
+        info!("Starting process group {}", id);
+        let components = self.collect_components(id).await?;
+        let mut result = GroupScheduleResult {
+            group_id: id.to_string(),
+            ..Default::default()
+        };
+
+        if options.enable_controller_services {
+            self.enable_controller_services(id, &components.group_ids, options.poll, &mut result)
+                .await?;
+        }
+
+        let waves = schedule_waves(&components.nodes, &components.edges);
+        for wave in waves.iter().rev() {
+            for node in wave {
+                if let Some(schedulable) = components.schedulable.get(node)
+                    && schedulable.state == RunState::Stopped
+                {
+                    self.change_run_state(schedulable, RunState::Running, &mut result)
+                        .await;
+                }
+            }
+        }
+
+        info!(
+            "Process group {} started: {} changed, {} failed",
+            id,
+            result.changed.len(),
+            result.failed.len()
+        );
+        Ok(result)
+    }
+
+    /// Stops a Process Group and all its descendants, in a safe order.
+    ///
+    /// 1. Stops the sources (components nothing is connected to).
+    /// 2. Optionally waits until every queue of the group is empty, letting the
+    ///    downstream components process what is already in flight.
+    /// 3. Stops the remaining components in topological order.
+    ///
+    /// A component failing to stop does not abort the operation; it is
+    /// reported in `GroupScheduleResult::failed`.
+    ///
+    /// # Errors
+    /// Returns an error only if the group itself cannot be read.
+    pub async fn stop_group(
+        &self,
+        id: &str,
+        options: &StopOptions,
+    ) -> anyhow::Result<GroupScheduleResult> {
+        info!("Stopping process group {}", id);
+        let components = self.collect_components(id).await?;
+        let mut result = GroupScheduleResult {
+            group_id: id.to_string(),
+            ..Default::default()
+        };
+
+        let waves = schedule_waves(&components.nodes, &components.edges);
+        for (index, wave) in waves.iter().enumerate() {
+            for node in wave {
+                if let Some(schedulable) = components.schedulable.get(node)
+                    && schedulable.state == RunState::Running
+                {
+                    self.change_run_state(schedulable, RunState::Stopped, &mut result)
+                        .await;
+                }
+            }
+
+            if index == 0 && options.wait_for_empty_queues {
+                info!("Waiting for the queues of process group {} to drain", id);
+                let drained = match self.wait_for_empty_queues(id, options.poll).await {
+                    Ok(()) => true,
+                    Err(PollError::Timeout(timeout)) => {
+                        warn!(
+                            "Queues of process group {} not empty after {:?}",
+                            id, timeout
+                        );
+                        false
+                    },
+                    Err(PollError::Failed(err)) => return Err(err),
+                };
+                result.queues_drained = Some(drained);
+            }
+        }
+
+        info!(
+            "Process group {} stopped: {} changed, {} failed",
+            id,
+            result.changed.len(),
+            result.failed.len()
+        );
+        Ok(result)
+    }
+
+    /// Walks the group and its descendants through `/flow/process-groups/{id}`.
+    pub(crate) async fn collect_components(&self, id: &str) -> anyhow::Result<GroupComponents> {
+        let mut components = GroupComponents::default();
+        let mut pending = vec![(id.to_string(), String::new())];
+
+        while let Some((group_id, group_path)) = pending.pop() {
+            let entity = self.get_process_group_flow(&group_id).await?;
+            let flow = entity
+                .process_group_flow
+                .and_then(|process_group_flow| process_group_flow.flow)
+                .unwrap_or_default();
+            components.group_ids.insert(group_id);
+
+            for processor in flow.processors.into_iter().flatten() {
+                let Some(id) = processor.id else { continue };
+                let component = processor.component.unwrap_or_default();
+                let state = match component.state {
+                    Some(ProcessorDtoState::Running) => RunState::Running,
+                    Some(ProcessorDtoState::Disabled) => RunState::Disabled,
+                    _ => RunState::Stopped,
+                };
+                components.add(Schedulable {
+                    component: ComponentRef {
+                        id,
+                        name: component.name,
+                        kind: ScheduledComponentKind::Processor,
+                    },
+                    versioned_id: component.versioned_component_id,
+                    group_path: group_path.clone(),
+                    revision: processor.revision,
+                    state,
+                });
+            }
+            let ports = flow
+                .input_ports
+                .into_iter()
+                .flatten()
+                .map(|port| (port, ScheduledComponentKind::InputPort))
+                .chain(
+                    flow.output_ports
+                        .into_iter()
+                        .flatten()
+                        .map(|port| (port, ScheduledComponentKind::OutputPort)),
+                );
+            for (port, kind) in ports {
+                let Some(id) = port.id else { continue };
+                let component = port.component.unwrap_or_default();
+                let state = match component.state {
+                    Some(PortDtoState::Running) => RunState::Running,
+                    Some(PortDtoState::Disabled) => RunState::Disabled,
+                    _ => RunState::Stopped,
+                };
+                components.add(Schedulable {
+                    component: ComponentRef {
+                        id,
+                        name: component.name,
+                        kind,
+                    },
+                    versioned_id: component.versioned_component_id,
+                    group_path: group_path.clone(),
+                    revision: port.revision,
+                    state,
+                });
+            }
+            for funnel in flow.funnels.into_iter().flatten() {
+                components.nodes.extend(funnel.id);
+            }
+            for connection in flow.connections.into_iter().flatten() {
+                if let (Some(source), Some(destination)) =
+                    (connection.source_id, connection.destination_id)
+                {
+                    components.edges.push((source, destination));
+                }
+            }
+            for child in flow.process_groups.into_iter().flatten() {
+                let Some(child_id) = child.id else { continue };
+                let name = child
+                    .component
+                    .and_then(|component| component.name)
+                    .unwrap_or_default();
+                let child_path = if group_path.is_empty() {
+                    name
+                } else {
+                    format!("{}/{}", group_path, name)
+                };
+                pending.push((child_id, child_path));
+            }
+        }
+        Ok(components)
+    }
+
+    async fn change_run_state(
+        &self,
+        schedulable: &Schedulable,
+        state: RunState,
+        result: &mut GroupScheduleResult,
+    ) {
+        let component = &schedulable.component;
+        let revision = schedulable.revision.clone();
+        let running = state == RunState::Running;
+        let outcome = match component.kind {
+            ScheduledComponentKind::Processor => self
+                .put_processor_run_status(
+                    &component.id,
+                    &ProcessorRunStatusEntity {
+                        disconnected_node_acknowledged: None,
+                        revision,
+                        state: Some(if running {
+                            ProcessorRunStatusEntityState::Running
+                        } else {
+                            ProcessorRunStatusEntityState::Stopped
+                        }),
+                    },
+                )
+                .await
+                .map(|_| ()),
+            ScheduledComponentKind::InputPort | ScheduledComponentKind::OutputPort => self
+                .put_port_run_status(
+                    &component.id,
+                    component.kind == ScheduledComponentKind::InputPort,
+                    &PortRunStatusEntity {
+                        disconnected_node_acknowledged: None,
+                        revision,
+                        state: Some(if running {
+                            PortRunStatusEntityState::Running
+                        } else {
+                            PortRunStatusEntityState::Stopped
+                        }),
+                    },
+                )
+                .await
+                .map(|_| ()),
+            ScheduledComponentKind::ControllerService => {
+                Err(anyhow::anyhow!("Controller services have no run state"))
+            },
+        };
+
+        match outcome {
+            Ok(()) => {
+                info!(
+                    "{:?} {} ({}) is now {:?}",
+                    component.kind,
+                    component.name.as_deref().unwrap_or("-"),
+                    component.id,
+                    state
+                );
+                result.changed.push(component.clone());
+            },
+            Err(err) => {
+                warn!(
+                    "{:?} {} ({}) failed to change to {:?}: {}",
+                    component.kind,
+                    component.name.as_deref().unwrap_or("-"),
+                    component.id,
+                    state,
+                    err
+                );
+                result.failed.push(ComponentFailure {
+                    component: component.clone(),
+                    reason: err.to_string(),
+                });
+            },
+        }
+    }
+
+    /// Enables the services needed by `group_ids`, retrying in passes so that
+    /// services referencing other services are enabled after their dependencies.
+    async fn enable_controller_services(
+        &self,
+        id: &str,
+        group_ids: &HashSet<String>,
+        poll: PollOptions,
+        result: &mut GroupScheduleResult,
+    ) -> anyhow::Result<()> {
+        let services = self.get_controller_services(id).await?;
+        let mut pending: Vec<ControllerServiceEntity> = services
+            .controller_services
+            .into_iter()
+            .flatten()
+            .filter(|service| is_needed_by(service, group_ids))
+            .filter(|service| {
+                service.component.as_ref().and_then(|c| c.state)
+                    == Some(ControllerServiceDtoState::Disabled)
+            })
+            .collect();
+
+        let mut errors: HashMap<String, String> = HashMap::new();
+        while !pending.is_empty() {
+            let mut requested = Vec::new();
+            let mut remaining = Vec::new();
+            for service in pending {
+                let Some(service_id) = service.id.clone() else {
+                    continue;
+                };
+                let payload = ControllerServiceRunStatusEntity {
+                    disconnected_node_acknowledged: None,
+                    revision: service.revision.clone(),
+                    state: Some(ControllerServiceRunStatusEntityState::Enabled),
+                    ui_only: None,
+                };
+                match self
+                    .put_controller_service_run_status(&service_id, &payload)
+                    .await
+                {
+                    Ok(_) => {
+                        info!("Enabling controller service {}", service_id);
+                        result.changed.push(service_ref(&service));
+                        requested.push(service_id);
+                    },
+                    Err(err) => {
+                        errors.insert(service_id, err.to_string());
+                        remaining.push(service);
+                    },
+                }
+            }
+
+            if requested.is_empty() {
+                for service in &remaining {
+                    let reason = service
+                        .id
+                        .as_ref()
+                        .and_then(|service_id| errors.remove(service_id))
+                        .unwrap_or_else(|| "Could not be enabled".to_string());
+                    warn!(
+                        "Controller service {:?} not enabled: {}",
+                        service.id, reason
+                    );
+                    result.failed.push(ComponentFailure {
+                        component: service_ref(service),
+                        reason,
+                    });
+                }
+                break;
+            }
+            self.wait_until_enabled(id, &requested, poll, result)
+                .await?;
+            pending = remaining;
+        }
+        Ok(())
+    }
+
+    async fn wait_until_enabled(
+        &self,
+        id: &str,
+        service_ids: &[String],
+        poll: PollOptions,
+        result: &mut GroupScheduleResult,
+    ) -> anyhow::Result<()> {
+        let waiting = poll_until(poll, || async {
+            let services = self.get_controller_services(id).await?;
+            let enabled = services
+                .controller_services
+                .iter()
+                .flatten()
+                .filter(|service| {
+                    service.component.as_ref().and_then(|c| c.state)
+                        == Some(ControllerServiceDtoState::Enabled)
+                })
+                .filter_map(|service| service.id.clone())
+                .collect::<HashSet<_>>();
+            let all_enabled = service_ids.iter().all(|id| enabled.contains(id));
+            Ok(all_enabled.then_some(()))
+        })
+        .await;
+
+        match waiting {
+            Ok(()) => Ok(()),
+            Err(PollError::Timeout(timeout)) => {
+                let services = self.get_controller_services(id).await?;
+                for service in services.controller_services.iter().flatten() {
+                    let enabled = service.component.as_ref().and_then(|c| c.state)
+                        == Some(ControllerServiceDtoState::Enabled);
+                    let requested = service
+                        .id
+                        .as_ref()
+                        .is_some_and(|service_id| service_ids.contains(service_id));
+                    if requested && !enabled {
+                        result.failed.push(ComponentFailure {
+                            component: service_ref(service),
+                            reason: format!("Not enabled after {:?}", timeout),
+                        });
+                    }
+                }
+                Ok(())
+            },
+            Err(PollError::Failed(err)) => Err(err),
+        }
+    }
+
+    async fn wait_for_empty_queues(&self, id: &str, poll: PollOptions) -> Result<(), PollError> {
+        poll_until(poll, || async {
+            let status = self.get_process_group_status(id, true).await?;
+            let queued = status
+                .process_group_status
+                .and_then(|status| status.aggregate_snapshot)
+                .and_then(|snapshot| snapshot.flow_files_queued)
+                .unwrap_or(0);
+            Ok((queued == 0).then_some(()))
+        })
+        .await
+    }
+}
+
+impl GroupComponents {
+    fn add(&mut self, schedulable: Schedulable) {
+        self.nodes.push(schedulable.component.id.clone());
+        self.schedulable
+            .insert(schedulable.component.id.clone(), schedulable);
+    }
+}
+
+/// A service is needed by a group when it lives inside it, or when a
+/// component inside it references the service.
+fn is_needed_by(service: &ControllerServiceEntity, group_ids: &HashSet<String>) -> bool {
+    let Some(component) = service.component.as_ref() else {
+        return false;
+    };
+    let inside = component
+        .parent_group_id
+        .as_ref()
+        .is_some_and(|group_id| group_ids.contains(group_id));
+    let referenced = component
+        .referencing_components
+        .iter()
+        .flatten()
+        .filter_map(|reference| reference.component.as_ref())
+        .filter_map(|reference| reference.group_id.as_ref())
+        .any(|group_id| group_ids.contains(group_id));
+    inside || referenced
+}
+
+fn service_ref(service: &ControllerServiceEntity) -> ComponentRef {
+    ComponentRef {
+        id: service.id.clone().unwrap_or_default(),
+        name: service.component.as_ref().and_then(|c| c.name.clone()),
+        kind: ScheduledComponentKind::ControllerService,
+    }
+}
+
+/// Splits `nodes` into waves following `edges`: the first wave holds the
+/// sources, and every node comes after all the nodes feeding it.
+///
+/// Nodes taking part in a cycle cannot be ordered; they are appended as a last
+/// wave, in discovery order. Edges pointing to unknown nodes are ignored.
+fn schedule_waves(nodes: &[String], edges: &[(String, String)]) -> Vec<Vec<String>> {
+    let index: HashMap<&str, usize> = nodes
+        .iter()
+        .enumerate()
+        .map(|(i, node)| (node.as_str(), i))
+        .collect();
+    let mut in_degree = vec![0usize; nodes.len()];
+    let mut successors: Vec<Vec<usize>> = vec![Vec::new(); nodes.len()];
+    let mut seen = HashSet::new();
+    for (source, destination) in edges {
+        let (Some(&s), Some(&d)) = (index.get(source.as_str()), index.get(destination.as_str()))
+        else {
+            continue;
+        };
+        if s != d && seen.insert((s, d)) {
+            successors[s].push(d);
+            in_degree[d] += 1;
+        }
+    }
+
+    let mut waves = Vec::new();
+    let mut placed = vec![false; nodes.len()];
+    let mut current: Vec<usize> = (0..nodes.len()).filter(|&i| in_degree[i] == 0).collect();
+    while !current.is_empty() {
+        let mut next = Vec::new();
+        for &node in &current {
+            placed[node] = true;
+            for &successor in &successors[node] {
+                in_degree[successor] -= 1;
+                if in_degree[successor] == 0 {
+                    next.push(successor);
+                }
+            }
+        }
+        waves.push(current.iter().map(|&i| nodes[i].clone()).collect());
+        current = next;
+    }
+
+    let cyclic: Vec<String> = (0..nodes.len())
+        .filter(|&i| !placed[i])
+        .map(|i| nodes[i].clone())
+        .collect();
+    if !cyclic.is_empty() {
+        waves.push(cyclic);
+    }
+    waves
+}
+
+impl RequestStatus for ProcessGroupReplaceRequestDto {
+    fn is_finished(&self) -> bool {
+        self.complete.unwrap_or(false)
+    }
+
+    fn failure_reason(&self) -> Option<&str> {
+        self.failure_reason.as_deref()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::proxy::v260::access::Access;
+    use tracing_test::traced_test;
+
+    fn ids(values: &[&str]) -> Vec<String> {
+        values.iter().map(|v| v.to_string()).collect()
+    }
+
+    #[test]
+    fn test_schedule_waves_orders_sources_first() {
+        let nodes = ids(&["sink", "middle", "source", "funnel"]);
+        let edges = vec![
+            ("source".to_string(), "funnel".to_string()),
+            ("funnel".to_string(), "middle".to_string()),
+            ("middle".to_string(), "sink".to_string()),
+            ("middle".to_string(), "unknown".to_string()),
+        ];
+        let waves = schedule_waves(&nodes, &edges);
+        assert_eq!(
+            waves,
+            vec![
+                ids(&["source"]),
+                ids(&["funnel"]),
+                ids(&["middle"]),
+                ids(&["sink"])
+            ]
+        );
+    }
+
+    #[test]
+    fn test_schedule_waves_appends_cycles_last() {
+        let nodes = ids(&["source", "a", "b"]);
+        let edges = vec![
+            ("source".to_string(), "a".to_string()),
+            ("a".to_string(), "b".to_string()),
+            ("b".to_string(), "a".to_string()),
+        ];
+        let waves = schedule_waves(&nodes, &edges);
+        assert_eq!(waves, vec![ids(&["source"]), ids(&["a", "b"])]);
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    async fn test_get_process_group_flow() {
+        // --- 1. Setup ---
+        let client = Arc::new(HttpClient::new());
+        let config = Arc::new(Config::default()); // Assumes correct credentials
+        let access = Access::new(client.clone(), config.clone());
+        let _ = access.get_access_token().await;
+
+        // --- 2. Check flow of the root group ---
+        let process_group = ProcessGroup::new(client.clone(), config.clone());
+        let flow = process_group.get_process_group_flow("root").await;
+        assert!(
+            flow.is_ok(),
+            "test_get_process_group_flow call error: {:?}",
+            flow
+        );
+        assert!(flow.unwrap().process_group_flow.is_some());
+    }
+}
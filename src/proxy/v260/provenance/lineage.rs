@@ -0,0 +1,143 @@
+//! Lineage as a graph.
+//!
+//! `LineageResultsDto` holds flat lists of nodes (FlowFiles and events) and
+//! links. `LineageGraph` indexes them by node id so the lineage can be walked
+//! from any node in both directions.
+
+use crate::proxy::v260::api::{LineageResultsDto, ProvenanceNodeDto, ProvenanceNodeDtoType};
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+/// A directed edge between two lineage nodes.
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct LineageEdge {
+    pub source: String,
+    pub target: String,
+    /// The FlowFile travelling along the edge.
+    pub flow_file_uuid: Option<String>,
+}
+
+/// The lineage of a FlowFile.
+#[derive(Serialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct LineageGraph {
+    /// The nodes, by id.
+    pub nodes: BTreeMap<String, ProvenanceNodeDto>,
+    pub edges: Vec<LineageEdge>,
+    /// Errors reported by NiFi while computing the lineage.
+    pub errors: Vec<String>,
+}
+
+impl LineageGraph {
+    pub fn from_results(results: LineageResultsDto) -> Self {
+        let nodes = results
+            .nodes
+            .into_iter()
+            .filter_map(|node| Some((node.id.clone()?, node)))
+            .collect();
+        let edges = results
+            .links
+            .into_iter()
+            .filter_map(|link| {
+                Some(LineageEdge {
+                    source: link.source_id?,
+                    target: link.target_id?,
+                    flow_file_uuid: link.flow_file_uuid,
+                })
+            })
+            .collect();
+        Self {
+            nodes,
+            edges,
+            errors: results.errors.unwrap_or_default(),
+        }
+    }
+
+    /// The nodes with an edge coming from `id`.
+    pub fn children(&self, id: &str) -> Vec<&ProvenanceNodeDto> {
+        self.edges
+            .iter()
+            .filter(|edge| edge.source == id)
+            .filter_map(|edge| self.nodes.get(&edge.target))
+            .collect()
+    }
+
+    /// The nodes with an edge going to `id`.
+    pub fn parents(&self, id: &str) -> Vec<&ProvenanceNodeDto> {
+        self.edges
+            .iter()
+            .filter(|edge| edge.target == id)
+            .filter_map(|edge| self.nodes.get(&edge.source))
+            .collect()
+    }
+
+    /// The nodes without parents, where the lineage starts.
+    pub fn roots(&self) -> Vec<&ProvenanceNodeDto> {
+        self.nodes
+            .iter()
+            .filter(|(id, _)| !self.edges.iter().any(|edge| &edge.target == *id))
+            .map(|(_, node)| node)
+            .collect()
+    }
+
+    /// The event nodes, ordered by time.
+    pub fn events(&self) -> Vec<&ProvenanceNodeDto> {
+        let mut events: Vec<_> = self
+            .nodes
+            .values()
+            .filter(|node| node.type_ == Some(ProvenanceNodeDtoType::Event))
+            .collect();
+        events.sort_by_key(|node| node.millis);
+        events
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::proxy::v260::api::ProvenanceLinkDto;
+
+    fn node(id: &str, type_: ProvenanceNodeDtoType, millis: i64) -> ProvenanceNodeDto {
+        ProvenanceNodeDto {
+            id: Some(id.to_string()),
+            type_: Some(type_),
+            millis: Some(millis),
+            ..Default::default()
+        }
+    }
+
+    fn link(source: &str, target: &str) -> ProvenanceLinkDto {
+        ProvenanceLinkDto {
+            source_id: Some(source.to_string()),
+            target_id: Some(target.to_string()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_lineage_graph() {
+        let results = LineageResultsDto {
+            errors: None,
+            nodes: vec![
+                node("ff", ProvenanceNodeDtoType::Flowfile, 0),
+                node("2", ProvenanceNodeDtoType::Event, 20),
+                node("1", ProvenanceNodeDtoType::Event, 10),
+            ],
+            links: vec![link("ff", "1"), link("1", "2")],
+        };
+
+        let graph = LineageGraph::from_results(results);
+
+        assert_eq!(graph.roots().len(), 1);
+        assert_eq!(graph.roots()[0].id.as_deref(), Some("ff"));
+        assert_eq!(graph.children("1")[0].id.as_deref(), Some("2"));
+        assert_eq!(graph.parents("1")[0].id.as_deref(), Some("ff"));
+        let events: Vec<_> = graph
+            .events()
+            .iter()
+            .map(|n| n.id.clone().unwrap())
+            .collect();
+        assert_eq!(events, vec!["1", "2"]);
+    }
+}
@@ -0,0 +1,510 @@
+//! # Provenance Module
+//!
+//! Provides high-level bindings for the provenance repository:
+//!
+//! * `/provenance` - asynchronous provenance queries, built from a typed
+//!   `query::ProvenanceQuery` and wrapped by `search`.
+//! * `/provenance/lineage` - asynchronous lineage computations, wrapped by
+//!   `lineage` and returned as a `lineage::LineageGraph`.
+//! * `/provenance/search-options` - the fields that can be searched.
+//! * `/provenance-events` - single events and the latest events of a component,
+//!   their input and output content as a byte stream, and replays (see `replay`).
+
+use crate::common::client::{ByteStream, HttpClient, HttpClientError, JsonResponse};
+use crate::common::config::Config;
+use crate::common::polling::{PollOptions, RequestStatus, run_async_request, with_node};
+use crate::proxy::v260::api::{
+    LatestProvenanceEventsEntity, LineageDto, LineageEntity, ProvenanceDto, ProvenanceEntity,
+    ProvenanceEventDto, ProvenanceEventEntity, ProvenanceOptionsEntity,
+    ReplayLastEventRequestEntity, ReplayLastEventRequestEntityNodes, ReplayLastEventResponseEntity,
+    SubmitReplayRequestEntity,
+};
+use crate::proxy::v260::provenance::lineage::LineageGraph;
+use crate::proxy::v260::provenance::query::{LineageQuery, ProvenanceQuery};
+use crate::proxy::v260::provenance::replay::{LatestReplay, ReplayOutcome};
+use reqwest::StatusCode;
+use std::sync::Arc;
+
+pub mod lineage;
+pub mod query;
+pub mod replay;
+
+/// Which content of a provenance event to download.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ContentDirection {
+    /// The content before the event.
+    Input,
+    /// The content after the event.
+    Output,
+}
+
+impl ContentDirection {
+    fn path(self) -> &'static str {
+        match self {
+            ContentDirection::Input => "input",
+            ContentDirection::Output => "output",
+        }
+    }
+}
+
+/// The events returned by a finished provenance query.
+#[derive(Debug, Clone, Default)]
+pub struct ProvenanceResults {
+    /// The matching events, most recent first.
+    pub events: Vec<ProvenanceEventDto>,
+    /// The number of matching events, which may exceed `events.len()` when
+    /// the query hit `max_results`.
+    pub total_count: i64,
+    /// Errors reported by NiFi while searching.
+    pub errors: Vec<String>,
+}
+
+impl ProvenanceResults {
+    /// Iterates over the events in pages of `size` events.
+    ///
+    /// # Panics
+    /// Panics if `size` is 0.
+    pub fn pages(&self, size: usize) -> std::slice::Chunks<'_, ProvenanceEventDto> {
+        self.events.chunks(size)
+    }
+
+    /// `true` when NiFi found more events than it returned.
+    pub fn is_truncated(&self) -> bool {
+        self.total_count > self.events.len() as i64
+    }
+}
+
+/// A service for querying provenance events and lineage.
+///
+/// This service is instantiated with shared (`Arc`) instances of `HttpClient` and `Config`.
+#[derive(Debug)]
+pub struct Provenance {
+    client: Arc<HttpClient>,
+    config: Arc<Config>,
+}
+
+impl Provenance {
+    /// Creates a new instance of the `Provenance` service.
+    ///
+    /// # Arguments
+    ///
+    /// * `client` - The shared `HttpClient` to be used for requests.
+    /// * `config` - The application configuration (containing `api_base_url`).
+    pub fn new(client: Arc<HttpClient>, config: Arc<Config>) -> Self {
+        Self { client, config }
+    }
+
+    /// Retrieves the searchable fields of provenance queries.
+    ///
+    /// Sends a `GET` request to `/provenance/search-options`.
+    ///
+    /// # Errors
+    /// Returns `HttpClientError` if the request fails.
+    pub async fn get_search_options(&self) -> anyhow::Result<ProvenanceOptionsEntity> {
+        let url = format!("{}/provenance/search-options", self.config.api_base_url);
+        Ok(self
+            .client
+            .get_json::<ProvenanceOptionsEntity>(&url)
+            .await?)
+    }
+
+    /// Submits a provenance query.
+    ///
+    /// Sends a `POST` request to `/provenance`.
+    ///
+    /// # Errors
+    /// Returns `HttpClientError` if the request fails.
+    pub async fn post_query(&self, payload: &ProvenanceEntity) -> anyhow::Result<ProvenanceEntity> {
+        let url = format!("{}/provenance", self.config.api_base_url);
+        Ok(self
+            .client
+            .post_json::<ProvenanceEntity, ProvenanceEntity>(&url, payload)
+            .await?)
+    }
+
+    /// Retrieves the state and results of a provenance query.
+    ///
+    /// Sends a `GET` request to `/provenance/{id}`.
+    ///
+    /// # Errors
+    /// Returns `HttpClientError` if the request fails.
+    pub async fn get_query(
+        &self,
+        id: &str,
+        cluster_node_id: Option<&str>,
+    ) -> anyhow::Result<ProvenanceEntity> {
+        let url = with_node(
+            format!("{}/provenance/{}", self.config.api_base_url, id),
+            cluster_node_id,
+        )?;
+        Ok(self.client.get_json::<ProvenanceEntity>(&url).await?)
+    }
+
+    /// Deletes a provenance query.
+    ///
+    /// Sends a `DELETE` request to `/provenance/{id}`.
+    ///
+    /// # Errors
+    /// Returns `HttpClientError` if the request fails.
+    pub async fn delete_query(
+        &self,
+        id: &str,
+        cluster_node_id: Option<&str>,
+    ) -> anyhow::Result<ProvenanceEntity> {
+        let url = with_node(
+            format!("{}/provenance/{}", self.config.api_base_url, id),
+            cluster_node_id,
+        )?;
+        let response = self
+            .client
+            .delete::<JsonResponse<ProvenanceEntity>>(&url)
+            .await?;
+        Ok(response.0)
+    }
+
+    /// Runs a provenance query to completion.
+    ///
+    /// Submits the query, polls it until NiFi reports it finished and deletes
+    /// it afterwards, even when polling failed.
+    ///
+    /// # Errors
+    /// Returns an error if the query has two terms on the same field, a
+    /// request fails, the query does not finish within `poll.timeout`, or
+    /// NiFi returns no query id.
+    pub async fn search(
+        &self,
+        query: &ProvenanceQuery,
+        poll: PollOptions,
+    ) -> anyhow::Result<ProvenanceResults> {
+        let payload = ProvenanceEntity {
+            provenance: Some(ProvenanceDto {
+                request: Some(query.to_request()?),
+                ..Default::default()
+            }),
+        };
+        let node = query.cluster_node_id.as_deref();
+        let provenance: ProvenanceDto = run_async_request(
+            "Provenance query",
+            poll,
+            async {
+                let submitted = self.post_query(&payload).await?;
+                Ok(submitted.provenance.and_then(|provenance| provenance.id))
+            },
+            |id| async move {
+                Ok(self
+                    .get_query(&id, node)
+                    .await?
+                    .provenance
+                    .unwrap_or_default())
+            },
+            |id| async move { self.delete_query(&id, node).await },
+        )
+        .await?;
+
+        let results = provenance.results.unwrap_or_default();
+        Ok(ProvenanceResults {
+            total_count: results
+                .total_count
+                .unwrap_or(results.provenance_events.len() as i64),
+            events: results.provenance_events,
+            errors: results.errors.unwrap_or_default(),
+        })
+    }
+
+    /// Submits a lineage computation.
+    ///
+    /// Sends a `POST` request to `/provenance/lineage`.
+    ///
+    /// # Errors
+    /// Returns `HttpClientError` if the request fails.
+    pub async fn post_lineage(&self, payload: &LineageEntity) -> anyhow::Result<LineageEntity> {
+        let url = format!("{}/provenance/lineage", self.config.api_base_url);
+        Ok(self
+            .client
+            .post_json::<LineageEntity, LineageEntity>(&url, payload)
+            .await?)
+    }
+
+    /// Retrieves the state and results of a lineage computation.
+    ///
+    /// Sends a `GET` request to `/provenance/lineage/{id}`.
+    ///
+    /// # Errors
+    /// Returns `HttpClientError` if the request fails.
+    pub async fn get_lineage(
+        &self,
+        id: &str,
+        cluster_node_id: Option<&str>,
+    ) -> anyhow::Result<LineageEntity> {
+        let url = with_node(
+            format!("{}/provenance/lineage/{}", self.config.api_base_url, id),
+            cluster_node_id,
+        )?;
+        Ok(self.client.get_json::<LineageEntity>(&url).await?)
+    }
+
+    /// Deletes a lineage computation.
+    ///
+    /// Sends a `DELETE` request to `/provenance/lineage/{id}`.
+    ///
+    /// # Errors
+    /// Returns `HttpClientError` if the request fails.
+    pub async fn delete_lineage(
+        &self,
+        id: &str,
+        cluster_node_id: Option<&str>,
+    ) -> anyhow::Result<LineageEntity> {
+        let url = with_node(
+            format!("{}/provenance/lineage/{}", self.config.api_base_url, id),
+            cluster_node_id,
+        )?;
+        let response = self
+            .client
+            .delete::<JsonResponse<LineageEntity>>(&url)
+            .await?;
+        Ok(response.0)
+    }
+
+    /// Computes a lineage to completion and returns it as a graph.
+    ///
+    /// Follows the same submit, poll and delete cycle as `search`.
+    ///
+    /// # Errors
+    /// Returns an error if a request fails, the computation does not finish
+    /// within `poll.timeout`, or NiFi returns no lineage id.
+    pub async fn lineage(
+        &self,
+        query: &LineageQuery,
+        cluster_node_id: Option<&str>,
+        poll: PollOptions,
+    ) -> anyhow::Result<LineageGraph> {
+        let payload = LineageEntity {
+            lineage: Some(LineageDto {
+                request: Some(query.to_request(cluster_node_id.map(str::to_string))),
+                ..Default::default()
+            }),
+        };
+        let lineage: LineageDto = run_async_request(
+            "Lineage request",
+            poll,
+            async {
+                let submitted = self.post_lineage(&payload).await?;
+                Ok(submitted.lineage.and_then(|lineage| lineage.id))
+            },
+            |id| async move {
+                Ok(self
+                    .get_lineage(&id, cluster_node_id)
+                    .await?
+                    .lineage
+                    .unwrap_or_default())
+            },
+            |id| async move { self.delete_lineage(&id, cluster_node_id).await },
+        )
+        .await?;
+
+        Ok(LineageGraph::from_results(
+            lineage.results.unwrap_or_default(),
+        ))
+    }
+
+    /// Retrieves a single provenance event.
+    ///
+    /// Sends a `GET` request to `/provenance-events/{id}`.
+    ///
+    /// # Errors
+    /// Returns `HttpClientError` if the request fails.
+    pub async fn get_event(
+        &self,
+        id: i64,
+        cluster_node_id: Option<&str>,
+    ) -> anyhow::Result<ProvenanceEventEntity> {
+        let url = with_node(
+            format!("{}/provenance-events/{}", self.config.api_base_url, id),
+            cluster_node_id,
+        )?;
+        Ok(self.client.get_json::<ProvenanceEventEntity>(&url).await?)
+    }
+
+    /// Retrieves the latest provenance events of a component.
+    ///
+    /// Sends a `GET` request to `/provenance-events/latest/{componentId}`.
+    ///
+    /// # Errors
+    /// Returns `HttpClientError` if the request fails.
+    pub async fn get_latest_events(
+        &self,
+        component_id: &str,
+        limit: Option<u32>,
+    ) -> anyhow::Result<LatestProvenanceEventsEntity> {
+        let mut url = format!(
+            "{}/provenance-events/latest/{}",
+            self.config.api_base_url, component_id
+        );
+        if let Some(limit) = limit {
+            url = format!("{}?limit={}", url, limit);
+        }
+        Ok(self
+            .client
+            .get_json::<LatestProvenanceEventsEntity>(&url)
+            .await?)
+    }
+
+    /// Downloads the input or output content of a provenance event.
+    ///
+    /// Sends a `GET` request to `/provenance-events/{id}/content/{input|output}`
+    /// and returns the body as a stream of chunks, so large payloads are not
+    /// buffered in memory.
+    ///
+    /// # Errors
+    /// Returns `HttpClientError` if the request fails (e.g. the content is no
+    /// longer available).
+    pub async fn get_content(
+        &self,
+        id: i64,
+        direction: ContentDirection,
+        cluster_node_id: Option<&str>,
+    ) -> anyhow::Result<ByteStream> {
+        let url = with_node(
+            format!(
+                "{}/provenance-events/{}/content/{}",
+                self.config.api_base_url,
+                id,
+                direction.path()
+            ),
+            cluster_node_id,
+        )?;
+        Ok(self.client.get_stream(&url).await?)
+    }
+
+    /// Replays the content of a provenance event.
+    ///
+    /// Sends a `POST` request to `/provenance-events/replays`.
+    ///
+    /// # Errors
+    /// Returns `HttpClientError` if the request fails.
+    pub async fn post_replay(
+        &self,
+        payload: &SubmitReplayRequestEntity,
+    ) -> anyhow::Result<ProvenanceEventEntity> {
+        let url = format!("{}/provenance-events/replays", self.config.api_base_url);
+        Ok(self
+            .client
+            .post_json::<SubmitReplayRequestEntity, ProvenanceEventEntity>(&url, payload)
+            .await?)
+    }
+
+    /// Replays the content of a provenance event, as a typed outcome.
+    ///
+    /// NiFi refusing the replay (`400`, `404` or `409`, e.g. because the
+    /// content is gone) is reported as `ReplayOutcome::Failed`.
+    ///
+    /// # Errors
+    /// Returns an error if the request fails for any other reason, including
+    /// `401` and `403`.
+    pub async fn replay_event(
+        &self,
+        event_id: i64,
+        cluster_node_id: Option<&str>,
+    ) -> anyhow::Result<ReplayOutcome> {
+        let payload = SubmitReplayRequestEntity {
+            cluster_node_id: cluster_node_id.map(str::to_string),
+            event_id: Some(event_id),
+        };
+        match self.post_replay(&payload).await {
+            Ok(entity) => Ok(ReplayOutcome::Replayed {
+                event_ids: entity
+                    .provenance_event
+                    .and_then(|event| event.event_id)
+                    .into_iter()
+                    .collect(),
+            }),
+            Err(err) => match err.downcast::<HttpClientError>() {
+                Ok(HttpClientError::HttpError {
+                    status: StatusCode::BAD_REQUEST | StatusCode::NOT_FOUND | StatusCode::CONFLICT,
+                    message,
+                }) => Ok(ReplayOutcome::Failed { reason: message }),
+                Ok(err) => Err(err.into()),
+                Err(err) => Err(err),
+            },
+        }
+    }
+
+    /// Replays the latest event of a component.
+    ///
+    /// Sends a `POST` request to `/provenance-events/latest/replays`.
+    ///
+    /// # Errors
+    /// Returns `HttpClientError` if the request fails.
+    pub async fn post_latest_replay(
+        &self,
+        payload: &ReplayLastEventRequestEntity,
+    ) -> anyhow::Result<ReplayLastEventResponseEntity> {
+        let url = format!(
+            "{}/provenance-events/latest/replays",
+            self.config.api_base_url
+        );
+        Ok(self
+            .client
+            .post_json::<ReplayLastEventRequestEntity, ReplayLastEventResponseEntity>(&url, payload)
+            .await?)
+    }
+
+    /// Replays the latest event of a component on all nodes (or only on the
+    /// primary node), as a typed outcome per node.
+    ///
+    /// # Errors
+    /// Returns `HttpClientError` if the request fails.
+    pub async fn replay_latest(
+        &self,
+        component_id: &str,
+        nodes: ReplayLastEventRequestEntityNodes,
+    ) -> anyhow::Result<LatestReplay> {
+        let payload = ReplayLastEventRequestEntity {
+            component_id: Some(component_id.to_string()),
+            nodes: Some(nodes),
+        };
+        let response = self.post_latest_replay(&payload).await?;
+        Ok(LatestReplay::from_response(&response))
+    }
+}
+
+impl RequestStatus for ProvenanceDto {
+    fn is_finished(&self) -> bool {
+        self.finished.unwrap_or(false)
+    }
+}
+
+impl RequestStatus for LineageDto {
+    fn is_finished(&self) -> bool {
+        self.finished.unwrap_or(false)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::proxy::v260::access::Access;
+    use tracing_test::traced_test;
+
+    #[tokio::test]
+    #[traced_test]
+    async fn test_get_search_options() {
+        // --- 1. Setup ---
+        let client = Arc::new(HttpClient::new());
+        let config = Arc::new(Config::default());
+        let access = Access::new(client.clone(), config.clone());
+        let _ = access.get_access_token().await;
+        let provenance = Provenance::new(client, config);
+
+        // --- 2. Execution ---
+        let options = provenance.get_search_options().await.unwrap();
+
+        // --- 3. Verification ---
+        let fields = options.provenance_options.unwrap().searchable_fields;
+        assert!(
+            fields
+                .iter()
+                .any(|field| field.id.as_deref() == Some("EventType"))
+        );
+    }
+}
@@ -0,0 +1,263 @@
+//! Typed provenance queries.
+//!
+//! `ProvenanceRequestDto::search_terms` is a free-form map keyed by the ids of
+//! the searchable fields (see `/provenance/search-options`). `ProvenanceQuery`
+//! builds it from typed `SearchTerm`s, and formats the time range the way NiFi
+//! expects it (`MM/dd/yyyy HH:mm:ss z`).
+
+use crate::proxy::v260::api::{
+    LineageRequestDto, LineageRequestDtoLineageRequestType, ProvenanceRequestDto,
+    ProvenanceSearchValueDto,
+};
+use anyhow::bail;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+
+/// The date format of `ProvenanceRequestDto::start_date` and `end_date`.
+const DATE_FORMAT: &str = "%m/%d/%Y %H:%M:%S UTC";
+
+/// The type of a provenance event, as reported in `ProvenanceEventDto::event_type`.
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum EventType {
+    Create,
+    Receive,
+    Fetch,
+    Send,
+    Upload,
+    RemoteInvocation,
+    Download,
+    Drop,
+    Expire,
+    Fork,
+    Join,
+    Clone,
+    ContentModified,
+    AttributesModified,
+    Route,
+    #[serde(rename = "ADDINFO")]
+    AddInfo,
+    Replay,
+    Unknown,
+}
+
+impl EventType {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            EventType::Create => "CREATE",
+            EventType::Receive => "RECEIVE",
+            EventType::Fetch => "FETCH",
+            EventType::Send => "SEND",
+            EventType::Upload => "UPLOAD",
+            EventType::RemoteInvocation => "REMOTE_INVOCATION",
+            EventType::Download => "DOWNLOAD",
+            EventType::Drop => "DROP",
+            EventType::Expire => "EXPIRE",
+            EventType::Fork => "FORK",
+            EventType::Join => "JOIN",
+            EventType::Clone => "CLONE",
+            EventType::ContentModified => "CONTENT_MODIFIED",
+            EventType::AttributesModified => "ATTRIBUTES_MODIFIED",
+            EventType::Route => "ROUTE",
+            EventType::AddInfo => "ADDINFO",
+            EventType::Replay => "REPLAY",
+            EventType::Unknown => "UNKNOWN",
+        }
+    }
+}
+
+impl fmt::Display for EventType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// A single search criterion.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SearchTerm {
+    /// Events generated by the component with this id.
+    ComponentId(String),
+    /// Events about the FlowFile with this UUID.
+    FlowFileUuid(String),
+    EventType(EventType),
+    Filename(String),
+    Relationship(String),
+    TransitUri(String),
+    /// Any other searchable field, by its id in `/provenance/search-options`.
+    Other(String, String),
+}
+
+impl SearchTerm {
+    /// The searchable field id and the searched value.
+    fn key_value(&self) -> (&str, String) {
+        match self {
+            SearchTerm::ComponentId(id) => ("ProcessorID", id.clone()),
+            SearchTerm::FlowFileUuid(uuid) => ("FlowFileUUID", uuid.clone()),
+            SearchTerm::EventType(event_type) => ("EventType", event_type.to_string()),
+            SearchTerm::Filename(name) => ("Filename", name.clone()),
+            SearchTerm::Relationship(name) => ("Relationship", name.clone()),
+            SearchTerm::TransitUri(uri) => ("TransitURI", uri.clone()),
+            SearchTerm::Other(field, value) => (field.as_str(), value.clone()),
+        }
+    }
+}
+
+/// A provenance search.
+#[derive(Debug, Clone, Default)]
+pub struct ProvenanceQuery {
+    /// Terms that must match.
+    pub terms: Vec<SearchTerm>,
+    /// Terms that must not match.
+    pub excluded: Vec<SearchTerm>,
+    pub start: Option<DateTime<Utc>>,
+    pub end: Option<DateTime<Utc>>,
+    /// Defaults to NiFi's own limit (1000) when unset.
+    pub max_results: Option<i32>,
+    /// Only query this node of a cluster.
+    pub cluster_node_id: Option<String>,
+}
+
+impl ProvenanceQuery {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn term(mut self, term: SearchTerm) -> Self {
+        self.terms.push(term);
+        self
+    }
+
+    pub fn exclude(mut self, term: SearchTerm) -> Self {
+        self.excluded.push(term);
+        self
+    }
+
+    pub fn between(mut self, start: DateTime<Utc>, end: DateTime<Utc>) -> Self {
+        self.start = Some(start);
+        self.end = Some(end);
+        self
+    }
+
+    pub fn max_results(mut self, max_results: i32) -> Self {
+        self.max_results = Some(max_results);
+        self
+    }
+
+    /// Builds the request body of `POST /provenance`.
+    ///
+    /// # Errors
+    /// NiFi takes a single value per searchable field, so two terms (included
+    /// or excluded) on the same field are rejected rather than one silently
+    /// replacing the other.
+    pub fn to_request(&self) -> anyhow::Result<ProvenanceRequestDto> {
+        let included = self.terms.iter().map(|term| (term, false));
+        let excluded = self.excluded.iter().map(|term| (term, true));
+        let mut search_terms = HashMap::new();
+        for (term, inverse) in included.chain(excluded) {
+            let (key, value) = term.key_value();
+            let search_value = ProvenanceSearchValueDto {
+                inverse: Some(inverse),
+                value: Some(value),
+            };
+            if search_terms.insert(key.to_string(), search_value).is_some() {
+                bail!("Provenance query has more than one term on {}", key);
+            }
+        }
+        Ok(ProvenanceRequestDto {
+            cluster_node_id: self.cluster_node_id.clone(),
+            start_date: self.start.map(|date| date.format(DATE_FORMAT).to_string()),
+            end_date: self.end.map(|date| date.format(DATE_FORMAT).to_string()),
+            max_results: self.max_results,
+            search_terms,
+            ..Default::default()
+        })
+    }
+}
+
+/// What a lineage is computed from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LineageQuery {
+    /// The full lineage of a FlowFile.
+    FlowFile(String),
+    /// The parents of the FlowFile of an event (expanding a node upwards).
+    Parents(i64),
+    /// The children of the FlowFile of an event (expanding a node downwards).
+    Children(i64),
+}
+
+impl LineageQuery {
+    /// Builds the request body of `POST /provenance/lineage`.
+    pub fn to_request(&self, cluster_node_id: Option<String>) -> LineageRequestDto {
+        let (lineage_request_type, event_id, uuid) = match self {
+            LineageQuery::FlowFile(uuid) => (
+                LineageRequestDtoLineageRequestType::Flowfile,
+                None,
+                Some(uuid.clone()),
+            ),
+            LineageQuery::Parents(event_id) => (
+                LineageRequestDtoLineageRequestType::Parents,
+                Some(*event_id),
+                None,
+            ),
+            LineageQuery::Children(event_id) => (
+                LineageRequestDtoLineageRequestType::Children,
+                Some(*event_id),
+                None,
+            ),
+        };
+        LineageRequestDto {
+            cluster_node_id,
+            event_id,
+            lineage_request_type: Some(lineage_request_type),
+            uuid,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_query_to_request() {
+        let query = ProvenanceQuery::new()
+            .term(SearchTerm::ComponentId("p1".to_string()))
+            .exclude(SearchTerm::EventType(EventType::Drop))
+            .between(
+                Utc.with_ymd_and_hms(2025, 1, 2, 3, 4, 5).unwrap(),
+                Utc.with_ymd_and_hms(2025, 1, 3, 0, 0, 0).unwrap(),
+            )
+            .max_results(100);
+
+        let request = query.to_request().unwrap();
+
+        assert_eq!(
+            request.start_date.as_deref(),
+            Some("01/02/2025 03:04:05 UTC")
+        );
+        assert_eq!(request.end_date.as_deref(), Some("01/03/2025 00:00:00 UTC"));
+        assert_eq!(request.max_results, Some(100));
+        assert_eq!(
+            request.search_terms["ProcessorID"].value.as_deref(),
+            Some("p1")
+        );
+        assert_eq!(request.search_terms["ProcessorID"].inverse, Some(false));
+        assert_eq!(
+            request.search_terms["EventType"].value.as_deref(),
+            Some("DROP")
+        );
+        assert_eq!(request.search_terms["EventType"].inverse, Some(true));
+    }
+
+    #[test]
+    fn test_duplicate_terms_are_rejected() {
+        let query = ProvenanceQuery::new()
+            .term(SearchTerm::EventType(EventType::Send))
+            .exclude(SearchTerm::EventType(EventType::Drop));
+
+        assert!(query.to_request().is_err());
+    }
+}
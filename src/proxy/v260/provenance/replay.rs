@@ -0,0 +1,144 @@
+//! Typed outcomes of provenance replays.
+//!
+//! `/provenance-events/replays` answers with the new `REPLAY` event, while
+//! `/provenance-events/latest/replays` answers with one snapshot per node
+//! plus an aggregate. Both are mapped to a `ReplayOutcome`.
+
+use crate::proxy::v260::api::{ReplayLastEventResponseEntity, ReplayLastEventSnapshotDto};
+use serde::Serialize;
+
+/// The result of a replay on one node (or on the whole cluster).
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase", tag = "outcome")]
+pub enum ReplayOutcome {
+    /// The content was replayed; these are the ids of the new `REPLAY` events.
+    Replayed { event_ids: Vec<i64> },
+    /// The component has no event that could be replayed.
+    NoEvent,
+    /// NiFi refused or failed to replay the event.
+    Failed { reason: String },
+}
+
+impl ReplayOutcome {
+    pub fn from_snapshot(snapshot: &ReplayLastEventSnapshotDto) -> Self {
+        if let Some(reason) = &snapshot.failure_explanation {
+            ReplayOutcome::Failed {
+                reason: reason.clone(),
+            }
+        } else if snapshot.event_available == Some(false) || snapshot.events_replayed.is_empty() {
+            ReplayOutcome::NoEvent
+        } else {
+            ReplayOutcome::Replayed {
+                event_ids: snapshot.events_replayed.clone(),
+            }
+        }
+    }
+
+    pub fn is_replayed(&self) -> bool {
+        matches!(self, ReplayOutcome::Replayed { .. })
+    }
+}
+
+/// The outcome of a replay on one node of a cluster.
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct NodeReplay {
+    pub node_id: Option<String>,
+    pub address: Option<String>,
+    pub outcome: ReplayOutcome,
+}
+
+/// The outcome of replaying the latest event of a component.
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct LatestReplay {
+    pub component_id: Option<String>,
+    /// The outcome across all nodes.
+    pub outcome: ReplayOutcome,
+    /// The outcome per node; empty on a standalone instance.
+    pub nodes: Vec<NodeReplay>,
+}
+
+impl LatestReplay {
+    pub fn from_response(response: &ReplayLastEventResponseEntity) -> Self {
+        let outcome = match &response.aggregate_snapshot {
+            Some(snapshot) => ReplayOutcome::from_snapshot(snapshot),
+            None => ReplayOutcome::NoEvent,
+        };
+        let nodes = response
+            .node_snapshots
+            .iter()
+            .map(|node| NodeReplay {
+                node_id: node.node_id.clone(),
+                address: node.address.clone(),
+                outcome: node
+                    .snapshot
+                    .as_ref()
+                    .map(ReplayOutcome::from_snapshot)
+                    .unwrap_or(ReplayOutcome::NoEvent),
+            })
+            .collect();
+        Self {
+            component_id: response.component_id.clone(),
+            outcome,
+            nodes,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::proxy::v260::api::NodeReplayLastEventSnapshotDto;
+
+    fn snapshot(events: Vec<i64>, failure: Option<&str>) -> ReplayLastEventSnapshotDto {
+        ReplayLastEventSnapshotDto {
+            event_available: Some(!events.is_empty() || failure.is_some()),
+            events_replayed: events,
+            failure_explanation: failure.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn test_latest_replay_outcomes() {
+        let response = ReplayLastEventResponseEntity {
+            aggregate_snapshot: Some(snapshot(vec![42], None)),
+            component_id: Some("p1".to_string()),
+            node_snapshots: vec![
+                NodeReplayLastEventSnapshotDto {
+                    node_id: Some("n1".to_string()),
+                    snapshot: Some(snapshot(vec![42], None)),
+                    ..Default::default()
+                },
+                NodeReplayLastEventSnapshotDto {
+                    node_id: Some("n2".to_string()),
+                    snapshot: Some(snapshot(vec![], Some("Content no longer available"))),
+                    ..Default::default()
+                },
+                NodeReplayLastEventSnapshotDto {
+                    node_id: Some("n3".to_string()),
+                    snapshot: Some(snapshot(vec![], None)),
+                    ..Default::default()
+                },
+            ],
+            nodes: None,
+        };
+
+        let replay = LatestReplay::from_response(&response);
+
+        assert_eq!(
+            replay.outcome,
+            ReplayOutcome::Replayed {
+                event_ids: vec![42]
+            }
+        );
+        assert!(replay.nodes[0].outcome.is_replayed());
+        assert_eq!(
+            replay.nodes[1].outcome,
+            ReplayOutcome::Failed {
+                reason: "Content no longer available".to_string()
+            }
+        );
+        assert_eq!(replay.nodes[2].outcome, ReplayOutcome::NoEvent);
+    }
+}
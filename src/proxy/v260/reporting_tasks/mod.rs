@@ -0,0 +1,389 @@
+//! # Reporting Tasks Module
+//!
+//! Provides bindings for reporting tasks:
+//!
+//! * `/controller/reporting-tasks` and `/reporting-tasks/{id}` - CRUD, run
+//!   status, component state and property descriptors. Configuration
+//!   verification goes through the `verification` module
+//!   (`VerifiableComponent::ReportingTask`).
+//! * `/flow/reporting-tasks` - listing, and export of the reporting tasks (with
+//!   the controller services they use) as a `VersionedReportingTaskSnapshot`.
+//! * `/controller/reporting-tasks/import` - import of such a snapshot.
+//!
+//! `export_snapshot` and `import_snapshot` read and write snapshot files so the
+//! reporting task configuration can be kept in source control and re-imported
+//! into new clusters.
+
+use crate::common::client::{HttpClient, JsonResponse};
+use crate::common::config::Config;
+use crate::proxy::v260::api::{
+    ComponentStateEntity, ConfigurationAnalysisEntity, PropertyDescriptorEntity,
+    ReportingTaskEntity, ReportingTaskRunStatusEntity, ReportingTaskRunStatusEntityState,
+    ReportingTasksEntity, VersionedReportingTaskImportRequestEntity,
+    VersionedReportingTaskImportResponseEntity, VersionedReportingTaskSnapshot,
+};
+use anyhow::{Context, bail};
+use reqwest::Url;
+use std::path::Path;
+use std::sync::Arc;
+
+/// A service for managing reporting tasks.
+///
+/// This service is instantiated with shared (`Arc`) instances of `HttpClient` and `Config`.
+#[derive(Debug)]
+pub struct ReportingTasks {
+    client: Arc<HttpClient>,
+    config: Arc<Config>,
+}
+
+impl ReportingTasks {
+    /// Creates a new instance of the `ReportingTasks` service.
+    ///
+    /// # Arguments
+    ///
+    /// * `client` - The shared `HttpClient` to be used for requests.
+    /// * `config` - The application configuration (containing `api_base_url`).
+    pub fn new(client: Arc<HttpClient>, config: Arc<Config>) -> Self {
+        Self { client, config }
+    }
+
+    /// Retrieves all reporting tasks.
+    ///
+    /// Sends a `GET` request to `/flow/reporting-tasks`.
+    ///
+    /// # Errors
+    /// Returns `HttpClientError` if the request fails.
+    pub async fn get_reporting_tasks(&self) -> anyhow::Result<ReportingTasksEntity> {
+        let url = format!("{}/flow/reporting-tasks", self.config.api_base_url);
+        Ok(self.client.get_json::<ReportingTasksEntity>(&url).await?)
+    }
+
+    /// Creates a reporting task.
+    ///
+    /// Sends a `POST` request to `/controller/reporting-tasks`.
+    /// The `payload` must contain a revision with version `0`, a `type` and a `bundle`.
+    ///
+    /// # Errors
+    /// Returns `HttpClientError` if the request fails.
+    pub async fn post_reporting_task(
+        &self,
+        payload: &ReportingTaskEntity,
+    ) -> anyhow::Result<ReportingTaskEntity> {
+        let url = format!("{}/controller/reporting-tasks", self.config.api_base_url);
+        Ok(self
+            .client
+            .post_json::<ReportingTaskEntity, ReportingTaskEntity>(&url, payload)
+            .await?)
+    }
+
+    /// Retrieves a reporting task.
+    ///
+    /// Sends a `GET` request to `/reporting-tasks/{id}`.
+    ///
+    /// # Errors
+    /// Returns `HttpClientError` if the request fails.
+    pub async fn get_reporting_task(&self, id: &str) -> anyhow::Result<ReportingTaskEntity> {
+        let url = format!("{}/reporting-tasks/{}", self.config.api_base_url, id);
+        Ok(self.client.get_json::<ReportingTaskEntity>(&url).await?)
+    }
+
+    /// Updates a reporting task.
+    ///
+    /// Sends a `PUT` request to `/reporting-tasks/{id}`.
+    /// The `payload` must contain the current revision.
+    ///
+    /// # Errors
+    /// Returns `HttpClientError` if the request fails (e.g., 409 Conflict on bad version).
+    pub async fn put_reporting_task(
+        &self,
+        id: &str,
+        payload: &ReportingTaskEntity,
+    ) -> anyhow::Result<ReportingTaskEntity> {
+        let url = format!("{}/reporting-tasks/{}", self.config.api_base_url, id);
+        Ok(self
+            .client
+            .put_json::<ReportingTaskEntity, ReportingTaskEntity>(&url, payload)
+            .await?)
+    }
+
+    /// Deletes a stopped reporting task at its current revision.
+    ///
+    /// Sends a `DELETE` request to `/reporting-tasks/{id}`.
+    ///
+    /// # Errors
+    /// Returns `HttpClientError` if the request fails.
+    pub async fn delete_reporting_task(&self, id: &str) -> anyhow::Result<ReportingTaskEntity> {
+        let task = self.get_reporting_task(id).await?;
+        let Some(version) = task.revision.and_then(|revision| revision.version) else {
+            bail!("Revision was None");
+        };
+        let url = Url::parse_with_params(
+            &format!("{}/reporting-tasks/{}", self.config.api_base_url, id),
+            [("version", version.to_string())],
+        )?;
+        let response = self
+            .client
+            .delete::<JsonResponse<ReportingTaskEntity>>(url.as_str())
+            .await?;
+        Ok(response.0)
+    }
+
+    /// Starts or stops a reporting task.
+    ///
+    /// Sends a `PUT` request to `/reporting-tasks/{id}/run-status`.
+    /// The `payload` must contain the current revision.
+    ///
+    /// # Errors
+    /// Returns `HttpClientError` if the request fails.
+    pub async fn put_run_status(
+        &self,
+        id: &str,
+        payload: &ReportingTaskRunStatusEntity,
+    ) -> anyhow::Result<ReportingTaskEntity> {
+        let url = format!(
+            "{}/reporting-tasks/{}/run-status",
+            self.config.api_base_url, id
+        );
+        Ok(self
+            .client
+            .put_json::<ReportingTaskRunStatusEntity, ReportingTaskEntity>(&url, payload)
+            .await?)
+    }
+
+    /// Starts or stops a reporting task at its current revision.
+    ///
+    /// # Errors
+    /// Returns an error if a request fails (e.g., 409 Conflict when the task is invalid).
+    pub async fn set_run_status(
+        &self,
+        id: &str,
+        state: ReportingTaskRunStatusEntityState,
+    ) -> anyhow::Result<ReportingTaskEntity> {
+        let task = self.get_reporting_task(id).await?;
+        let payload = ReportingTaskRunStatusEntity {
+            revision: task.revision,
+            state: Some(state),
+            disconnected_node_acknowledged: None,
+        };
+        self.put_run_status(id, &payload).await
+    }
+
+    /// Retrieves the state stored by a reporting task.
+    ///
+    /// Sends a `GET` request to `/reporting-tasks/{id}/state`.
+    ///
+    /// # Errors
+    /// Returns `HttpClientError` if the request fails.
+    pub async fn get_state(&self, id: &str) -> anyhow::Result<ComponentStateEntity> {
+        let url = format!("{}/reporting-tasks/{}/state", self.config.api_base_url, id);
+        Ok(self.client.get_json::<ComponentStateEntity>(&url).await?)
+    }
+
+    /// Clears the state of a stopped reporting task.
+    ///
+    /// Sends a `POST` request to `/reporting-tasks/{id}/state/clear-requests`.
+    ///
+    /// # Errors
+    /// Returns `HttpClientError` if the request fails.
+    pub async fn clear_state(&self, id: &str) -> anyhow::Result<ComponentStateEntity> {
+        let url = format!(
+            "{}/reporting-tasks/{}/state/clear-requests",
+            self.config.api_base_url, id
+        );
+        Ok(self.client.post_empty::<ComponentStateEntity>(&url).await?)
+    }
+
+    /// Retrieves the descriptor of a property.
+    ///
+    /// Sends a `GET` request to `/reporting-tasks/{id}/descriptors`.
+    ///
+    /// # Errors
+    /// Returns `HttpClientError` if the request fails.
+    pub async fn get_property_descriptor(
+        &self,
+        id: &str,
+        property_name: &str,
+        sensitive: bool,
+    ) -> anyhow::Result<PropertyDescriptorEntity> {
+        let url = Url::parse_with_params(
+            &format!(
+                "{}/reporting-tasks/{}/descriptors",
+                self.config.api_base_url, id
+            ),
+            [
+                ("propertyName", property_name.to_string()),
+                ("sensitive", sensitive.to_string()),
+            ],
+        )?;
+        Ok(self
+            .client
+            .get_json::<PropertyDescriptorEntity>(url.as_str())
+            .await?)
+    }
+
+    /// Analyzes a configuration, listing the attributes it references and
+    /// whether it can be verified.
+    ///
+    /// Sends a `POST` request to `/reporting-tasks/{id}/config/analysis`.
+    ///
+    /// # Errors
+    /// Returns `HttpClientError` if the request fails.
+    pub async fn post_config_analysis(
+        &self,
+        id: &str,
+        payload: &ConfigurationAnalysisEntity,
+    ) -> anyhow::Result<ConfigurationAnalysisEntity> {
+        let url = format!(
+            "{}/reporting-tasks/{}/config/analysis",
+            self.config.api_base_url, id
+        );
+        Ok(self
+            .client
+            .post_json::<ConfigurationAnalysisEntity, ConfigurationAnalysisEntity>(&url, payload)
+            .await?)
+    }
+
+    /// Exports reporting tasks, with the controller services they reference.
+    /// All of them when `reporting_task_id` is `None`.
+    ///
+    /// Sends a `GET` request to `/flow/reporting-tasks/snapshot`.
+    ///
+    /// # Errors
+    /// Returns `HttpClientError` if the request fails.
+    pub async fn get_snapshot(
+        &self,
+        reporting_task_id: Option<&str>,
+    ) -> anyhow::Result<VersionedReportingTaskSnapshot> {
+        let url = snapshot_url(
+            &format!("{}/flow/reporting-tasks/snapshot", self.config.api_base_url),
+            reporting_task_id,
+        )?;
+        Ok(self
+            .client
+            .get_json::<VersionedReportingTaskSnapshot>(&url)
+            .await?)
+    }
+
+    /// Downloads the same snapshot as `get_snapshot`, as the raw JSON document
+    /// NiFi serves for saving to a file.
+    ///
+    /// Sends a `GET` request to `/flow/reporting-tasks/download`.
+    ///
+    /// # Errors
+    /// Returns `HttpClientError` if the request fails.
+    pub async fn download_snapshot(
+        &self,
+        reporting_task_id: Option<&str>,
+    ) -> anyhow::Result<String> {
+        let url = snapshot_url(
+            &format!("{}/flow/reporting-tasks/download", self.config.api_base_url),
+            reporting_task_id,
+        )?;
+        Ok(self.client.get_text(&url).await?)
+    }
+
+    /// Imports reporting tasks and their controller services. New instances are
+    /// created; existing ones are left alone.
+    ///
+    /// Sends a `POST` request to `/controller/reporting-tasks/import`.
+    ///
+    /// # Errors
+    /// Returns `HttpClientError` if the request fails.
+    pub async fn post_import(
+        &self,
+        payload: &VersionedReportingTaskImportRequestEntity,
+    ) -> anyhow::Result<VersionedReportingTaskImportResponseEntity> {
+        let url = format!(
+            "{}/controller/reporting-tasks/import",
+            self.config.api_base_url
+        );
+        Ok(self
+            .client
+            .post_json::<VersionedReportingTaskImportRequestEntity, VersionedReportingTaskImportResponseEntity>(
+                &url, payload,
+            )
+            .await?)
+    }
+
+    /// Writes a snapshot of the reporting tasks to `path` as pretty JSON.
+    ///
+    /// # Errors
+    /// Returns an error if the request fails or the file cannot be written.
+    pub async fn export_snapshot(
+        &self,
+        reporting_task_id: Option<&str>,
+        path: &Path,
+    ) -> anyhow::Result<VersionedReportingTaskSnapshot> {
+        let snapshot = self.get_snapshot(reporting_task_id).await?;
+        let json = serde_json::to_string_pretty(&snapshot)?;
+        tokio::fs::write(path, json)
+            .await
+            .with_context(|| format!("Failed to write {}", path.display()))?;
+        Ok(snapshot)
+    }
+
+    /// Imports a snapshot file written by `export_snapshot` (or downloaded
+    /// from the UI).
+    ///
+    /// # Errors
+    /// Returns an error if the file cannot be read or parsed, or the request fails.
+    pub async fn import_snapshot(
+        &self,
+        path: &Path,
+    ) -> anyhow::Result<VersionedReportingTaskImportResponseEntity> {
+        let json = tokio::fs::read_to_string(path)
+            .await
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        let snapshot: VersionedReportingTaskSnapshot = serde_json::from_str(&json)
+            .with_context(|| format!("{} is not a reporting task snapshot", path.display()))?;
+        let payload = VersionedReportingTaskImportRequestEntity {
+            reporting_task_snapshot: Some(snapshot),
+            disconnected_node_acknowledged: None,
+        };
+        self.post_import(&payload).await
+    }
+}
+
+fn snapshot_url(base: &str, reporting_task_id: Option<&str>) -> anyhow::Result<String> {
+    match reporting_task_id {
+        Some(id) => Ok(Url::parse_with_params(base, [("reportingTaskId", id)])?.to_string()),
+        None => Ok(base.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::proxy::v260::access::Access;
+    use tracing_test::traced_test;
+
+    #[test]
+    fn test_snapshot_url() {
+        let base = "https://nifi/nifi-api/flow/reporting-tasks/snapshot";
+
+        assert_eq!(snapshot_url(base, None).unwrap(), base);
+        assert_eq!(
+            snapshot_url(base, Some("abc")).unwrap(),
+            format!("{}?reportingTaskId=abc", base)
+        );
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    async fn test_get_snapshot() {
+        // --- 1. Setup ---
+        let client = Arc::new(HttpClient::new());
+        let config = Arc::new(Config::default());
+        let access = Access::new(client.clone(), config.clone());
+        let _ = access.get_access_token().await;
+
+        // --- 2. Export every reporting task ---
+        let reporting_tasks = ReportingTasks::new(client.clone(), config.clone());
+        let snapshot = reporting_tasks.get_snapshot(None).await;
+        assert!(
+            snapshot.is_ok(),
+            "test_get_snapshot call error: {:?}",
+            snapshot
+        );
+    }
+}
@@ -0,0 +1,240 @@
+//! Status history as typed time series.
+//!
+//! `StatusHistoryDto` stores one `StatusSnapshotDto` per capture (every
+//! minute by default), each holding a map of metric name to value.
+//! `StatusHistory` turns that into one `TimeSeries` per metric, for the
+//! cluster aggregate and for every node.
+//!
+//! The counters of a snapshot (`inputCount`, `outputBytes`...) cover the
+//! trailing five minutes at capture time, so the rate helpers divide them by
+//! `COUNTER_WINDOW` rather than by the capture interval.
+
+use crate::proxy::v260::api::{StatusHistoryDto, StatusSnapshotDto};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+use std::time::Duration;
+
+/// The period covered by the counters of a status snapshot.
+pub const COUNTER_WINDOW: Duration = Duration::from_secs(5 * 60);
+
+/// The metric names NiFi uses for throughput.
+pub const INPUT_COUNT: &str = "inputCount";
+pub const INPUT_BYTES: &str = "inputBytes";
+pub const OUTPUT_COUNT: &str = "outputCount";
+pub const OUTPUT_BYTES: &str = "outputBytes";
+
+/// One value of a metric.
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Sample {
+    pub timestamp: DateTime<Utc>,
+    pub value: i64,
+}
+
+/// The values of one metric, oldest first.
+#[derive(Serialize, Debug, Clone, PartialEq, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct TimeSeries {
+    pub field: String,
+    pub label: Option<String>,
+    pub description: Option<String>,
+    /// How NiFi formats the values: `COUNT`, `DATA_SIZE` or `DURATION`.
+    pub formatter: Option<String>,
+    pub samples: Vec<Sample>,
+}
+
+impl TimeSeries {
+    pub fn latest(&self) -> Option<Sample> {
+        self.samples.last().copied()
+    }
+
+    /// The samples captured within `window` of the latest one. A window
+    /// reaching before the earliest representable time covers every sample.
+    pub fn window(&self, window: Duration) -> &[Sample] {
+        let Some(latest) = self.latest() else {
+            return &[];
+        };
+        let window = chrono::Duration::from_std(window).unwrap_or(chrono::Duration::MAX);
+        let Some(start) = latest.timestamp.checked_sub_signed(window) else {
+            return &self.samples;
+        };
+        let first = self
+            .samples
+            .iter()
+            .position(|sample| sample.timestamp >= start)
+            .unwrap_or(self.samples.len());
+        &self.samples[first..]
+    }
+
+    /// The average of the values within `window` of the latest sample.
+    pub fn average(&self, window: Duration) -> Option<f64> {
+        let samples = self.window(window);
+        if samples.is_empty() {
+            return None;
+        }
+        let sum: i64 = samples.iter().map(|sample| sample.value).sum();
+        Some(sum as f64 / samples.len() as f64)
+    }
+
+    /// The per-second rate of a five-minute counter, averaged over `window`.
+    pub fn rate_per_second(&self, window: Duration) -> Option<f64> {
+        self.average(window)
+            .map(|average| average / COUNTER_WINDOW.as_secs_f64())
+    }
+}
+
+/// The history of one node of a cluster.
+#[derive(Serialize, Debug, Clone, PartialEq, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct NodeHistory {
+    pub node_id: Option<String>,
+    pub address: Option<String>,
+    pub series: BTreeMap<String, TimeSeries>,
+}
+
+/// The status history of a component.
+#[derive(Serialize, Debug, Clone, PartialEq, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct StatusHistory {
+    /// Name, type, group... of the component.
+    pub component_details: HashMap<String, String>,
+    /// The series for the whole cluster (or the standalone instance), by field.
+    pub series: BTreeMap<String, TimeSeries>,
+    pub nodes: Vec<NodeHistory>,
+}
+
+impl StatusHistory {
+    pub fn from_dto(dto: &StatusHistoryDto) -> Self {
+        let descriptors = dto
+            .field_descriptors
+            .iter()
+            .filter_map(|descriptor| {
+                let field = descriptor.field.clone()?;
+                Some((
+                    field.clone(),
+                    TimeSeries {
+                        field,
+                        label: descriptor.label.clone(),
+                        description: descriptor.description.clone(),
+                        formatter: descriptor.formatter.clone(),
+                        samples: Vec::new(),
+                    },
+                ))
+            })
+            .collect::<BTreeMap<_, _>>();
+        Self {
+            component_details: dto
+                .component_details
+                .iter()
+                .filter_map(|(key, value)| Some((key.clone(), value.clone()?)))
+                .collect(),
+            series: to_series(&descriptors, &dto.aggregate_snapshots),
+            nodes: dto
+                .node_snapshots
+                .iter()
+                .map(|node| NodeHistory {
+                    node_id: node.node_id.clone(),
+                    address: node.address.clone(),
+                    series: to_series(&descriptors, &node.status_snapshots),
+                })
+                .collect(),
+        }
+    }
+
+    pub fn get(&self, field: &str) -> Option<&TimeSeries> {
+        self.series.get(field)
+    }
+
+    /// FlowFiles received per second, averaged over `window`.
+    pub fn flow_files_in_per_second(&self, window: Duration) -> Option<f64> {
+        self.get(INPUT_COUNT)?.rate_per_second(window)
+    }
+
+    /// FlowFiles sent per second, averaged over `window`.
+    pub fn flow_files_out_per_second(&self, window: Duration) -> Option<f64> {
+        self.get(OUTPUT_COUNT)?.rate_per_second(window)
+    }
+
+    /// Bytes received per second, averaged over `window`.
+    pub fn bytes_in_per_second(&self, window: Duration) -> Option<f64> {
+        self.get(INPUT_BYTES)?.rate_per_second(window)
+    }
+
+    /// Bytes sent per second, averaged over `window`.
+    pub fn bytes_out_per_second(&self, window: Duration) -> Option<f64> {
+        self.get(OUTPUT_BYTES)?.rate_per_second(window)
+    }
+}
+
+/// Splits snapshots into one series per field, sorted by time. Fields
+/// without a descriptor still get a series.
+fn to_series(
+    descriptors: &BTreeMap<String, TimeSeries>,
+    snapshots: &[StatusSnapshotDto],
+) -> BTreeMap<String, TimeSeries> {
+    let mut series = descriptors.clone();
+    let mut snapshots: Vec<_> = snapshots
+        .iter()
+        .filter_map(|snapshot| Some((snapshot.timestamp?, &snapshot.status_metrics)))
+        .collect();
+    snapshots.sort_by_key(|(timestamp, _)| *timestamp);
+    for (timestamp, metrics) in snapshots {
+        for (field, value) in metrics {
+            series
+                .entry(field.clone())
+                .or_insert_with(|| TimeSeries {
+                    field: field.clone(),
+                    ..Default::default()
+                })
+                .samples
+                .push(Sample {
+                    timestamp,
+                    value: *value,
+                });
+        }
+    }
+    series
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::proxy::v260::api::StatusDescriptorDto;
+    use chrono::TimeZone;
+
+    fn snapshot(minute: u32, input_count: i64) -> StatusSnapshotDto {
+        StatusSnapshotDto {
+            status_metrics: HashMap::from([(INPUT_COUNT.to_string(), input_count)]),
+            timestamp: Some(Utc.with_ymd_and_hms(2025, 1, 1, 0, minute, 0).unwrap()),
+        }
+    }
+
+    #[test]
+    fn test_status_history_rates() {
+        let dto = StatusHistoryDto {
+            field_descriptors: vec![StatusDescriptorDto {
+                field: Some(INPUT_COUNT.to_string()),
+                label: Some("FlowFiles In (5 mins)".to_string()),
+                formatter: Some("COUNT".to_string()),
+                ..Default::default()
+            }],
+            // Out of order on purpose.
+            aggregate_snapshots: vec![snapshot(2, 600), snapshot(0, 3000), snapshot(1, 300)],
+            ..Default::default()
+        };
+
+        let history = StatusHistory::from_dto(&dto);
+
+        let series = history.get(INPUT_COUNT).unwrap();
+        assert_eq!(series.label.as_deref(), Some("FlowFiles In (5 mins)"));
+        assert_eq!(series.latest().unwrap().value, 600);
+        assert_eq!(series.window(Duration::from_secs(60)).len(), 2);
+        // (300 + 600) / 2 FlowFiles per five minutes.
+        assert_eq!(
+            history.flow_files_in_per_second(Duration::from_secs(60)),
+            Some(1.5)
+        );
+        assert_eq!(history.bytes_in_per_second(Duration::from_secs(60)), None);
+        assert_eq!(series.window(Duration::MAX).len(), 3);
+    }
+}
@@ -0,0 +1,209 @@
+//! # Status Module
+//!
+//! Provides high-level bindings for the status of the flow and its components:
+//!
+//! * `/flow/status` - controller-wide counters (active threads, queued data...).
+//! * `/flow/process-groups/{id}/status` - the status of a group, optionally
+//!   with every descendant component.
+//! * `/flow/{processors|connections|input-ports|output-ports|remote-process-groups}/{id}/status`
+//!   - the status of a single component.
+//! * `/flow/{...}/{id}/status/history` - the captured history of a component,
+//!   raw or as a typed `history::StatusHistory`.
+
+use crate::common::client::HttpClient;
+use crate::common::config::Config;
+use crate::proxy::v260::api::{
+    ConnectionStatusEntity, ControllerStatusEntity, PortStatusEntity, ProcessGroupStatusEntity,
+    ProcessorStatusEntity, RemoteProcessGroupStatusEntity, StatusHistoryEntity,
+};
+use crate::proxy::v260::status::history::StatusHistory;
+use anyhow::bail;
+use serde::de::DeserializeOwned;
+use std::sync::Arc;
+
+pub mod history;
+
+/// The components with a status history.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HistoryComponent {
+    Processor,
+    Connection,
+    ProcessGroup,
+    RemoteProcessGroup,
+}
+
+impl HistoryComponent {
+    fn path(self) -> &'static str {
+        match self {
+            HistoryComponent::Processor => "processors",
+            HistoryComponent::Connection => "connections",
+            HistoryComponent::ProcessGroup => "process-groups",
+            HistoryComponent::RemoteProcessGroup => "remote-process-groups",
+        }
+    }
+}
+
+/// A service for reading the status of the flow and its components.
+///
+/// This service is instantiated with shared (`Arc`) instances of `HttpClient` and `Config`.
+#[derive(Debug)]
+pub struct Status {
+    client: Arc<HttpClient>,
+    config: Arc<Config>,
+}
+
+impl Status {
+    /// Creates a new instance of the `Status` service.
+    ///
+    /// # Arguments
+    ///
+    /// * `client` - The shared `HttpClient` to be used for requests.
+    /// * `config` - The application configuration (containing `api_base_url`).
+    pub fn new(client: Arc<HttpClient>, config: Arc<Config>) -> Self {
+        Self { client, config }
+    }
+
+    /// Retrieves the controller-wide status.
+    ///
+    /// Sends a `GET` request to `/flow/status`.
+    ///
+    /// # Errors
+    /// Returns `HttpClientError` if the request fails.
+    pub async fn get_controller_status(&self) -> anyhow::Result<ControllerStatusEntity> {
+        self.get("flow/status").await
+    }
+
+    /// Retrieves the status of a process group. With `recursive`, the status
+    /// of every descendant component is included.
+    ///
+    /// Sends a `GET` request to `/flow/process-groups/{id}/status`.
+    ///
+    /// # Errors
+    /// Returns `HttpClientError` if the request fails.
+    pub async fn get_process_group_status(
+        &self,
+        id: &str,
+        recursive: bool,
+    ) -> anyhow::Result<ProcessGroupStatusEntity> {
+        self.get(&format!(
+            "flow/process-groups/{}/status?recursive={}",
+            id, recursive
+        ))
+        .await
+    }
+
+    /// Retrieves the status of a processor.
+    ///
+    /// Sends a `GET` request to `/flow/processors/{id}/status`.
+    ///
+    /// # Errors
+    /// Returns `HttpClientError` if the request fails.
+    pub async fn get_processor_status(&self, id: &str) -> anyhow::Result<ProcessorStatusEntity> {
+        self.get(&format!("flow/processors/{}/status", id)).await
+    }
+
+    /// Retrieves the status of a connection.
+    ///
+    /// Sends a `GET` request to `/flow/connections/{id}/status`.
+    ///
+    /// # Errors
+    /// Returns `HttpClientError` if the request fails.
+    pub async fn get_connection_status(&self, id: &str) -> anyhow::Result<ConnectionStatusEntity> {
+        self.get(&format!("flow/connections/{}/status", id)).await
+    }
+
+    /// Retrieves the status of an input port.
+    ///
+    /// Sends a `GET` request to `/flow/input-ports/{id}/status`.
+    ///
+    /// # Errors
+    /// Returns `HttpClientError` if the request fails.
+    pub async fn get_input_port_status(&self, id: &str) -> anyhow::Result<PortStatusEntity> {
+        self.get(&format!("flow/input-ports/{}/status", id)).await
+    }
+
+    /// Retrieves the status of an output port.
+    ///
+    /// Sends a `GET` request to `/flow/output-ports/{id}/status`.
+    ///
+    /// # Errors
+    /// Returns `HttpClientError` if the request fails.
+    pub async fn get_output_port_status(&self, id: &str) -> anyhow::Result<PortStatusEntity> {
+        self.get(&format!("flow/output-ports/{}/status", id)).await
+    }
+
+    /// Retrieves the status of a remote process group.
+    ///
+    /// Sends a `GET` request to `/flow/remote-process-groups/{id}/status`.
+    ///
+    /// # Errors
+    /// Returns `HttpClientError` if the request fails.
+    pub async fn get_remote_process_group_status(
+        &self,
+        id: &str,
+    ) -> anyhow::Result<RemoteProcessGroupStatusEntity> {
+        self.get(&format!("flow/remote-process-groups/{}/status", id))
+            .await
+    }
+
+    /// Retrieves the status history of a component.
+    ///
+    /// Sends a `GET` request to `/flow/{processors|connections|process-groups|remote-process-groups}/{id}/status/history`.
+    ///
+    /// # Errors
+    /// Returns `HttpClientError` if the request fails.
+    pub async fn get_status_history(
+        &self,
+        component: HistoryComponent,
+        id: &str,
+    ) -> anyhow::Result<StatusHistoryEntity> {
+        self.get(&format!("flow/{}/{}/status/history", component.path(), id))
+            .await
+    }
+
+    /// Retrieves the status history of a component as typed time series.
+    ///
+    /// # Errors
+    /// Returns an error if the request fails or the history is not readable
+    /// by the current user.
+    pub async fn status_history(
+        &self,
+        component: HistoryComponent,
+        id: &str,
+    ) -> anyhow::Result<StatusHistory> {
+        let entity = self.get_status_history(component, id).await?;
+        let Some(history) = entity.status_history else {
+            bail!("No status history for {:?} {}", component, id);
+        };
+        Ok(StatusHistory::from_dto(&history))
+    }
+
+    async fn get<R: DeserializeOwned>(&self, path: &str) -> anyhow::Result<R> {
+        let url = format!("{}/{}", self.config.api_base_url, path);
+        Ok(self.client.get_json::<R>(&url).await?)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::proxy::v260::access::Access;
+    use tracing_test::traced_test;
+
+    #[tokio::test]
+    #[traced_test]
+    async fn test_get_process_group_status() {
+        // --- 1. Setup ---
+        let client = Arc::new(HttpClient::new());
+        let config = Arc::new(Config::default());
+        let access = Access::new(client.clone(), config.clone());
+        let _ = access.get_access_token().await;
+        let status = Status::new(client, config);
+
+        // --- 2. Execution ---
+        let entity = status.get_process_group_status("root", true).await.unwrap();
+
+        // --- 3. Verification ---
+        assert!(entity.process_group_status.is_some());
+    }
+}
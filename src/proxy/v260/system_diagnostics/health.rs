@@ -0,0 +1,271 @@
+//! Health verdicts from system diagnostics.
+//!
+//! A `HealthRule` sets degraded and unhealthy thresholds on one
+//! `HealthMetric`. `HealthRules::evaluate` checks every node (or the
+//! aggregate on a standalone instance) and reports the worst verdict, with
+//! one `HealthReason` per broken threshold.
+
+use crate::proxy::v260::system_diagnostics::{Diagnostics, DiagnosticsReport, StorageUsage};
+use serde::Serialize;
+use std::fmt;
+
+/// The overall state of an instance, from best to worst.
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Verdict {
+    Healthy,
+    Degraded,
+    Unhealthy,
+}
+
+/// What a rule measures. Utilizations are percentages; for repositories
+/// with several storage locations, the fullest one counts.
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "camelCase")]
+pub enum HealthMetric {
+    HeapUtilization,
+    NonHeapUtilization,
+    FlowFileRepositoryUtilization,
+    ContentRepositoryUtilization,
+    ProvenanceRepositoryUtilization,
+    /// The load average divided by the number of processors.
+    LoadPerProcessor,
+    TotalThreads,
+}
+
+impl HealthMetric {
+    fn measure(self, diagnostics: &Diagnostics) -> Option<f64> {
+        let fullest = |usages: &[StorageUsage]| {
+            usages
+                .iter()
+                .filter_map(StorageUsage::utilization)
+                .reduce(f64::max)
+        };
+        match self {
+            HealthMetric::HeapUtilization => diagnostics.heap.utilization(),
+            HealthMetric::NonHeapUtilization => diagnostics.non_heap.utilization(),
+            HealthMetric::FlowFileRepositoryUtilization => diagnostics
+                .flow_file_repository
+                .as_ref()
+                .and_then(StorageUsage::utilization),
+            HealthMetric::ContentRepositoryUtilization => {
+                fullest(&diagnostics.content_repositories)
+            },
+            HealthMetric::ProvenanceRepositoryUtilization => {
+                fullest(&diagnostics.provenance_repositories)
+            },
+            HealthMetric::LoadPerProcessor => diagnostics.load_per_processor(),
+            HealthMetric::TotalThreads => diagnostics.total_threads.map(f64::from),
+        }
+    }
+}
+
+impl fmt::Display for HealthMetric {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            HealthMetric::HeapUtilization => "heap utilization",
+            HealthMetric::NonHeapUtilization => "non-heap utilization",
+            HealthMetric::FlowFileRepositoryUtilization => "FlowFile repository utilization",
+            HealthMetric::ContentRepositoryUtilization => "content repository utilization",
+            HealthMetric::ProvenanceRepositoryUtilization => "provenance repository utilization",
+            HealthMetric::LoadPerProcessor => "load per processor",
+            HealthMetric::TotalThreads => "total threads",
+        };
+        f.write_str(name)
+    }
+}
+
+/// Thresholds on one metric. A value strictly above a threshold breaks it.
+#[derive(Serialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct HealthRule {
+    pub metric: HealthMetric,
+    pub degraded_above: Option<f64>,
+    pub unhealthy_above: Option<f64>,
+}
+
+impl HealthRule {
+    pub fn new(metric: HealthMetric) -> Self {
+        Self {
+            metric,
+            degraded_above: None,
+            unhealthy_above: None,
+        }
+    }
+
+    pub fn degraded_above(mut self, threshold: f64) -> Self {
+        self.degraded_above = Some(threshold);
+        self
+    }
+
+    pub fn unhealthy_above(mut self, threshold: f64) -> Self {
+        self.unhealthy_above = Some(threshold);
+        self
+    }
+
+    fn check(&self, diagnostics: &Diagnostics) -> Option<HealthReason> {
+        let value = self.metric.measure(diagnostics)?;
+        let (verdict, threshold) = match (self.unhealthy_above, self.degraded_above) {
+            (Some(threshold), _) if value > threshold => (Verdict::Unhealthy, threshold),
+            (_, Some(threshold)) if value > threshold => (Verdict::Degraded, threshold),
+            _ => return None,
+        };
+        Some(HealthReason {
+            node: diagnostics
+                .address
+                .clone()
+                .or_else(|| diagnostics.node_id.clone()),
+            metric: self.metric,
+            value,
+            threshold,
+            verdict,
+        })
+    }
+}
+
+/// A broken threshold.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct HealthReason {
+    /// The node address (or id); `None` for the aggregate.
+    pub node: Option<String>,
+    pub metric: HealthMetric,
+    pub value: f64,
+    pub threshold: f64,
+    pub verdict: Verdict,
+}
+
+impl fmt::Display for HealthReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(node) = &self.node {
+            write!(f, "{}: ", node)?;
+        }
+        write!(
+            f,
+            "{} is {:.1}, above {:.1} ({:?})",
+            self.metric, self.value, self.threshold, self.verdict
+        )
+    }
+}
+
+/// The result of evaluating health rules.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct HealthReport {
+    pub verdict: Verdict,
+    pub reasons: Vec<HealthReason>,
+}
+
+/// A set of health rules.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct HealthRules {
+    pub rules: Vec<HealthRule>,
+}
+
+impl Default for HealthRules {
+    /// Heap above 80% (degraded) or 90% (unhealthy), and repositories above
+    /// 85% or 95%.
+    fn default() -> Self {
+        Self {
+            rules: vec![
+                HealthRule::new(HealthMetric::HeapUtilization)
+                    .degraded_above(80.0)
+                    .unhealthy_above(90.0),
+                HealthRule::new(HealthMetric::FlowFileRepositoryUtilization)
+                    .degraded_above(85.0)
+                    .unhealthy_above(95.0),
+                HealthRule::new(HealthMetric::ContentRepositoryUtilization)
+                    .degraded_above(85.0)
+                    .unhealthy_above(95.0),
+                HealthRule::new(HealthMetric::ProvenanceRepositoryUtilization)
+                    .degraded_above(85.0)
+                    .unhealthy_above(95.0),
+            ],
+        }
+    }
+}
+
+impl HealthRules {
+    pub fn new() -> Self {
+        Self { rules: Vec::new() }
+    }
+
+    pub fn rule(mut self, rule: HealthRule) -> Self {
+        self.rules.push(rule);
+        self
+    }
+
+    /// Evaluates every node, or the aggregate when there are no nodes.
+    pub fn evaluate(&self, diagnostics: &DiagnosticsReport) -> HealthReport {
+        let targets: Vec<&Diagnostics> = if diagnostics.nodes.is_empty() {
+            vec![&diagnostics.aggregate]
+        } else {
+            diagnostics.nodes.iter().collect()
+        };
+        let reasons: Vec<HealthReason> = targets
+            .into_iter()
+            .flat_map(|target| self.rules.iter().filter_map(|rule| rule.check(target)))
+            .collect();
+        HealthReport {
+            verdict: reasons
+                .iter()
+                .map(|reason| reason.verdict)
+                .max()
+                .unwrap_or(Verdict::Healthy),
+            reasons,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::proxy::v260::system_diagnostics::MemoryUsage;
+
+    fn node(address: &str, heap_used: i64, content_used: i64) -> Diagnostics {
+        Diagnostics {
+            address: Some(address.to_string()),
+            heap: MemoryUsage {
+                used_bytes: heap_used,
+                max_bytes: Some(100),
+                ..Default::default()
+            },
+            content_repositories: vec![StorageUsage {
+                used_bytes: content_used,
+                total_bytes: 100,
+                ..Default::default()
+            }],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_evaluate() {
+        let rules = HealthRules::new()
+            .rule(HealthRule::new(HealthMetric::ContentRepositoryUtilization).degraded_above(85.0))
+            .rule(HealthRule::new(HealthMetric::HeapUtilization).unhealthy_above(90.0));
+
+        let healthy = DiagnosticsReport {
+            nodes: vec![node("nifi-1", 50, 50)],
+            ..Default::default()
+        };
+        let degraded = DiagnosticsReport {
+            nodes: vec![node("nifi-1", 50, 50), node("nifi-2", 50, 86)],
+            ..Default::default()
+        };
+        let unhealthy = DiagnosticsReport {
+            aggregate: node("nifi", 95, 86),
+            ..Default::default()
+        };
+
+        assert_eq!(rules.evaluate(&healthy).verdict, Verdict::Healthy);
+        let report = rules.evaluate(&degraded);
+        assert_eq!(report.verdict, Verdict::Degraded);
+        assert_eq!(
+            report.reasons[0].to_string(),
+            "nifi-2: content repository utilization is 86.0, above 85.0 (Degraded)"
+        );
+        let report = rules.evaluate(&unhealthy);
+        assert_eq!(report.verdict, Verdict::Unhealthy);
+        assert_eq!(report.reasons.len(), 2);
+    }
+}
@@ -0,0 +1,346 @@
+//! # System Diagnostics Module
+//!
+//! Provides high-level bindings for `/system-diagnostics` and
+//! `/system-diagnostics/jmx-metrics`.
+//!
+//! `SystemDiagnosticsDto` is mapped into a typed `DiagnosticsReport` (heap,
+//! non-heap, repository usage, garbage collection, load and threads), for
+//! the whole cluster and for every node. `health::HealthRules` evaluate it
+//! into a `Healthy`/`Degraded`/`Unhealthy` verdict.
+
+use crate::common::client::HttpClient;
+use crate::common::config::Config;
+use crate::proxy::v260::api::{
+    GarbageCollectionDto, StorageUsageDto, SystemDiagnosticsEntity, SystemDiagnosticsSnapshotDto,
+};
+use anyhow::bail;
+use reqwest::Url;
+use serde::Serialize;
+use serde_json::Value;
+use std::sync::Arc;
+
+pub mod health;
+
+/// Heap or non-heap memory usage.
+#[derive(Serialize, Debug, Clone, PartialEq, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct MemoryUsage {
+    pub used_bytes: i64,
+    pub free_bytes: i64,
+    pub total_bytes: i64,
+    /// `None` when the JVM sets no maximum (common for non-heap).
+    pub max_bytes: Option<i64>,
+}
+
+impl MemoryUsage {
+    /// Used memory as a percentage of the maximum (or of the total when
+    /// there is no maximum).
+    pub fn utilization(&self) -> Option<f64> {
+        let limit = self
+            .max_bytes
+            .filter(|max| *max > 0)
+            .unwrap_or(self.total_bytes);
+        (limit > 0).then(|| self.used_bytes as f64 * 100.0 / limit as f64)
+    }
+}
+
+/// The usage of one repository storage location.
+#[derive(Serialize, Debug, Clone, PartialEq, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct StorageUsage {
+    pub identifier: Option<String>,
+    pub used_bytes: i64,
+    pub free_bytes: i64,
+    pub total_bytes: i64,
+}
+
+impl StorageUsage {
+    fn from_dto(dto: &StorageUsageDto) -> Self {
+        Self {
+            identifier: dto.identifier.clone(),
+            used_bytes: dto.used_space_bytes.unwrap_or(0),
+            free_bytes: dto.free_space_bytes.unwrap_or(0),
+            total_bytes: dto.total_space_bytes.unwrap_or(0),
+        }
+    }
+
+    /// Used space as a percentage of the total.
+    pub fn utilization(&self) -> Option<f64> {
+        (self.total_bytes > 0).then(|| self.used_bytes as f64 * 100.0 / self.total_bytes as f64)
+    }
+}
+
+/// The activity of one garbage collector.
+#[derive(Serialize, Debug, Clone, PartialEq, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct GarbageCollection {
+    pub name: Option<String>,
+    pub collection_count: i64,
+    pub collection_millis: i64,
+}
+
+impl GarbageCollection {
+    fn from_dto(dto: &GarbageCollectionDto) -> Self {
+        Self {
+            name: dto.name.clone(),
+            collection_count: dto.collection_count.unwrap_or(0),
+            collection_millis: dto.collection_millis.unwrap_or(0),
+        }
+    }
+}
+
+/// The diagnostics of one node, or of the whole cluster.
+#[derive(Serialize, Debug, Clone, PartialEq, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct Diagnostics {
+    /// `None` for the aggregate.
+    pub node_id: Option<String>,
+    pub address: Option<String>,
+    pub heap: MemoryUsage,
+    pub non_heap: MemoryUsage,
+    pub flow_file_repository: Option<StorageUsage>,
+    pub content_repositories: Vec<StorageUsage>,
+    pub provenance_repositories: Vec<StorageUsage>,
+    pub garbage_collection: Vec<GarbageCollection>,
+    pub processor_load_average: Option<f64>,
+    pub available_processors: Option<i32>,
+    pub total_threads: Option<i32>,
+    pub daemon_threads: Option<i32>,
+    pub uptime: Option<String>,
+}
+
+impl Diagnostics {
+    pub fn from_snapshot(snapshot: &SystemDiagnosticsSnapshotDto) -> Self {
+        let storage = |usage: &Option<Vec<StorageUsageDto>>| {
+            usage.iter().flatten().map(StorageUsage::from_dto).collect()
+        };
+        Self {
+            node_id: None,
+            address: None,
+            heap: MemoryUsage {
+                used_bytes: snapshot.used_heap_bytes.unwrap_or(0),
+                free_bytes: snapshot.free_heap_bytes.unwrap_or(0),
+                total_bytes: snapshot.total_heap_bytes.unwrap_or(0),
+                max_bytes: snapshot.max_heap_bytes.filter(|max| *max > 0),
+            },
+            non_heap: MemoryUsage {
+                used_bytes: snapshot.used_non_heap_bytes.unwrap_or(0),
+                free_bytes: snapshot.free_non_heap_bytes.unwrap_or(0),
+                total_bytes: snapshot.total_non_heap_bytes.unwrap_or(0),
+                max_bytes: snapshot.max_non_heap_bytes.filter(|max| *max > 0),
+            },
+            flow_file_repository: snapshot
+                .flow_file_repository_storage_usage
+                .as_ref()
+                .map(StorageUsage::from_dto),
+            content_repositories: storage(&snapshot.content_repository_storage_usage),
+            provenance_repositories: storage(&snapshot.provenance_repository_storage_usage),
+            garbage_collection: snapshot
+                .garbage_collection
+                .iter()
+                .flatten()
+                .map(GarbageCollection::from_dto)
+                .collect(),
+            processor_load_average: snapshot.processor_load_average.filter(|load| *load >= 0.0),
+            available_processors: snapshot.available_processors,
+            total_threads: snapshot.total_threads,
+            daemon_threads: snapshot.daemon_threads,
+            uptime: snapshot.uptime.clone(),
+        }
+    }
+
+    /// The load average divided by the number of processors.
+    pub fn load_per_processor(&self) -> Option<f64> {
+        let processors = self.available_processors.filter(|count| *count > 0)?;
+        Some(self.processor_load_average? / processors as f64)
+    }
+}
+
+/// The diagnostics of a NiFi instance or cluster.
+#[derive(Serialize, Debug, Clone, PartialEq, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct DiagnosticsReport {
+    pub aggregate: Diagnostics,
+    /// One entry per node; only filled when requested `nodewise`.
+    pub nodes: Vec<Diagnostics>,
+}
+
+impl DiagnosticsReport {
+    pub fn from_entity(entity: &SystemDiagnosticsEntity) -> anyhow::Result<Self> {
+        let Some(dto) = entity.system_diagnostics.as_ref() else {
+            bail!("System diagnostics missing from response");
+        };
+        let aggregate = dto
+            .aggregate_snapshot
+            .as_ref()
+            .map(Diagnostics::from_snapshot)
+            .unwrap_or_default();
+        let nodes = dto
+            .node_snapshots
+            .iter()
+            .map(|node| Diagnostics {
+                node_id: node.node_id.clone(),
+                address: node.address.clone(),
+                ..node
+                    .snapshot
+                    .as_ref()
+                    .map(Diagnostics::from_snapshot)
+                    .unwrap_or_default()
+            })
+            .collect();
+        Ok(Self { aggregate, nodes })
+    }
+}
+
+/// One attribute of an MBean.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct JmxAttribute {
+    pub bean_name: String,
+    pub attribute_name: String,
+    /// The value as NiFi serialized it (number, string, object...).
+    pub value: Value,
+}
+
+/// A service for reading system diagnostics.
+///
+/// This service is instantiated with shared (`Arc`) instances of `HttpClient` and `Config`.
+#[derive(Debug)]
+pub struct SystemDiagnostics {
+    client: Arc<HttpClient>,
+    config: Arc<Config>,
+}
+
+impl SystemDiagnostics {
+    /// Creates a new instance of the `SystemDiagnostics` service.
+    ///
+    /// # Arguments
+    ///
+    /// * `client` - The shared `HttpClient` to be used for requests.
+    /// * `config` - The application configuration (containing `api_base_url`).
+    pub fn new(client: Arc<HttpClient>, config: Arc<Config>) -> Self {
+        Self { client, config }
+    }
+
+    /// Retrieves the system diagnostics, per node when `nodewise` is set.
+    ///
+    /// Sends a `GET` request to `/system-diagnostics`.
+    ///
+    /// # Errors
+    /// Returns `HttpClientError` if the request fails.
+    pub async fn get_system_diagnostics(
+        &self,
+        nodewise: bool,
+    ) -> anyhow::Result<SystemDiagnosticsEntity> {
+        let url = format!(
+            "{}/system-diagnostics?nodewise={}",
+            self.config.api_base_url, nodewise
+        );
+        Ok(self
+            .client
+            .get_json::<SystemDiagnosticsEntity>(&url)
+            .await?)
+    }
+
+    /// Retrieves the system diagnostics as typed results.
+    ///
+    /// # Errors
+    /// Returns an error if the request fails or the response has no diagnostics.
+    pub async fn diagnostics_report(&self, nodewise: bool) -> anyhow::Result<DiagnosticsReport> {
+        let entity = self.get_system_diagnostics(nodewise).await?;
+        DiagnosticsReport::from_entity(&entity)
+    }
+
+    /// Retrieves the JMX metrics, optionally filtered by a bean name regular
+    /// expression. Only beans allowed by `nifi.jmx.metrics.allowed.filter.pattern`
+    /// are returned.
+    ///
+    /// Sends a `GET` request to `/system-diagnostics/jmx-metrics`.
+    ///
+    /// # Errors
+    /// Returns `HttpClientError` if the request fails.
+    pub async fn get_jmx_metrics(
+        &self,
+        bean_name_filter: Option<&str>,
+    ) -> anyhow::Result<Vec<JmxAttribute>> {
+        let base = format!(
+            "{}/system-diagnostics/jmx-metrics",
+            self.config.api_base_url
+        );
+        let url = match bean_name_filter {
+            Some(filter) => {
+                Url::parse_with_params(&base, [("beanNameFilter", filter)])?.to_string()
+            },
+            None => base,
+        };
+        // Attribute values are arbitrary JSON, which the generated
+        // `JmxMetricsResultDto` cannot hold.
+        let response = self.client.get_json::<Value>(&url).await?;
+        Ok(jmx_attributes(&response))
+    }
+}
+
+fn jmx_attributes(response: &Value) -> Vec<JmxAttribute> {
+    response["jmxMetricsResults"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|result| {
+            Some(JmxAttribute {
+                bean_name: result["beanName"].as_str()?.to_string(),
+                attribute_name: result["attributeName"].as_str()?.to_string(),
+                value: result["attributeValue"].clone(),
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_from_entity() {
+        let entity: SystemDiagnosticsEntity = serde_json::from_value(json!({
+            "systemDiagnostics": {
+                "aggregateSnapshot": {
+                    "usedHeapBytes": 900, "totalHeapBytes": 1000, "maxHeapBytes": 1000,
+                    "usedNonHeapBytes": 50, "totalNonHeapBytes": 100, "maxNonHeapBytes": -1,
+                    "contentRepositoryStorageUsage": [
+                        {"identifier": "default", "usedSpaceBytes": 86, "totalSpaceBytes": 100}
+                    ],
+                    "processorLoadAverage": 6.0, "availableProcessors": 4,
+                },
+                "nodeSnapshots": [
+                    {"nodeId": "n1", "address": "nifi-1", "snapshot": {"usedHeapBytes": 1}}
+                ],
+            }
+        }))
+        .unwrap();
+
+        let diagnostics = DiagnosticsReport::from_entity(&entity).unwrap();
+
+        let aggregate = &diagnostics.aggregate;
+        assert_eq!(aggregate.heap.utilization(), Some(90.0));
+        assert_eq!(aggregate.non_heap.max_bytes, None);
+        assert_eq!(aggregate.non_heap.utilization(), Some(50.0));
+        assert_eq!(aggregate.content_repositories[0].utilization(), Some(86.0));
+        assert_eq!(aggregate.load_per_processor(), Some(1.5));
+        assert_eq!(diagnostics.nodes[0].node_id.as_deref(), Some("n1"));
+        assert_eq!(diagnostics.nodes[0].heap.used_bytes, 1);
+    }
+
+    #[test]
+    fn test_jmx_attributes() {
+        let response = json!({"jmxMetricsResults": [
+            {"beanName": "java.lang:type=Threading", "attributeName": "ThreadCount", "attributeValue": 42},
+            {"beanName": "broken"},
+        ]});
+
+        let attributes = jmx_attributes(&response);
+
+        assert_eq!(attributes.len(), 1);
+        assert_eq!(attributes[0].value, json!(42));
+    }
+}
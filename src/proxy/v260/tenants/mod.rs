@@ -0,0 +1,520 @@
+//! # Tenants Module
+//!
+//! Provides bindings for `/tenants`: users, user groups and tenant search.
+//!
+//! Besides the raw CRUD calls, the `Tenants` service looks tenants up by
+//! identity (the user name, or the group name) so provisioning scripts never
+//! have to deal with UUIDs:
+//!
+//! * `create_user` / `create_user_group` - create with a fresh revision.
+//! * `find_user` / `find_user_group` - exact identity match.
+//! * `add_members` / `remove_members` - update group membership by identity.
+//! * `delete_user_by_identity` / `delete_user_group_by_identity`.
+
+use crate::common::client::{HttpClient, JsonResponse};
+use crate::common::config::Config;
+use crate::proxy::v260::api::{
+    RevisionDto, TenantEntity, TenantsEntity, UserDto, UserEntity, UserGroupDto, UserGroupEntity,
+    UserGroupsEntity, UsersEntity,
+};
+use anyhow::bail;
+use reqwest::Url;
+use std::collections::BTreeSet;
+use std::sync::Arc;
+
+/// A service for managing users and user groups.
+///
+/// This service is instantiated with shared (`Arc`) instances of `HttpClient` and `Config`.
+#[derive(Debug)]
+pub struct Tenants {
+    client: Arc<HttpClient>,
+    config: Arc<Config>,
+}
+
+impl Tenants {
+    /// Creates a new instance of the `Tenants` service.
+    ///
+    /// # Arguments
+    ///
+    /// * `client` - The shared `HttpClient` to be used for requests.
+    /// * `config` - The application configuration (containing `api_base_url`).
+    pub fn new(client: Arc<HttpClient>, config: Arc<Config>) -> Self {
+        Self { client, config }
+    }
+
+    /// Retrieves all users.
+    ///
+    /// Sends a `GET` request to `/tenants/users`.
+    ///
+    /// # Errors
+    /// Returns `HttpClientError` if the request fails.
+    pub async fn get_users(&self) -> anyhow::Result<UsersEntity> {
+        let url = format!("{}/tenants/users", self.config.api_base_url);
+        Ok(self.client.get_json::<UsersEntity>(&url).await?)
+    }
+
+    /// Retrieves a user.
+    ///
+    /// Sends a `GET` request to `/tenants/users/{id}`.
+    ///
+    /// # Errors
+    /// Returns `HttpClientError` if the request fails.
+    pub async fn get_user(&self, id: &str) -> anyhow::Result<UserEntity> {
+        let url = format!("{}/tenants/users/{}", self.config.api_base_url, id);
+        Ok(self.client.get_json::<UserEntity>(&url).await?)
+    }
+
+    /// Creates a user.
+    ///
+    /// Sends a `POST` request to `/tenants/users`.
+    /// The `payload` must contain a revision with version `0`.
+    ///
+    /// # Errors
+    /// Returns `HttpClientError` if the request fails.
+    pub async fn post_user(&self, payload: &UserEntity) -> anyhow::Result<UserEntity> {
+        let url = format!("{}/tenants/users", self.config.api_base_url);
+        Ok(self
+            .client
+            .post_json::<UserEntity, UserEntity>(&url, payload)
+            .await?)
+    }
+
+    /// Updates a user.
+    ///
+    /// Sends a `PUT` request to `/tenants/users/{id}`.
+    /// The `payload` must contain the current revision.
+    ///
+    /// # Errors
+    /// Returns `HttpClientError` if the request fails (e.g., 409 Conflict on bad version).
+    pub async fn put_user(&self, id: &str, payload: &UserEntity) -> anyhow::Result<UserEntity> {
+        let url = format!("{}/tenants/users/{}", self.config.api_base_url, id);
+        Ok(self
+            .client
+            .put_json::<UserEntity, UserEntity>(&url, payload)
+            .await?)
+    }
+
+    /// Deletes a user at its current revision.
+    ///
+    /// Sends a `DELETE` request to `/tenants/users/{id}`.
+    ///
+    /// # Errors
+    /// Returns `HttpClientError` if the request fails.
+    pub async fn delete_user(&self, id: &str) -> anyhow::Result<UserEntity> {
+        let user = self.get_user(id).await?;
+        let url = delete_url(
+            &format!("{}/tenants/users/{}", self.config.api_base_url, id),
+            user.revision.as_ref(),
+        )?;
+        let response = self.client.delete::<JsonResponse<UserEntity>>(&url).await?;
+        Ok(response.0)
+    }
+
+    /// Retrieves all user groups.
+    ///
+    /// Sends a `GET` request to `/tenants/user-groups`.
+    ///
+    /// # Errors
+    /// Returns `HttpClientError` if the request fails.
+    pub async fn get_user_groups(&self) -> anyhow::Result<UserGroupsEntity> {
+        let url = format!("{}/tenants/user-groups", self.config.api_base_url);
+        Ok(self.client.get_json::<UserGroupsEntity>(&url).await?)
+    }
+
+    /// Retrieves a user group.
+    ///
+    /// Sends a `GET` request to `/tenants/user-groups/{id}`.
+    ///
+    /// # Errors
+    /// Returns `HttpClientError` if the request fails.
+    pub async fn get_user_group(&self, id: &str) -> anyhow::Result<UserGroupEntity> {
+        let url = format!("{}/tenants/user-groups/{}", self.config.api_base_url, id);
+        Ok(self.client.get_json::<UserGroupEntity>(&url).await?)
+    }
+
+    /// Creates a user group.
+    ///
+    /// Sends a `POST` request to `/tenants/user-groups`.
+    /// The `payload` must contain a revision with version `0`.
+    ///
+    /// # Errors
+    /// Returns `HttpClientError` if the request fails.
+    pub async fn post_user_group(
+        &self,
+        payload: &UserGroupEntity,
+    ) -> anyhow::Result<UserGroupEntity> {
+        let url = format!("{}/tenants/user-groups", self.config.api_base_url);
+        Ok(self
+            .client
+            .post_json::<UserGroupEntity, UserGroupEntity>(&url, payload)
+            .await?)
+    }
+
+    /// Updates a user group, including its members.
+    ///
+    /// Sends a `PUT` request to `/tenants/user-groups/{id}`.
+    /// The `payload` must contain the current revision.
+    ///
+    /// # Errors
+    /// Returns `HttpClientError` if the request fails (e.g., 409 Conflict on bad version).
+    pub async fn put_user_group(
+        &self,
+        id: &str,
+        payload: &UserGroupEntity,
+    ) -> anyhow::Result<UserGroupEntity> {
+        let url = format!("{}/tenants/user-groups/{}", self.config.api_base_url, id);
+        Ok(self
+            .client
+            .put_json::<UserGroupEntity, UserGroupEntity>(&url, payload)
+            .await?)
+    }
+
+    /// Deletes a user group at its current revision.
+    ///
+    /// Sends a `DELETE` request to `/tenants/user-groups/{id}`.
+    ///
+    /// # Errors
+    /// Returns `HttpClientError` if the request fails.
+    pub async fn delete_user_group(&self, id: &str) -> anyhow::Result<UserGroupEntity> {
+        let group = self.get_user_group(id).await?;
+        let url = delete_url(
+            &format!("{}/tenants/user-groups/{}", self.config.api_base_url, id),
+            group.revision.as_ref(),
+        )?;
+        let response = self
+            .client
+            .delete::<JsonResponse<UserGroupEntity>>(&url)
+            .await?;
+        Ok(response.0)
+    }
+
+    /// Searches users and user groups whose identity contains `query`.
+    ///
+    /// Sends a `GET` request to `/tenants/search-results`.
+    ///
+    /// # Errors
+    /// Returns `HttpClientError` if the request fails.
+    pub async fn search_tenants(&self, query: &str) -> anyhow::Result<TenantsEntity> {
+        let url = Url::parse_with_params(
+            &format!("{}/tenants/search-results", self.config.api_base_url),
+            [("q", query)],
+        )?;
+        Ok(self.client.get_json::<TenantsEntity>(url.as_str()).await?)
+    }
+
+    /// Finds the user with exactly this identity.
+    ///
+    /// # Errors
+    /// Returns an error if the request fails.
+    pub async fn find_user(&self, identity: &str) -> anyhow::Result<Option<UserEntity>> {
+        let users = self.get_users().await?.users;
+        Ok(users
+            .into_iter()
+            .find(|user| user_identity(user) == Some(identity)))
+    }
+
+    /// Finds the user group with exactly this identity (its name).
+    ///
+    /// # Errors
+    /// Returns an error if the request fails.
+    pub async fn find_user_group(&self, identity: &str) -> anyhow::Result<Option<UserGroupEntity>> {
+        let groups = self.get_user_groups().await?.user_groups;
+        Ok(groups
+            .into_iter()
+            .find(|group| group_identity(group) == Some(identity)))
+    }
+
+    /// Creates a user with the given identity.
+    ///
+    /// # Errors
+    /// Returns an error if the request fails (e.g., 409 Conflict when the
+    /// identity is taken).
+    pub async fn create_user(&self, identity: &str) -> anyhow::Result<UserEntity> {
+        let payload = UserEntity {
+            revision: Some(new_revision()),
+            component: Some(UserDto {
+                identity: Some(identity.to_string()),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        self.post_user(&payload).await
+    }
+
+    /// Creates a user group with the given identity and members, given by
+    /// user identity.
+    ///
+    /// # Errors
+    /// Returns an error if a member does not exist or a request fails.
+    pub async fn create_user_group(
+        &self,
+        identity: &str,
+        members: &[&str],
+    ) -> anyhow::Result<UserGroupEntity> {
+        let users = self.get_users().await?.users;
+        let member_ids = resolve_users(&users, members)?;
+        let payload = UserGroupEntity {
+            revision: Some(new_revision()),
+            component: Some(UserGroupDto {
+                identity: Some(identity.to_string()),
+                users: Some(member_ids.into_iter().map(tenant).collect()),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        self.post_user_group(&payload).await
+    }
+
+    /// Renames a user.
+    ///
+    /// # Errors
+    /// Returns an error if the user does not exist or a request fails.
+    pub async fn rename_user(
+        &self,
+        identity: &str,
+        new_identity: &str,
+    ) -> anyhow::Result<UserEntity> {
+        let Some(user) = self.find_user(identity).await? else {
+            bail!("User {} does not exist", identity);
+        };
+        let Some(id) = user.id.clone() else {
+            bail!("User {} has no id", identity);
+        };
+        let payload = UserEntity {
+            revision: user.revision,
+            component: Some(UserDto {
+                id: Some(id.clone()),
+                identity: Some(new_identity.to_string()),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        self.put_user(&id, &payload).await
+    }
+
+    /// Adds users, given by identity, to a group. Users that are already
+    /// members are left alone; nothing is sent when all of them are.
+    ///
+    /// # Errors
+    /// Returns an error if the group or a user does not exist, or a request fails.
+    pub async fn add_members(
+        &self,
+        group: &str,
+        members: &[&str],
+    ) -> anyhow::Result<UserGroupEntity> {
+        self.update_members(group, members, true).await
+    }
+
+    /// Removes users, given by identity, from a group. Users that are not
+    /// members are ignored.
+    ///
+    /// # Errors
+    /// Returns an error if the group or a user does not exist, or a request fails.
+    pub async fn remove_members(
+        &self,
+        group: &str,
+        members: &[&str],
+    ) -> anyhow::Result<UserGroupEntity> {
+        self.update_members(group, members, false).await
+    }
+
+    /// Deletes the user with this identity.
+    ///
+    /// # Errors
+    /// Returns an error if the user does not exist or a request fails.
+    pub async fn delete_user_by_identity(&self, identity: &str) -> anyhow::Result<UserEntity> {
+        match self.find_user(identity).await?.and_then(|user| user.id) {
+            Some(id) => self.delete_user(&id).await,
+            None => bail!("User {} does not exist", identity),
+        }
+    }
+
+    /// Deletes the user group with this identity.
+    ///
+    /// # Errors
+    /// Returns an error if the group does not exist or a request fails.
+    pub async fn delete_user_group_by_identity(
+        &self,
+        identity: &str,
+    ) -> anyhow::Result<UserGroupEntity> {
+        match self
+            .find_user_group(identity)
+            .await?
+            .and_then(|group| group.id)
+        {
+            Some(id) => self.delete_user_group(&id).await,
+            None => bail!("User group {} does not exist", identity),
+        }
+    }
+
+    async fn update_members(
+        &self,
+        group: &str,
+        members: &[&str],
+        add: bool,
+    ) -> anyhow::Result<UserGroupEntity> {
+        let Some(entity) = self.find_user_group(group).await? else {
+            bail!("User group {} does not exist", group);
+        };
+        let Some(id) = entity.id.clone() else {
+            bail!("User group {} has no id", group);
+        };
+        let users = self.get_users().await?.users;
+        let changed = resolve_users(&users, members)?;
+
+        let current = member_ids(&entity);
+        let updated: BTreeSet<String> = if add {
+            current.union(&changed).cloned().collect()
+        } else {
+            current.difference(&changed).cloned().collect()
+        };
+        if updated == current {
+            return Ok(entity);
+        }
+
+        let payload = UserGroupEntity {
+            revision: entity.revision,
+            component: Some(UserGroupDto {
+                id: Some(id.clone()),
+                identity: Some(group.to_string()),
+                users: Some(updated.into_iter().map(tenant).collect()),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        self.put_user_group(&id, &payload).await
+    }
+}
+
+/// The identity of a user.
+pub fn user_identity(user: &UserEntity) -> Option<&str> {
+    user.component.as_ref()?.identity.as_deref()
+}
+
+/// The identity (name) of a user group.
+pub fn group_identity(group: &UserGroupEntity) -> Option<&str> {
+    group.component.as_ref()?.identity.as_deref()
+}
+
+/// The ids of the members of a group.
+pub fn member_ids(group: &UserGroupEntity) -> BTreeSet<String> {
+    group
+        .component
+        .as_ref()
+        .and_then(|component| component.users.as_ref())
+        .map(|users| users.iter().filter_map(|user| user.id.clone()).collect())
+        .unwrap_or_default()
+}
+
+/// Maps user identities to ids, failing with every unknown identity.
+fn resolve_users(users: &[UserEntity], identities: &[&str]) -> anyhow::Result<BTreeSet<String>> {
+    let mut ids = BTreeSet::new();
+    let mut unknown = Vec::new();
+    for identity in identities {
+        match users
+            .iter()
+            .find(|user| user_identity(user) == Some(identity))
+            .and_then(|user| user.id.clone())
+        {
+            Some(id) => {
+                ids.insert(id);
+            },
+            None => unknown.push(*identity),
+        }
+    }
+    if !unknown.is_empty() {
+        bail!("Unknown users: {}", unknown.join(", "));
+    }
+    Ok(ids)
+}
+
+fn tenant(id: String) -> TenantEntity {
+    TenantEntity {
+        id: Some(id),
+        ..Default::default()
+    }
+}
+
+fn new_revision() -> RevisionDto {
+    RevisionDto {
+        client_id: None,
+        last_modifier: None,
+        version: Some(0),
+    }
+}
+
+fn delete_url(base: &str, revision: Option<&RevisionDto>) -> anyhow::Result<String> {
+    let Some(version) = revision.and_then(|revision| revision.version) else {
+        bail!("Revision was None");
+    };
+    let url = Url::parse_with_params(base, [("version", version.to_string())])?;
+    Ok(url.to_string())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::proxy::v260::access::Access;
+    use tracing_test::traced_test;
+
+    fn user(id: &str, identity: &str) -> UserEntity {
+        UserEntity {
+            id: Some(id.to_string()),
+            component: Some(UserDto {
+                id: Some(id.to_string()),
+                identity: Some(identity.to_string()),
+                ..Default::default()
+            }),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_resolve_users() {
+        let users = vec![user("1", "alice"), user("2", "bob")];
+
+        let ids = resolve_users(&users, &["bob", "alice"]).unwrap();
+        assert_eq!(ids, BTreeSet::from(["1".to_string(), "2".to_string()]));
+
+        let err = resolve_users(&users, &["alice", "carol", "dave"]).unwrap_err();
+        assert_eq!(err.to_string(), "Unknown users: carol, dave");
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    async fn test_group_membership() {
+        // --- 1. Setup ---
+        let client = Arc::new(HttpClient::new());
+        let config = Arc::new(Config::default());
+        let access = Access::new(client.clone(), config.clone());
+        let _ = access.get_access_token().await;
+        let tenants = Tenants::new(client.clone(), config.clone());
+        let user_name = uuid::Uuid::new_v4().to_string();
+        let group_name = uuid::Uuid::new_v4().to_string();
+
+        // --- 2. Create a user and an empty group ---
+        let created = tenants.create_user(&user_name).await;
+        assert!(created.is_ok(), "create_user call error: {:?}", created);
+        let group = tenants.create_user_group(&group_name, &[]).await;
+        assert!(group.is_ok(), "create_user_group call error: {:?}", group);
+
+        // --- 3. Add and remove the member ---
+        let group = tenants
+            .add_members(&group_name, &[&user_name])
+            .await
+            .unwrap();
+        assert_eq!(member_ids(&group).len(), 1);
+        let group = tenants
+            .remove_members(&group_name, &[&user_name])
+            .await
+            .unwrap();
+        assert!(member_ids(&group).is_empty());
+
+        // --- 4. Cleanup ---
+        tenants
+            .delete_user_group_by_identity(&group_name)
+            .await
+            .unwrap();
+        tenants.delete_user_by_identity(&user_name).await.unwrap();
+    }
+}
@@ -0,0 +1,359 @@
+//! # Verification Module
+//!
+//! Provides bindings for configuration verification, which NiFi offers for
+//! several component kinds under the same shape of endpoint:
+//!
+//! * `/{kind}/{id}/config/verification-requests[/{requestId}]` - submission,
+//!   progress and deletion of a verification request, where `kind` is one of
+//!   `processors`, `controller-services`, `parameter-providers`,
+//!   `reporting-tasks` or `controller/flow-analysis-rules`.
+//!
+//! `verify` runs the whole request (submit, poll, delete) and maps the
+//! per-step `ConfigVerificationResultDto`s to typed `VerificationStep`s, e.g.
+//! to confirm that a database connection pool can connect before enabling it.
+//! NiFi only verifies components that are stopped or disabled.
+
+use crate::common::client::{HttpClient, JsonResponse};
+use crate::common::config::Config;
+use crate::common::polling::{PollOptions, RequestStatus, run_async_request};
+use crate::proxy::v260::api::{
+    ConfigVerificationResultDto, ConfigVerificationResultDtoOutcome, VerifyConfigRequestDto,
+    VerifyConfigRequestEntity,
+};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Arc;
+use thiserror::Error;
+
+/// A kind of component whose configuration NiFi can verify.
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum VerifiableComponent {
+    Processor,
+    ControllerService,
+    ParameterProvider,
+    ReportingTask,
+    FlowAnalysisRule,
+}
+
+impl VerifiableComponent {
+    /// The path of the components of this kind, relative to the API base URL.
+    pub fn path(&self) -> &'static str {
+        match self {
+            VerifiableComponent::Processor => "processors",
+            VerifiableComponent::ControllerService => "controller-services",
+            VerifiableComponent::ParameterProvider => "parameter-providers",
+            VerifiableComponent::ReportingTask => "reporting-tasks",
+            VerifiableComponent::FlowAnalysisRule => "controller/flow-analysis-rules",
+        }
+    }
+}
+
+/// The outcome of one verification step.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct VerificationStep {
+    pub name: String,
+    /// `None` when NiFi did not report an outcome.
+    pub outcome: Option<ConfigVerificationResultDtoOutcome>,
+    pub explanation: Option<String>,
+}
+
+impl VerificationStep {
+    pub fn from_dto(dto: &ConfigVerificationResultDto) -> Self {
+        Self {
+            name: dto.verification_step_name.clone().unwrap_or_default(),
+            outcome: dto.outcome,
+            explanation: dto.explanation.clone(),
+        }
+    }
+
+    pub fn is_failed(&self) -> bool {
+        self.outcome == Some(ConfigVerificationResultDtoOutcome::Failed)
+    }
+}
+
+impl fmt::Display for VerificationStep {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let outcome = match self.outcome {
+            Some(ConfigVerificationResultDtoOutcome::Successful) => "SUCCESSFUL",
+            Some(ConfigVerificationResultDtoOutcome::Failed) => "FAILED",
+            Some(ConfigVerificationResultDtoOutcome::Skipped) => "SKIPPED",
+            None => "UNKNOWN",
+        };
+        write!(f, "[{}] {}", outcome, self.name)?;
+        if let Some(explanation) = &self.explanation {
+            write!(f, ": {}", explanation)?;
+        }
+        Ok(())
+    }
+}
+
+/// The steps of a finished verification request.
+#[derive(Serialize, Debug, Clone, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct VerificationResults {
+    pub component_id: String,
+    pub steps: Vec<VerificationStep>,
+}
+
+impl VerificationResults {
+    pub fn from_dto(component_id: &str, dto: &VerifyConfigRequestDto) -> Self {
+        Self {
+            component_id: component_id.to_string(),
+            steps: dto.results.iter().map(VerificationStep::from_dto).collect(),
+        }
+    }
+
+    /// The steps that failed.
+    pub fn failures(&self) -> impl Iterator<Item = &VerificationStep> {
+        self.steps.iter().filter(|step| step.is_failed())
+    }
+
+    /// `true` when no step failed. Skipped steps do not count as failures.
+    pub fn is_successful(&self) -> bool {
+        self.failures().next().is_none()
+    }
+
+    /// Fails when a step failed.
+    pub fn check(&self) -> Result<(), VerificationError> {
+        let failures: Vec<VerificationStep> = self.failures().cloned().collect();
+        if failures.is_empty() {
+            Ok(())
+        } else {
+            Err(VerificationError::FailedSteps {
+                component_id: self.component_id.clone(),
+                failures,
+            })
+        }
+    }
+}
+
+/// Represents the ways a verified configuration can be rejected.
+#[derive(Debug, Error)]
+pub enum VerificationError {
+    #[error(
+        "VerificationError::FailedSteps - {component_id}: {}",
+        .failures.iter().map(|step| step.to_string()).collect::<Vec<_>>().join("; ")
+    )]
+    FailedSteps {
+        component_id: String,
+        failures: Vec<VerificationStep>,
+    },
+}
+
+/// A service for verifying the configuration of components of any
+/// `VerifiableComponent` kind.
+///
+/// This service is instantiated with shared (`Arc`) instances of `HttpClient` and `Config`.
+#[derive(Debug)]
+pub struct Verification {
+    client: Arc<HttpClient>,
+    config: Arc<Config>,
+}
+
+impl Verification {
+    /// Creates a new instance of the `Verification` service.
+    ///
+    /// # Arguments
+    ///
+    /// * `client` - The shared `HttpClient` to be used for requests.
+    /// * `config` - The application configuration (containing `api_base_url`).
+    pub fn new(client: Arc<HttpClient>, config: Arc<Config>) -> Self {
+        Self { client, config }
+    }
+
+    fn requests_url(&self, kind: VerifiableComponent, id: &str) -> String {
+        format!(
+            "{}/{}/{}/config/verification-requests",
+            self.config.api_base_url,
+            kind.path(),
+            id
+        )
+    }
+
+    /// Submits a verification request.
+    ///
+    /// Sends a `POST` request to `/{kind}/{id}/config/verification-requests`.
+    ///
+    /// # Errors
+    /// Returns `HttpClientError` if the request fails.
+    pub async fn post_verification_request(
+        &self,
+        kind: VerifiableComponent,
+        id: &str,
+        payload: &VerifyConfigRequestEntity,
+    ) -> anyhow::Result<VerifyConfigRequestEntity> {
+        let url = self.requests_url(kind, id);
+        Ok(self
+            .client
+            .post_json::<VerifyConfigRequestEntity, VerifyConfigRequestEntity>(&url, payload)
+            .await?)
+    }
+
+    /// Retrieves the progress of a verification request.
+    ///
+    /// Sends a `GET` request to `/{kind}/{id}/config/verification-requests/{requestId}`.
+    ///
+    /// # Errors
+    /// Returns `HttpClientError` if the request fails.
+    pub async fn get_verification_request(
+        &self,
+        kind: VerifiableComponent,
+        id: &str,
+        request_id: &str,
+    ) -> anyhow::Result<VerifyConfigRequestEntity> {
+        let url = format!("{}/{}", self.requests_url(kind, id), request_id);
+        Ok(self
+            .client
+            .get_json::<VerifyConfigRequestEntity>(&url)
+            .await?)
+    }
+
+    /// Deletes a verification request.
+    ///
+    /// Sends a `DELETE` request to `/{kind}/{id}/config/verification-requests/{requestId}`.
+    ///
+    /// # Errors
+    /// Returns `HttpClientError` if the request fails.
+    pub async fn delete_verification_request(
+        &self,
+        kind: VerifiableComponent,
+        id: &str,
+        request_id: &str,
+    ) -> anyhow::Result<VerifyConfigRequestEntity> {
+        let url = format!("{}/{}", self.requests_url(kind, id), request_id);
+        let response = self
+            .client
+            .delete::<JsonResponse<VerifyConfigRequestEntity>>(&url)
+            .await?;
+        Ok(response.0)
+    }
+
+    /// Verifies a configuration of a component and waits for the results.
+    ///
+    /// Runs the whole verification-request flow: submits `properties` (with
+    /// `attributes` for evaluating Expression Language), polls the request
+    /// until it completes and deletes it afterwards. A failed step is part of
+    /// the results, not an error; use `VerificationResults::check` for that.
+    ///
+    /// # Errors
+    /// Returns an error if any request fails, if NiFi reports a failure reason
+    /// (e.g. the component is running), or if the request does not complete
+    /// within `poll.timeout`.
+    pub async fn verify(
+        &self,
+        kind: VerifiableComponent,
+        id: &str,
+        properties: HashMap<String, Option<String>>,
+        attributes: HashMap<String, Option<String>>,
+        poll: PollOptions,
+    ) -> anyhow::Result<VerificationResults> {
+        let request = run_async_request(
+            &format!("Verification request for {}", id),
+            poll,
+            async {
+                let submitted = self
+                    .post_verification_request(
+                        kind,
+                        id,
+                        &VerifyConfigRequestEntity {
+                            request: Some(VerifyConfigRequestDto {
+                                component_id: Some(id.to_string()),
+                                properties,
+                                attributes,
+                                ..Default::default()
+                            }),
+                        },
+                    )
+                    .await?;
+                Ok(submitted.request.and_then(|request| request.request_id))
+            },
+            |request_id| async move {
+                Ok(self
+                    .get_verification_request(kind, id, &request_id)
+                    .await?
+                    .request
+                    .unwrap_or_default())
+            },
+            |request_id| async move {
+                self.delete_verification_request(kind, id, &request_id)
+                    .await
+            },
+        )
+        .await?;
+        Ok(VerificationResults::from_dto(id, &request))
+    }
+}
+
+impl RequestStatus for VerifyConfigRequestDto {
+    fn is_finished(&self) -> bool {
+        self.complete.unwrap_or(false)
+    }
+
+    fn failure_reason(&self) -> Option<&str> {
+        self.failure_reason.as_deref()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_verification_results() {
+        // --- 1. Setup: a database check that connected, but skipped a query ---
+        let dto = VerifyConfigRequestDto {
+            complete: Some(true),
+            results: vec![
+                ConfigVerificationResultDto {
+                    verification_step_name: Some("Establish Connection".to_string()),
+                    outcome: Some(ConfigVerificationResultDtoOutcome::Successful),
+                    explanation: None,
+                },
+                ConfigVerificationResultDto {
+                    verification_step_name: Some("Run Query".to_string()),
+                    outcome: Some(ConfigVerificationResultDtoOutcome::Skipped),
+                    explanation: Some("No validation query configured".to_string()),
+                },
+            ],
+            ..Default::default()
+        };
+
+        // --- 2. Skipped steps are not failures ---
+        let mut results = VerificationResults::from_dto("dbcp-1", &dto);
+        assert!(results.is_successful());
+        assert!(results.check().is_ok());
+
+        // --- 3. A failed step makes the results fail ---
+        results.steps[0].outcome = Some(ConfigVerificationResultDtoOutcome::Failed);
+        assert!(!results.is_successful());
+        match results.check() {
+            Err(VerificationError::FailedSteps { failures, .. }) => {
+                assert_eq!(failures, vec![results.steps[0].clone()])
+            },
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_failure_reason_is_an_error() {
+        // NiFi refuses to verify a running processor with a failure reason.
+        let result = run_async_request(
+            "Verification request for p1",
+            PollOptions::default(),
+            async { Ok(Some("request-1".to_string())) },
+            |_| async {
+                Ok(VerifyConfigRequestDto {
+                    complete: Some(true),
+                    failure_reason: Some("Processor is running".to_string()),
+                    ..Default::default()
+                })
+            },
+            |_| async { Ok(()) },
+        )
+        .await;
+
+        assert!(result.is_err());
+    }
+}
@@ -0,0 +1,157 @@
+//! Differences between two versions of a registry flow.
+//!
+//! `Versions::diff_versions` compares two `FlowVersion` coordinates of the same
+//! registry (possibly on different branches, buckets or flows) and returns a
+//! `VersionDiff`, which can be rendered as unified-diff text for release notes.
+
+use crate::proxy::v260::api::FlowComparisonEntity;
+use crate::proxy::v260::versions::modifications::{ComponentChange, LocalModifications};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// The coordinates of one version of a flow inside a registry.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct FlowVersion {
+    pub branch: String,
+    pub bucket_id: String,
+    pub flow_id: String,
+    pub version: String,
+}
+
+impl fmt::Display for FlowVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}/{}/{}@{}",
+            self.branch, self.bucket_id, self.flow_id, self.version
+        )
+    }
+}
+
+/// The components that differ between two versions of a flow.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct VersionDiff {
+    pub registry_id: String,
+    pub from: FlowVersion,
+    pub to: FlowVersion,
+    /// Components present in `to` only.
+    pub added: Vec<ComponentChange>,
+    /// Components present in `from` only.
+    pub removed: Vec<ComponentChange>,
+    /// Components present in both, with their differences.
+    pub modified: Vec<ComponentChange>,
+}
+
+impl VersionDiff {
+    /// Types the differences returned by the registry diff endpoint.
+    pub fn from_comparison(
+        registry_id: &str,
+        from: &FlowVersion,
+        to: &FlowVersion,
+        comparison: &FlowComparisonEntity,
+    ) -> Self {
+        let LocalModifications {
+            added,
+            removed,
+            modified,
+        } = LocalModifications::from_comparison(comparison);
+        Self {
+            registry_id: registry_id.to_string(),
+            from: from.clone(),
+            to: to.clone(),
+            added,
+            removed,
+            modified,
+        }
+    }
+
+    /// `true` when both versions are equivalent.
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.modified.is_empty()
+    }
+
+    /// Renders the differences as unified-diff text: one hunk per component,
+    /// `-` lines for the old values and `+` lines for the new ones.
+    pub fn unified(&self) -> String {
+        let mut out = format!("--- {}\n+++ {}\n", self.from, self.to);
+        for (sign, changes) in [('+', &self.added), ('-', &self.removed)] {
+            for change in changes {
+                out.push_str(&format!("@@ {} @@\n", header(change)));
+                out.push_str(&format!("{}{}\n", sign, header(change)));
+            }
+        }
+        for change in &self.modified {
+            out.push_str(&format!("@@ {} @@\n", header(change)));
+            for property in &change.properties {
+                match &property.name {
+                    Some(name) if property.from.is_some() || property.to.is_some() => {
+                        if let Some(from) = &property.from {
+                            out.push_str(&format!("-{}: {}\n", name, from));
+                        }
+                        if let Some(to) = &property.to {
+                            out.push_str(&format!("+{}: {}\n", name, to));
+                        }
+                    },
+                    _ => out.push_str(&format!(" {}\n", property.description)),
+                }
+            }
+            for other in &change.other {
+                out.push_str(&format!(" {}\n", other.description));
+            }
+        }
+        out
+    }
+}
+
+fn header(change: &ComponentChange) -> String {
+    format!(
+        "{} {} ({})",
+        change.component_type.as_deref().unwrap_or("Component"),
+        change.component_name.as_deref().unwrap_or("-"),
+        change.component_id.as_deref().unwrap_or("-"),
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::proxy::v260::api::{ComponentDifferenceDto, DifferenceDto};
+
+    fn version(version: &str) -> FlowVersion {
+        FlowVersion {
+            branch: "main".to_string(),
+            bucket_id: "bucket".to_string(),
+            flow_id: "flow".to_string(),
+            version: version.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_unified_diff() {
+        let comparison = FlowComparisonEntity {
+            component_differences: Some(vec![ComponentDifferenceDto {
+                component_id: Some("p1".to_string()),
+                component_name: Some("fetch".to_string()),
+                component_type: Some("Processor".to_string()),
+                differences: vec![DifferenceDto {
+                    difference_type: Some("Property Value Changed".to_string()),
+                    difference: Some(
+                        "Property 'URL' was changed from 'http://a' to 'http://b'".to_string(),
+                    ),
+                }],
+                process_group_id: None,
+            }]),
+        };
+
+        let diff =
+            VersionDiff::from_comparison("registry", &version("1"), &version("2"), &comparison);
+
+        assert_eq!(
+            diff.unified(),
+            "--- main/bucket/flow@1\n+++ main/bucket/flow@2\n\
+             @@ Processor fetch (p1) @@\n-URL: http://a\n+URL: http://b\n"
+        );
+    }
+}
@@ -0,0 +1,904 @@
+//! # Versions Module
+//!
+//! Provides high-level bindings for flow registries and version control of
+//! process groups:
+//!
+//! * `/controller/registry-clients` - the registry clients configured in NiFi.
+//! * `/flow/registries/...` - browsing registries: branches, buckets, flows and
+//!   the versions of a flow.
+//! * `/versions/process-groups/{id}` - starting and stopping version control,
+//!   committing new versions and downloading the versioned snapshot.
+//! * `/versions/update-requests` and `/versions/revert-requests` - the async
+//!   flows to change the version of a group and to revert its local
+//!   modifications, wrapped by `change_version` and `revert_local_modifications`.
+//! * `/flow/registries/.../diff/...` - the differences between two versions of
+//!   a flow, as a `diff::VersionDiff`.
+//! * `/process-groups/{id}/local-modifications` - what changed since the last
+//!   commit, as a `modifications::LocalModifications` report.
+
+use crate::common::client::{HttpClient, JsonResponse};
+use crate::common::config::Config;
+use crate::common::polling::{PollOptions, RequestStatus, run_async_request};
+use crate::proxy::v260::api::{
+    FlowComparisonEntity, FlowRegistryBranchesEntity, FlowRegistryBucketsEntity,
+    FlowRegistryClientEntity, FlowRegistryClientsEntity, RegisteredFlowSnapshot,
+    StartVersionControlRequestEntity, VersionControlInformationEntity, VersionedFlowDto,
+    VersionedFlowDtoAction, VersionedFlowEntity, VersionedFlowSnapshotEntity,
+    VersionedFlowSnapshotMetadataSetEntity, VersionedFlowUpdateRequestDto,
+    VersionedFlowUpdateRequestEntity, VersionedFlowsEntity,
+};
+use crate::proxy::v260::process_group::ProcessGroup;
+use crate::proxy::v260::versions::diff::{FlowVersion, VersionDiff};
+use crate::proxy::v260::versions::modifications::LocalModifications;
+use anyhow::bail;
+use reqwest::Url;
+use std::sync::Arc;
+
+pub mod diff;
+pub mod modifications;
+
+/// The two async request flows sharing `VersionedFlowUpdateRequestEntity`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum RequestKind {
+    Update,
+    Revert,
+}
+
+impl RequestKind {
+    fn path(self) -> &'static str {
+        match self {
+            RequestKind::Update => "update-requests",
+            RequestKind::Revert => "revert-requests",
+        }
+    }
+}
+
+/// A service for interacting with flow registries and version control.
+///
+/// This service is instantiated with shared (`Arc`) instances of `HttpClient` and `Config`.
+#[derive(Debug)]
+pub struct Versions {
+    client: Arc<HttpClient>,
+    config: Arc<Config>,
+}
+
+impl Versions {
+    /// Creates a new instance of the `Versions` service.
+    ///
+    /// # Arguments
+    ///
+    /// * `client` - The shared `HttpClient` to be used for requests.
+    /// * `config` - The application configuration (containing `api_base_url`).
+    pub fn new(client: Arc<HttpClient>, config: Arc<Config>) -> Self {
+        Self { client, config }
+    }
+
+    /// Retrieves the registry clients configured in NiFi.
+    ///
+    /// Sends a `GET` request to `/controller/registry-clients`.
+    ///
+    /// # Errors
+    /// Returns `HttpClientError` if the request fails.
+    pub async fn get_registry_clients(&self) -> anyhow::Result<FlowRegistryClientsEntity> {
+        let response = self
+            .client
+            .get_json::<FlowRegistryClientsEntity>(&format!(
+                "{}/controller/registry-clients",
+                self.config.api_base_url
+            ))
+            .await?;
+        Ok(response)
+    }
+
+    /// Retrieves a registry client by its ID.
+    ///
+    /// Sends a `GET` request to `/controller/registry-clients/{id}`.
+    ///
+    /// # Errors
+    /// Returns `HttpClientError` if the request fails (e.g., 404 Not Found).
+    pub async fn get_registry_client(&self, id: &str) -> anyhow::Result<FlowRegistryClientEntity> {
+        let response = self
+            .client
+            .get_json::<FlowRegistryClientEntity>(&format!(
+                "{}/controller/registry-clients/{}",
+                self.config.api_base_url, id
+            ))
+            .await?;
+        Ok(response)
+    }
+
+    /// Creates a registry client.
+    ///
+    /// Sends a `POST` request to `/controller/registry-clients`. The `payload`
+    /// must carry a revision with version `0`.
+    ///
+    /// # Errors
+    /// Returns `HttpClientError` if the request fails.
+    pub async fn post_registry_client(
+        &self,
+        payload: &FlowRegistryClientEntity,
+    ) -> anyhow::Result<FlowRegistryClientEntity> {
+        let response = self
+            .client
+            .post_json::<FlowRegistryClientEntity, FlowRegistryClientEntity>(
+                &format!("{}/controller/registry-clients", self.config.api_base_url),
+                payload,
+            )
+            .await?;
+        Ok(response)
+    }
+
+    /// Updates a registry client.
+    ///
+    /// Sends a `PUT` request to `/controller/registry-clients/{id}`. The
+    /// `payload` must contain the current revision.
+    ///
+    /// # Errors
+    /// Returns `HttpClientError` if the request fails (e.g., 409 Conflict on bad version).
+    pub async fn put_registry_client(
+        &self,
+        id: &str,
+        payload: &FlowRegistryClientEntity,
+    ) -> anyhow::Result<FlowRegistryClientEntity> {
+        let response = self
+            .client
+            .put_json::<FlowRegistryClientEntity, FlowRegistryClientEntity>(
+                &format!(
+                    "{}/controller/registry-clients/{}",
+                    self.config.api_base_url, id
+                ),
+                payload,
+            )
+            .await?;
+        Ok(response)
+    }
+
+    /// Deletes a registry client, reading its current revision first.
+    ///
+    /// Sends a `DELETE` request to `/controller/registry-clients/{id}`.
+    ///
+    /// # Errors
+    /// Returns `HttpClientError` if the request fails.
+    pub async fn delete_registry_client(
+        &self,
+        id: &str,
+    ) -> anyhow::Result<FlowRegistryClientEntity> {
+        let existing = self.get_registry_client(id).await?;
+        let Some(version) = existing.revision.and_then(|revision| revision.version) else {
+            bail!("Revision was None");
+        };
+        let response = self
+            .client
+            .delete::<JsonResponse<FlowRegistryClientEntity>>(&format!(
+                "{}/controller/registry-clients/{}?version={}",
+                self.config.api_base_url, id, version
+            ))
+            .await?;
+        Ok(response.0)
+    }
+
+    /// Retrieves the registries the current user can browse.
+    ///
+    /// Sends a `GET` request to `/flow/registries`.
+    ///
+    /// # Errors
+    /// Returns `HttpClientError` if the request fails.
+    pub async fn get_registries(&self) -> anyhow::Result<FlowRegistryClientsEntity> {
+        let response = self
+            .client
+            .get_json::<FlowRegistryClientsEntity>(&format!(
+                "{}/flow/registries",
+                self.config.api_base_url
+            ))
+            .await?;
+        Ok(response)
+    }
+
+    /// Retrieves the branches of a registry.
+    ///
+    /// Sends a `GET` request to `/flow/registries/{id}/branches`.
+    ///
+    /// # Errors
+    /// Returns `HttpClientError` if the request fails (e.g., the registry does
+    /// not support branching).
+    pub async fn get_branches(
+        &self,
+        registry_id: &str,
+    ) -> anyhow::Result<FlowRegistryBranchesEntity> {
+        let response = self
+            .client
+            .get_json::<FlowRegistryBranchesEntity>(&format!(
+                "{}/flow/registries/{}/branches",
+                self.config.api_base_url, registry_id
+            ))
+            .await?;
+        Ok(response)
+    }
+
+    /// Retrieves the buckets of a registry, on `branch` or the default branch.
+    ///
+    /// Sends a `GET` request to `/flow/registries/{id}/buckets`.
+    ///
+    /// # Errors
+    /// Returns `HttpClientError` if the request fails.
+    pub async fn get_buckets(
+        &self,
+        registry_id: &str,
+        branch: Option<&str>,
+    ) -> anyhow::Result<FlowRegistryBucketsEntity> {
+        let url = with_branch(
+            format!(
+                "{}/flow/registries/{}/buckets",
+                self.config.api_base_url, registry_id
+            ),
+            branch,
+        )?;
+        let response = self
+            .client
+            .get_json::<FlowRegistryBucketsEntity>(&url)
+            .await?;
+        Ok(response)
+    }
+
+    /// Retrieves the flows of a bucket.
+    ///
+    /// Sends a `GET` request to `/flow/registries/{registry_id}/buckets/{bucket_id}/flows`.
+    ///
+    /// # Errors
+    /// Returns `HttpClientError` if the request fails.
+    pub async fn get_flows(
+        &self,
+        registry_id: &str,
+        bucket_id: &str,
+        branch: Option<&str>,
+    ) -> anyhow::Result<VersionedFlowsEntity> {
+        let url = with_branch(
+            format!(
+                "{}/flow/registries/{}/buckets/{}/flows",
+                self.config.api_base_url, registry_id, bucket_id
+            ),
+            branch,
+        )?;
+        let response = self.client.get_json::<VersionedFlowsEntity>(&url).await?;
+        Ok(response)
+    }
+
+    /// Retrieves the details of a flow.
+    ///
+    /// Sends a `GET` request to
+    /// `/flow/registries/{registry_id}/buckets/{bucket_id}/flows/{flow_id}/details`.
+    ///
+    /// # Errors
+    /// Returns `HttpClientError` if the request fails.
+    pub async fn get_flow(
+        &self,
+        registry_id: &str,
+        bucket_id: &str,
+        flow_id: &str,
+        branch: Option<&str>,
+    ) -> anyhow::Result<VersionedFlowEntity> {
+        let url = with_branch(
+            format!(
+                "{}/flow/registries/{}/buckets/{}/flows/{}/details",
+                self.config.api_base_url, registry_id, bucket_id, flow_id
+            ),
+            branch,
+        )?;
+        let response = self.client.get_json::<VersionedFlowEntity>(&url).await?;
+        Ok(response)
+    }
+
+    /// Retrieves the metadata of every version of a flow.
+    ///
+    /// Sends a `GET` request to
+    /// `/flow/registries/{registry_id}/buckets/{bucket_id}/flows/{flow_id}/versions`.
+    ///
+    /// # Errors
+    /// Returns `HttpClientError` if the request fails.
+    pub async fn get_flow_versions(
+        &self,
+        registry_id: &str,
+        bucket_id: &str,
+        flow_id: &str,
+        branch: Option<&str>,
+    ) -> anyhow::Result<VersionedFlowSnapshotMetadataSetEntity> {
+        let url = with_branch(
+            format!(
+                "{}/flow/registries/{}/buckets/{}/flows/{}/versions",
+                self.config.api_base_url, registry_id, bucket_id, flow_id
+            ),
+            branch,
+        )?;
+        let response = self
+            .client
+            .get_json::<VersionedFlowSnapshotMetadataSetEntity>(&url)
+            .await?;
+        Ok(response)
+    }
+
+    /// Compares two versions of registry flows.
+    ///
+    /// Sends a `GET` request to
+    /// `/flow/registries/{registry_id}/branches/{a}/buckets/{a}/flows/{a}/{version_a}/diff/branches/{b}/buckets/{b}/flows/{b}/{version_b}`.
+    /// `offset` and `limit` page through the component differences.
+    ///
+    /// # Errors
+    /// Returns `HttpClientError` if the request fails.
+    pub async fn get_version_diff(
+        &self,
+        registry_id: &str,
+        from: &FlowVersion,
+        to: &FlowVersion,
+        offset: Option<u32>,
+        limit: Option<u32>,
+    ) -> anyhow::Result<FlowComparisonEntity> {
+        let mut url = version_diff_url(&self.config.api_base_url, registry_id, from, to)?;
+        if let Some(offset) = offset {
+            url.query_pairs_mut()
+                .append_pair("offset", &offset.to_string());
+        }
+        if let Some(limit) = limit {
+            url.query_pairs_mut()
+                .append_pair("limit", &limit.to_string());
+        }
+        let response = self
+            .client
+            .get_json::<FlowComparisonEntity>(url.as_str())
+            .await?;
+        Ok(response)
+    }
+
+    /// The typed differences between two versions of registry flows.
+    ///
+    /// # Errors
+    /// Returns `HttpClientError` if the request fails.
+    pub async fn diff_versions(
+        &self,
+        registry_id: &str,
+        from: &FlowVersion,
+        to: &FlowVersion,
+    ) -> anyhow::Result<VersionDiff> {
+        let comparison = self
+            .get_version_diff(registry_id, from, to, None, None)
+            .await?;
+        Ok(VersionDiff::from_comparison(
+            registry_id,
+            from,
+            to,
+            &comparison,
+        ))
+    }
+
+    /// Retrieves the version control information of a Process Group.
+    ///
+    /// Sends a `GET` request to `/versions/process-groups/{id}`. The
+    /// `version_control_information` is `None` for groups not under version control.
+    ///
+    /// # Errors
+    /// Returns `HttpClientError` if the request fails.
+    pub async fn get_version_control_information(
+        &self,
+        group_id: &str,
+    ) -> anyhow::Result<VersionControlInformationEntity> {
+        let response = self
+            .client
+            .get_json::<VersionControlInformationEntity>(&format!(
+                "{}/versions/process-groups/{}",
+                self.config.api_base_url, group_id
+            ))
+            .await?;
+        Ok(response)
+    }
+
+    /// Saves the contents of a Process Group to a registry, either starting
+    /// version control or committing a new version.
+    ///
+    /// Sends a `POST` request to `/versions/process-groups/{id}`. The
+    /// `payload` must contain the current `processGroupRevision`.
+    ///
+    /// # Errors
+    /// Returns `HttpClientError` if the request fails.
+    pub async fn post_version_control(
+        &self,
+        group_id: &str,
+        payload: &StartVersionControlRequestEntity,
+    ) -> anyhow::Result<VersionControlInformationEntity> {
+        let response = self
+            .client
+            .post_json::<StartVersionControlRequestEntity, VersionControlInformationEntity>(
+                &format!(
+                    "{}/versions/process-groups/{}",
+                    self.config.api_base_url, group_id
+                ),
+                payload,
+            )
+            .await?;
+        Ok(response)
+    }
+
+    /// Updates the version control information of a Process Group after its
+    /// flow was saved to a registry by other means.
+    ///
+    /// Sends a `PUT` request to `/versions/process-groups/{id}`.
+    ///
+    /// # Errors
+    /// Returns `HttpClientError` if the request fails.
+    pub async fn put_version_control(
+        &self,
+        group_id: &str,
+        payload: &VersionedFlowSnapshotEntity,
+    ) -> anyhow::Result<VersionControlInformationEntity> {
+        let response = self
+            .client
+            .put_json::<VersionedFlowSnapshotEntity, VersionControlInformationEntity>(
+                &format!(
+                    "{}/versions/process-groups/{}",
+                    self.config.api_base_url, group_id
+                ),
+                payload,
+            )
+            .await?;
+        Ok(response)
+    }
+
+    /// Puts a Process Group under version control as a new flow in `bucket_id`.
+    ///
+    /// Reads the current revision of the group and saves its first version.
+    ///
+    /// # Errors
+    /// Returns an error if the group cannot be read or the registry rejects the flow.
+    pub async fn start_version_control(
+        &self,
+        group_id: &str,
+        flow: &VersionedFlowDto,
+    ) -> anyhow::Result<VersionControlInformationEntity> {
+        let group = self.process_group().get_process_group(group_id).await?;
+        self.post_version_control(
+            group_id,
+            &StartVersionControlRequestEntity {
+                disconnected_node_acknowledged: None,
+                process_group_revision: group.revision,
+                versioned_flow: Some(VersionedFlowDto {
+                    action: Some(flow.action.unwrap_or(VersionedFlowDtoAction::Commit)),
+                    ..flow.clone()
+                }),
+            },
+        )
+        .await
+    }
+
+    /// Commits the local modifications of a version-controlled Process Group as
+    /// a new version of its flow.
+    ///
+    /// # Errors
+    /// Returns an error if the group is not under version control or the commit fails.
+    pub async fn commit_version(
+        &self,
+        group_id: &str,
+        comments: Option<&str>,
+    ) -> anyhow::Result<VersionControlInformationEntity> {
+        let current = self.get_version_control_information(group_id).await?;
+        let Some(info) = current.version_control_information else {
+            bail!("Process group {} is not under version control", group_id);
+        };
+        self.post_version_control(
+            group_id,
+            &StartVersionControlRequestEntity {
+                disconnected_node_acknowledged: None,
+                process_group_revision: current.process_group_revision,
+                versioned_flow: Some(VersionedFlowDto {
+                    action: Some(VersionedFlowDtoAction::Commit),
+                    branch: info.branch,
+                    bucket_id: info.bucket_id,
+                    comments: comments.map(str::to_string),
+                    description: info.flow_description,
+                    flow_id: info.flow_id,
+                    flow_name: info.flow_name,
+                    registry_id: info.registry_id,
+                }),
+            },
+        )
+        .await
+    }
+
+    /// Stops version control of a Process Group, keeping its contents.
+    ///
+    /// Sends a `DELETE` request to `/versions/process-groups/{id}`.
+    ///
+    /// # Errors
+    /// Returns `HttpClientError` if the request fails.
+    pub async fn stop_version_control(
+        &self,
+        group_id: &str,
+    ) -> anyhow::Result<VersionControlInformationEntity> {
+        let group = self.process_group().get_process_group(group_id).await?;
+        let Some(version) = group.revision.and_then(|revision| revision.version) else {
+            bail!("Revision was None");
+        };
+        let response = self
+            .client
+            .delete::<JsonResponse<VersionControlInformationEntity>>(&format!(
+                "{}/versions/process-groups/{}?version={}",
+                self.config.api_base_url, group_id, version
+            ))
+            .await?;
+        Ok(response.0)
+    }
+
+    /// Downloads the flow a version-controlled Process Group is tracking, as
+    /// stored in the registry (without local modifications).
+    ///
+    /// Sends a `GET` request to `/versions/process-groups/{id}/download`.
+    ///
+    /// # Errors
+    /// Returns `HttpClientError` if the request fails.
+    pub async fn download_versioned_snapshot(
+        &self,
+        group_id: &str,
+    ) -> anyhow::Result<RegisteredFlowSnapshot> {
+        let response = self
+            .client
+            .get_json::<RegisteredFlowSnapshot>(&format!(
+                "{}/versions/process-groups/{}/download",
+                self.config.api_base_url, group_id
+            ))
+            .await?;
+        Ok(response)
+    }
+
+    /// Retrieves the differences between a version-controlled Process Group
+    /// and the version it tracks.
+    ///
+    /// Sends a `GET` request to `/process-groups/{id}/local-modifications`.
+    ///
+    /// # Errors
+    /// Returns `HttpClientError` if the request fails.
+    pub async fn get_local_modifications(
+        &self,
+        group_id: &str,
+    ) -> anyhow::Result<FlowComparisonEntity> {
+        let response = self
+            .client
+            .get_json::<FlowComparisonEntity>(&format!(
+                "{}/process-groups/{}/local-modifications",
+                self.config.api_base_url, group_id
+            ))
+            .await?;
+        Ok(response)
+    }
+
+    /// The local modifications of a Process Group, grouped into added, removed
+    /// and modified components.
+    ///
+    /// # Errors
+    /// Returns `HttpClientError` if the request fails.
+    pub async fn local_modifications(&self, group_id: &str) -> anyhow::Result<LocalModifications> {
+        let comparison = self.get_local_modifications(group_id).await?;
+        Ok(LocalModifications::from_comparison(&comparison))
+    }
+
+    /// Submits a request to change the version of a Process Group.
+    ///
+    /// Sends a `POST` request to `/versions/update-requests/process-groups/{id}`.
+    ///
+    /// # Errors
+    /// Returns `HttpClientError` if the request fails.
+    pub async fn post_update_request(
+        &self,
+        group_id: &str,
+        payload: &VersionControlInformationEntity,
+    ) -> anyhow::Result<VersionedFlowUpdateRequestEntity> {
+        self.post_request(RequestKind::Update, group_id, payload)
+            .await
+    }
+
+    /// Retrieves the progress of an update request.
+    ///
+    /// Sends a `GET` request to `/versions/update-requests/{id}`.
+    ///
+    /// # Errors
+    /// Returns `HttpClientError` if the request fails.
+    pub async fn get_update_request(
+        &self,
+        request_id: &str,
+    ) -> anyhow::Result<VersionedFlowUpdateRequestEntity> {
+        self.get_request(RequestKind::Update, request_id).await
+    }
+
+    /// Deletes a (finished) update request.
+    ///
+    /// Sends a `DELETE` request to `/versions/update-requests/{id}`.
+    ///
+    /// # Errors
+    /// Returns `HttpClientError` if the request fails.
+    pub async fn delete_update_request(
+        &self,
+        request_id: &str,
+    ) -> anyhow::Result<VersionedFlowUpdateRequestEntity> {
+        self.delete_request(RequestKind::Update, request_id).await
+    }
+
+    /// Submits a request to revert the local modifications of a Process Group.
+    ///
+    /// Sends a `POST` request to `/versions/revert-requests/process-groups/{id}`.
+    ///
+    /// # Errors
+    /// Returns `HttpClientError` if the request fails.
+    pub async fn post_revert_request(
+        &self,
+        group_id: &str,
+        payload: &VersionControlInformationEntity,
+    ) -> anyhow::Result<VersionedFlowUpdateRequestEntity> {
+        self.post_request(RequestKind::Revert, group_id, payload)
+            .await
+    }
+
+    /// Retrieves the progress of a revert request.
+    ///
+    /// Sends a `GET` request to `/versions/revert-requests/{id}`.
+    ///
+    /// # Errors
+    /// Returns `HttpClientError` if the request fails.
+    pub async fn get_revert_request(
+        &self,
+        request_id: &str,
+    ) -> anyhow::Result<VersionedFlowUpdateRequestEntity> {
+        self.get_request(RequestKind::Revert, request_id).await
+    }
+
+    /// Deletes a (finished) revert request.
+    ///
+    /// Sends a `DELETE` request to `/versions/revert-requests/{id}`.
+    ///
+    /// # Errors
+    /// Returns `HttpClientError` if the request fails.
+    pub async fn delete_revert_request(
+        &self,
+        request_id: &str,
+    ) -> anyhow::Result<VersionedFlowUpdateRequestEntity> {
+        self.delete_request(RequestKind::Revert, request_id).await
+    }
+
+    /// Changes a version-controlled Process Group to `version` of its flow and
+    /// waits until NiFi finishes.
+    ///
+    /// Runs the whole update-request flow: reads the current version control
+    /// information, submits the request, polls it until it completes and
+    /// deletes it afterwards.
+    ///
+    /// # Errors
+    /// Returns an error if the group is not under version control, if any
+    /// request fails, if NiFi reports a failure reason, or if the request does
+    /// not complete within `poll.timeout`.
+    pub async fn change_version(
+        &self,
+        group_id: &str,
+        version: &str,
+        poll: PollOptions,
+    ) -> anyhow::Result<VersionedFlowUpdateRequestDto> {
+        let mut current = self.get_version_control_information(group_id).await?;
+        let Some(info) = current.version_control_information.as_mut() else {
+            bail!("Process group {} is not under version control", group_id);
+        };
+        info.version = Some(version.to_string());
+        self.run_request(RequestKind::Update, group_id, &current, poll)
+            .await
+    }
+
+    /// Reverts the local modifications of a version-controlled Process Group
+    /// and waits until NiFi finishes.
+    ///
+    /// # Errors
+    /// Returns an error if the group is not under version control, if any
+    /// request fails, if NiFi reports a failure reason, or if the request does
+    /// not complete within `poll.timeout`.
+    pub async fn revert_local_modifications(
+        &self,
+        group_id: &str,
+        poll: PollOptions,
+    ) -> anyhow::Result<VersionedFlowUpdateRequestDto> {
+        let current = self.get_version_control_information(group_id).await?;
+        if current.version_control_information.is_none() {
+            bail!("Process group {} is not under version control", group_id);
+        }
+        self.run_request(RequestKind::Revert, group_id, &current, poll)
+            .await
+    }
+
+    fn process_group(&self) -> ProcessGroup {
+        ProcessGroup::new(self.client.clone(), self.config.clone())
+    }
+
+    async fn post_request(
+        &self,
+        kind: RequestKind,
+        group_id: &str,
+        payload: &VersionControlInformationEntity,
+    ) -> anyhow::Result<VersionedFlowUpdateRequestEntity> {
+        let response = self
+            .client
+            .post_json::<VersionControlInformationEntity, VersionedFlowUpdateRequestEntity>(
+                &format!(
+                    "{}/versions/{}/process-groups/{}",
+                    self.config.api_base_url,
+                    kind.path(),
+                    group_id
+                ),
+                payload,
+            )
+            .await?;
+        Ok(response)
+    }
+
+    async fn get_request(
+        &self,
+        kind: RequestKind,
+        request_id: &str,
+    ) -> anyhow::Result<VersionedFlowUpdateRequestEntity> {
+        let response = self
+            .client
+            .get_json::<VersionedFlowUpdateRequestEntity>(&format!(
+                "{}/versions/{}/{}",
+                self.config.api_base_url,
+                kind.path(),
+                request_id
+            ))
+            .await?;
+        Ok(response)
+    }
+
+    async fn delete_request(
+        &self,
+        kind: RequestKind,
+        request_id: &str,
+    ) -> anyhow::Result<VersionedFlowUpdateRequestEntity> {
+        let response = self
+            .client
+            .delete::<JsonResponse<VersionedFlowUpdateRequestEntity>>(&format!(
+                "{}/versions/{}/{}",
+                self.config.api_base_url,
+                kind.path(),
+                request_id
+            ))
+            .await?;
+        Ok(response.0)
+    }
+
+    /// Submits, polls and deletes an update or revert request.
+    async fn run_request(
+        &self,
+        kind: RequestKind,
+        group_id: &str,
+        payload: &VersionControlInformationEntity,
+        poll: PollOptions,
+    ) -> anyhow::Result<VersionedFlowUpdateRequestDto> {
+        run_async_request(
+            &format!("{:?} request for process group {}", kind, group_id),
+            poll,
+            async {
+                let submitted = self.post_request(kind, group_id, payload).await?;
+                Ok(submitted.request.and_then(|request| request.request_id))
+            },
+            |request_id| async move {
+                Ok(self
+                    .get_request(kind, &request_id)
+                    .await?
+                    .request
+                    .unwrap_or_default())
+            },
+            |request_id| async move { self.delete_request(kind, &request_id).await },
+        )
+        .await
+    }
+}
+
+/// Appends `?branch=` (URL-encoded) to `url` when a branch is given.
+fn with_branch(url: String, branch: Option<&str>) -> anyhow::Result<String> {
+    match branch {
+        Some(branch) => Ok(Url::parse_with_params(&url, [("branch", branch)])?.to_string()),
+        None => Ok(url),
+    }
+}
+
+/// The URL of the diff between two registry flow versions. Every id is
+/// pushed as its own path segment, so a branch like `feature/a` stays one
+/// segment.
+fn version_diff_url(
+    base: &str,
+    registry_id: &str,
+    from: &FlowVersion,
+    to: &FlowVersion,
+) -> anyhow::Result<Url> {
+    let mut url = Url::parse(base)?;
+    {
+        let Ok(mut segments) = url.path_segments_mut() else {
+            bail!("{} cannot be a base URL", base);
+        };
+        segments
+            .pop_if_empty()
+            .extend(["flow", "registries", registry_id]);
+        for (index, version) in [from, to].into_iter().enumerate() {
+            if index > 0 {
+                segments.push("diff");
+            }
+            segments.extend([
+                "branches",
+                &version.branch,
+                "buckets",
+                &version.bucket_id,
+                "flows",
+                &version.flow_id,
+                &version.version,
+            ]);
+        }
+    }
+    Ok(url)
+}
+
+impl RequestStatus for VersionedFlowUpdateRequestDto {
+    fn is_finished(&self) -> bool {
+        self.complete.unwrap_or(false)
+    }
+
+    fn failure_reason(&self) -> Option<&str> {
+        self.failure_reason.as_deref()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::proxy::v260::access::Access;
+    use tracing_test::traced_test;
+
+    #[test]
+    fn test_with_branch() {
+        let url = with_branch(
+            "https://nifi/flow/registries/r/buckets".to_string(),
+            Some("feature/a b"),
+        )
+        .unwrap();
+        assert_eq!(
+            url,
+            "https://nifi/flow/registries/r/buckets?branch=feature%2Fa+b"
+        );
+    }
+
+    #[test]
+    fn test_version_diff_url() {
+        let version = |branch: &str, version: &str| FlowVersion {
+            branch: branch.to_string(),
+            bucket_id: "bucket 1".to_string(),
+            flow_id: "flow".to_string(),
+            version: version.to_string(),
+        };
+        let url = version_diff_url(
+            "https://nifi/nifi-api",
+            "r",
+            &version("main", "1"),
+            &version("feature/a", "2"),
+        )
+        .unwrap();
+        assert_eq!(
+            url.as_str(),
+            "https://nifi/nifi-api/flow/registries/r/branches/main/buckets/bucket%201/flows/flow/1/diff/branches/feature%2Fa/buckets/bucket%201/flows/flow/2"
+        );
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    async fn test_get_registry_clients() {
+        let client = Arc::new(HttpClient::new());
+        let config = Arc::new(Config::default()); // Assumes correct credentials
+        let access = Access::new(client.clone(), config.clone());
+        let _ = access.get_access_token().await;
+
+        let versions = Versions::new(client.clone(), config.clone());
+        let registries = versions.get_registry_clients().await;
+        assert!(
+            registries.is_ok(),
+            "test_get_registry_clients call error: {:?}",
+            registries
+        );
+    }
+}
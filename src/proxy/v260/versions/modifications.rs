@@ -0,0 +1,283 @@
+//! Typed view of the local modifications of a version-controlled Process Group.
+//!
+//! `/process-groups/{id}/local-modifications` returns a flat
+//! `FlowComparisonEntity`: one `ComponentDifferenceDto` per component, each
+//! holding free-text `DifferenceDto`s. `LocalModifications` groups them into
+//! added, removed and modified components and extracts property changes, so
+//! they can be printed (`Display`) or serialized to JSON.
+
+use crate::proxy::v260::api::{ComponentDifferenceDto, DifferenceDto, FlowComparisonEntity};
+use serde::Serialize;
+use std::fmt;
+
+/// The type of a single difference, from `DifferenceDto::difference_type`.
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum DifferenceKind {
+    ComponentAdded,
+    ComponentRemoved,
+    PropertyAdded,
+    PropertyRemoved,
+    PropertyChanged,
+    PropertyParameterized,
+    PropertyParameterizationRemoved,
+    PositionChanged,
+    NameChanged,
+    CommentsChanged,
+    ScheduledStateChanged,
+    /// Any other difference type, as reported by NiFi.
+    Other(String),
+}
+
+impl DifferenceKind {
+    /// Maps the display name NiFi uses for the difference type.
+    pub fn parse(difference_type: &str) -> Self {
+        match difference_type {
+            "Component Added" => DifferenceKind::ComponentAdded,
+            "Component Removed" => DifferenceKind::ComponentRemoved,
+            "Property Added" => DifferenceKind::PropertyAdded,
+            "Property Removed" => DifferenceKind::PropertyRemoved,
+            "Property Value Changed" => DifferenceKind::PropertyChanged,
+            "Property Parameterized" => DifferenceKind::PropertyParameterized,
+            "Property Parameterization Removed" => DifferenceKind::PropertyParameterizationRemoved,
+            "Position Changed" => DifferenceKind::PositionChanged,
+            "Name Changed" => DifferenceKind::NameChanged,
+            "Comments Changed" => DifferenceKind::CommentsChanged,
+            "Scheduled State Changed" => DifferenceKind::ScheduledStateChanged,
+            other => DifferenceKind::Other(other.to_string()),
+        }
+    }
+
+    /// `true` for differences about a single property.
+    pub fn is_property(&self) -> bool {
+        matches!(
+            self,
+            DifferenceKind::PropertyAdded
+                | DifferenceKind::PropertyRemoved
+                | DifferenceKind::PropertyChanged
+                | DifferenceKind::PropertyParameterized
+                | DifferenceKind::PropertyParameterizationRemoved
+        )
+    }
+}
+
+/// A changed property, extracted from the difference text when possible.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct PropertyDiff {
+    pub kind: DifferenceKind,
+    pub name: Option<String>,
+    /// The value in the versioned flow.
+    pub from: Option<String>,
+    /// The value in the local flow.
+    pub to: Option<String>,
+    /// The difference as NiFi described it.
+    pub description: String,
+}
+
+/// A difference that is not about a property (position, name, state...).
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct OtherDiff {
+    pub kind: DifferenceKind,
+    pub description: String,
+}
+
+/// A component with local modifications.
+#[derive(Serialize, Debug, Clone, PartialEq, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct ComponentChange {
+    pub component_id: Option<String>,
+    pub component_name: Option<String>,
+    pub component_type: Option<String>,
+    pub process_group_id: Option<String>,
+    pub properties: Vec<PropertyDiff>,
+    pub other: Vec<OtherDiff>,
+}
+
+/// The local modifications of a Process Group, grouped by kind of change.
+#[derive(Serialize, Debug, Clone, PartialEq, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct LocalModifications {
+    pub added: Vec<ComponentChange>,
+    pub removed: Vec<ComponentChange>,
+    pub modified: Vec<ComponentChange>,
+}
+
+impl LocalModifications {
+    /// Groups the differences of a `FlowComparisonEntity`.
+    pub fn from_comparison(comparison: &FlowComparisonEntity) -> Self {
+        let mut report = Self::default();
+        for component in comparison.component_differences.iter().flatten() {
+            let kinds: Vec<DifferenceKind> = component
+                .differences
+                .iter()
+                .map(|difference| {
+                    DifferenceKind::parse(difference.difference_type.as_deref().unwrap_or(""))
+                })
+                .collect();
+            let change = component_change(component);
+            if kinds.contains(&DifferenceKind::ComponentAdded) {
+                report.added.push(change);
+            } else if kinds.contains(&DifferenceKind::ComponentRemoved) {
+                report.removed.push(change);
+            } else {
+                report.modified.push(change);
+            }
+        }
+        report
+    }
+
+    /// `true` when the group has no local modifications.
+    pub fn is_clean(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.modified.is_empty()
+    }
+}
+
+impl fmt::Display for LocalModifications {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_clean() {
+            return writeln!(f, "No local modifications");
+        }
+        for (sign, title, changes) in [
+            ('+', "Added", &self.added),
+            ('-', "Removed", &self.removed),
+            ('~', "Modified", &self.modified),
+        ] {
+            if changes.is_empty() {
+                continue;
+            }
+            writeln!(f, "{} ({}):", title, changes.len())?;
+            for change in changes {
+                writeln!(
+                    f,
+                    "  {} {} {} ({})",
+                    sign,
+                    change.component_type.as_deref().unwrap_or("Component"),
+                    change.component_name.as_deref().unwrap_or("-"),
+                    change.component_id.as_deref().unwrap_or("-"),
+                )?;
+                for property in &change.properties {
+                    match (&property.name, &property.from, &property.to) {
+                        (Some(name), from, to) if from.is_some() || to.is_some() => writeln!(
+                            f,
+                            "      {}: {} -> {}",
+                            name,
+                            from.as_deref().unwrap_or("(unset)"),
+                            to.as_deref().unwrap_or("(unset)"),
+                        )?,
+                        _ => writeln!(f, "      {}", property.description)?,
+                    }
+                }
+                if sign == '~' {
+                    for other in &change.other {
+                        writeln!(f, "      {}", other.description)?;
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+fn component_change(component: &ComponentDifferenceDto) -> ComponentChange {
+    let mut change = ComponentChange {
+        component_id: component.component_id.clone(),
+        component_name: component.component_name.clone(),
+        component_type: component.component_type.clone(),
+        process_group_id: component.process_group_id.clone(),
+        ..Default::default()
+    };
+    for difference in &component.differences {
+        let kind = DifferenceKind::parse(difference.difference_type.as_deref().unwrap_or(""));
+        let description = difference.difference.clone().unwrap_or_default();
+        if kind.is_property() {
+            change.properties.push(property_diff(kind, difference));
+        } else {
+            change.other.push(OtherDiff { kind, description });
+        }
+    }
+    change
+}
+
+/// Extracts the property name and values from texts such as
+/// `Property 'Input Directory' was changed from '/tmp' to '/data'`.
+fn property_diff(kind: DifferenceKind, difference: &DifferenceDto) -> PropertyDiff {
+    let description = difference.difference.clone().unwrap_or_default();
+    let quoted = quoted_values(&description);
+    let lower = description.to_lowercase();
+    let (from, to) = if lower.contains(" from ") && lower.contains(" to ") && quoted.len() >= 3 {
+        (Some(quoted[1].clone()), Some(quoted[2].clone()))
+    } else {
+        (None, None)
+    };
+    PropertyDiff {
+        kind,
+        name: quoted.first().cloned(),
+        from,
+        to,
+        description,
+    }
+}
+
+/// The substrings enclosed in single quotes, in order.
+fn quoted_values(text: &str) -> Vec<String> {
+    text.split('\'')
+        .skip(1)
+        .step_by(2)
+        .map(str::to_string)
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn component(name: &str, differences: &[(&str, &str)]) -> ComponentDifferenceDto {
+        ComponentDifferenceDto {
+            component_id: Some(format!("{}-id", name)),
+            component_name: Some(name.to_string()),
+            component_type: Some("Processor".to_string()),
+            differences: differences
+                .iter()
+                .map(|(kind, text)| DifferenceDto {
+                    difference_type: Some(kind.to_string()),
+                    difference: Some(text.to_string()),
+                })
+                .collect(),
+            process_group_id: Some("group".to_string()),
+        }
+    }
+
+    #[test]
+    fn test_local_modifications_report() {
+        let comparison = FlowComparisonEntity {
+            component_differences: Some(vec![
+                component("new", &[("Component Added", "Processor was added")]),
+                component("old", &[("Component Removed", "Processor was removed")]),
+                component(
+                    "fetch",
+                    &[
+                        (
+                            "Property Value Changed",
+                            "Property 'URL' was changed from 'http://a' to 'http://b'",
+                        ),
+                        ("Position Changed", "Position was changed"),
+                    ],
+                ),
+            ]),
+        };
+
+        let report = LocalModifications::from_comparison(&comparison);
+
+        assert!(!report.is_clean());
+        assert_eq!(report.added.len(), 1);
+        assert_eq!(report.removed.len(), 1);
+        let fetch = &report.modified[0];
+        assert_eq!(fetch.properties[0].name.as_deref(), Some("URL"));
+        assert_eq!(fetch.properties[0].from.as_deref(), Some("http://a"));
+        assert_eq!(fetch.properties[0].to.as_deref(), Some("http://b"));
+        assert_eq!(fetch.other[0].kind, DifferenceKind::PositionChanged);
+        assert!(report.to_string().contains("URL: http://a -> http://b"));
+    }
+}